@@ -128,6 +128,90 @@ int main() {
 
         handle_error(citeproc_rs_driver_format_bibliography(driver, &rendered));
         printf("bibliography: \n%s\n", rendered);
+        assert(strstr(rendered, "<i>The Origins of Totalitarianism</i>") != NULL);
+
+        // switch to plain text; everything is recomputed in the new format
+        handle_error(citeproc_rs_driver_set_output_format(driver, CITEPROC_RS_OUTPUT_FORMAT_PLAIN));
+
+        handle_error(citeproc_rs_driver_format_cluster(driver, id, &rendered));
+        printf("cluster %d (plain): %s\n", id, rendered);
+        assert(strcmp(rendered, "prefix: Hannah Arendt, 1951; Hannah Arendt, 1951") == 0);
+
+        handle_error(citeproc_rs_driver_format_bibliography(driver, &rendered));
+        printf("bibliography (plain): \n%s\n", rendered);
+        assert(strcmp(rendered, "Hannah Arendt, The Origins of Totalitarianism\n") == 0);
+
+        // two more clusters, each with a single cite, then render the whole document at once
+        for (citeproc_rs_cluster_id next = 2; next <= 3; next++) {
+                handle_error(citeproc_rs_cluster_reset(cluster, next));
+                citeproc_rs_cluster_cite_new(cluster, ref_id, ref_id_len);
+                handle_error(citeproc_rs_driver_insert_cluster(driver, cluster));
+        }
+        citeproc_rs_cluster_position *three = malloc(3 * sizeof(citeproc_rs_cluster_position));
+        for (uint32_t i = 0; i < 3; i++) {
+                three[i] = (citeproc_rs_cluster_position) {
+                        .id = i + 1,
+                        .is_preview_marker = false,
+                        .is_note = true,
+                        .note_number = i + 1,
+                };
+        }
+        handle_error(citeproc_rs_driver_set_cluster_order(driver, three, 3));
+        free(three);
+
+        handle_error(citeproc_rs_driver_all_clusters(driver, &rendered));
+        printf("all clusters: %s\n", rendered);
+        assert(strcmp(rendered, "["
+                "{\"id\":1,\"output\":\"prefix: Hannah Arendt, 1951; Hannah Arendt, 1951\"},"
+                "{\"id\":2,\"output\":\"Hannah Arendt, 1951\"},"
+                "{\"id\":3,\"output\":\"Hannah Arendt, 1951\"}"
+                "]") == 0);
+        citeproc_rs_cluster_free(cluster);
+
+        // a second driver with a German style and no fetch callback; push the locale in ourselves
+        LIT_LEN(de_style, "<style xmlns=\"http://purl.org/net/xbiblio/csl\" class=\"note\" version=\"1.0\" default-locale=\"de-DE\">"
+                "<info><id>id</id><title>title</title><updated>2015-10-10T23:31:02+00:00</updated></info>"
+                "<citation><layout /></citation>"
+                "<bibliography><layout><date variable=\"issued\">"
+                "<date-part name=\"month\" suffix=\" \" /><date-part name=\"year\" />"
+                "</date></layout></bibliography>"
+                "</style>");
+        LIT_LEN(de_de, "<locale xmlns=\"http://purl.org/net/xbiblio/csl\" version=\"1.0\" xml:lang=\"de-DE\">"
+                "<terms><term name=\"month-03\">März</term></terms>"
+                "</locale>");
+        citeproc_rs_init_options de_init = {
+                .style = de_style,
+                .style_len = de_style_len,
+                .format = CITEPROC_RS_OUTPUT_FORMAT_PLAIN,
+                .buffer_ops = buffer_ops,
+        };
+        citeproc_rs_driver *de_driver = citeproc_rs_driver_new(de_init);
+        if (!de_driver) {
+                citeproc_rs_last_error_utf8(buffer_ops, &err);
+                printf("err creating driver: %s\n", err);
+                return 1;
+        }
+        const char *march_json = "{ \"id\": \"march\", \"type\": \"book\", \"issued\": { \"date-parts\": [[1951, 3]] } }";
+        size_t march_json_len = strlen(march_json);
+
+        // without de-DE, we fall back to en-US
+        handle_error(citeproc_rs_driver_preview_reference(
+                                de_driver, march_json, march_json_len,
+                                CITEPROC_RS_OUTPUT_FORMAT_PLAIN, &rendered));
+        assert(strcmp(rendered, "March 1951") == 0);
+
+        LIT_LEN(de_lang, "de-DE");
+        handle_error(citeproc_rs_driver_store_locale(de_driver, de_lang, de_lang_len, de_de, de_de_len));
+        handle_error(citeproc_rs_driver_preview_reference(
+                                de_driver, march_json, march_json_len,
+                                CITEPROC_RS_OUTPUT_FORMAT_PLAIN, &rendered));
+        printf("previewed with stored de-DE: %s\n", rendered);
+        assert(strcmp(rendered, "März 1951") == 0);
+
+        LIT_LEN(bad_lang, "not a language");
+        code = citeproc_rs_driver_store_locale(de_driver, bad_lang, bad_lang_len, de_de, de_de_len);
+        assert(code == CITEPROC_RS_ERROR_CODE_INVALID_LANG);
+        citeproc_rs_driver_free(de_driver);
 
         // we allocated these with cstring in the buffer_write_callback
         // if not though, calling free on NULL is fine
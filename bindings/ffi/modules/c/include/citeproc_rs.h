@@ -20,6 +20,7 @@ enum citeproc_rs_error_code {
   CITEPROC_RS_ERROR_CODE_CLUSTER_NOT_IN_FLOW = 10,
   CITEPROC_RS_ERROR_CODE_INVALID_STYLE = 11,
   CITEPROC_RS_ERROR_CODE_SET_LOGGER = 12,
+  CITEPROC_RS_ERROR_CODE_INVALID_LANG = 13,
 };
 typedef int32_t citeproc_rs_error_code;
 
@@ -89,6 +90,7 @@ enum citeproc_rs_output_format {
   CITEPROC_RS_OUTPUT_FORMAT_HTML,
   CITEPROC_RS_OUTPUT_FORMAT_RTF,
   CITEPROC_RS_OUTPUT_FORMAT_PLAIN,
+  CITEPROC_RS_OUTPUT_FORMAT_JATS,
 };
 typedef uint8_t citeproc_rs_output_format;
 
@@ -246,6 +248,18 @@ struct citeproc_rs_driver *citeproc_rs_driver_new(struct citeproc_rs_init_option
  */
 void citeproc_rs_driver_free(struct citeproc_rs_driver *driver);
 
+/**
+ * Changes the output format, using [citeproc::Processor::set_output_format]. Everything will
+ * be recomputed in the new format, so subsequent calls to e.g.
+ * [citeproc_rs_driver_format_cluster] will use it.
+ *
+ * # Safety
+ *
+ * Driver must be a valid pointer to a Driver.
+ */
+citeproc_rs_error_code citeproc_rs_driver_set_output_format(struct citeproc_rs_driver *driver,
+                                                            citeproc_rs_output_format format);
+
 /**
  * [citeproc::Processor::set_cluster_order], but using an ffi-compatible [ClusterPosition]
  *
@@ -270,6 +284,20 @@ citeproc_rs_error_code citeproc_rs_driver_format_cluster(struct citeproc_rs_driv
                                                          citeproc_rs_cluster_id cluster_id,
                                                          void *user_buf);
 
+/**
+ * Writes every cluster in the document into a buffer as JSON, in document order, like
+ * `[{ "id": 1, "output": "..." }, ...]`. Only clusters given a position by
+ * [citeproc_rs_driver_set_cluster_order] are included. Useful for rendering a whole document
+ * at once, e.g. after opening it.
+ *
+ * # Safety
+ *
+ * Driver must be a valid pointer to a Driver, and `user_buf` must match the expected user
+ * data in the BufferOps struct passed to driver's init call.
+ */
+citeproc_rs_error_code citeproc_rs_driver_all_clusters(struct citeproc_rs_driver *driver,
+                                                       void *user_buf);
+
 /**
  * Writes a bibliography into a buffer, using [citeproc::Processor::get_bibliography]
  */
@@ -308,6 +336,25 @@ citeproc_rs_error_code citeproc_rs_driver_insert_reference(struct citeproc_rs_dr
                                                            const char *ref_json,
                                                            uintptr_t ref_json_len);
 
+/**
+ * Stores a locale's XML for a language, using [citeproc::Processor::store_locales]. Use this
+ * to supply locales you already have, at any time after [citeproc_rs_driver_new], instead of (or
+ * as well as) the locale fetch callback. Returns an error code if the language tag is invalid or
+ * the XML does not parse cleanly.
+ *
+ * # Safety
+ *
+ * `driver` must be a valid pointer to a Driver.
+ *
+ * `lang`/`lang_len` and `locale_xml`/`locale_xml_len` must each refer to a UTF-8 byte array of
+ * that length.
+ */
+citeproc_rs_error_code citeproc_rs_driver_store_locale(struct citeproc_rs_driver *driver,
+                                                      const char *lang,
+                                                      uintptr_t lang_len,
+                                                      const char *locale_xml,
+                                                      uintptr_t locale_xml_len);
+
 /**
  * Clear the last error (thread local).
  */
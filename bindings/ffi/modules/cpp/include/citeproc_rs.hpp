@@ -23,6 +23,7 @@ enum class ErrorCode : int32_t {
   cluster_not_in_flow = 10,
   invalid_style = 11,
   set_logger = 12,
+  invalid_lang = 13,
 };
 
 enum class LevelFilter : uintptr_t {
@@ -78,6 +79,7 @@ enum class OutputFormat : uint8_t {
   html,
   rtf,
   plain,
+  jats,
 };
 
 /// An opaque, boxed wrapper for a [citeproc::prelude::Cluster].
@@ -207,6 +209,16 @@ ErrorCode citeproc_rs_locale_slot_write(LocaleSlot *slot,
 /// The driver must either be from [citeproc_rs_driver_new] or be null.
  void citeproc_rs_driver_free(Driver *driver);
 
+/// Changes the output format, using [citeproc::Processor::set_output_format]. Everything will
+/// be recomputed in the new format, so subsequent calls to e.g.
+/// [citeproc_rs_driver_format_cluster] will use it.
+///
+/// # Safety
+///
+/// Driver must be a valid pointer to a Driver.
+
+ErrorCode citeproc_rs_driver_set_output_format(Driver *driver, OutputFormat format);
+
 /// [citeproc::Processor::set_cluster_order], but using an ffi-compatible [ClusterPosition]
 ///
 /// # Safety
@@ -226,6 +238,17 @@ ErrorCode citeproc_rs_driver_set_cluster_order(Driver *driver,
 ///
  ErrorCode citeproc_rs_driver_format_cluster(Driver *driver, ClusterId cluster_id, void *user_buf);
 
+/// Writes every cluster in the document into a buffer as JSON, in document order, like
+/// `[{ "id": 1, "output": "..." }, ...]`. Only clusters given a position by
+/// [citeproc_rs_driver_set_cluster_order] are included. Useful for rendering a whole document
+/// at once, e.g. after opening it.
+///
+/// # Safety
+///
+/// Driver must be a valid pointer to a Driver, and `user_buf` must match the expected user
+/// data in the BufferOps struct passed to driver's init call.
+ ErrorCode citeproc_rs_driver_all_clusters(Driver *driver, void *user_buf);
+
 /// Writes a bibliography into a buffer, using [citeproc::Processor::get_bibliography]
  ErrorCode citeproc_rs_driver_format_bibliography(Driver *driver, void *user_buf);
 
@@ -259,6 +282,24 @@ ErrorCode citeproc_rs_driver_insert_reference(Driver *driver,
                                               const char *ref_json,
                                               uintptr_t ref_json_len);
 
+/// Stores a locale's XML for a language, using [citeproc::Processor::store_locales]. Use this
+/// to supply locales you already have, at any time after [citeproc_rs_driver_new], instead of (or
+/// as well as) the locale fetch callback. Returns an error code if the language tag is invalid or
+/// the XML does not parse cleanly.
+///
+/// # Safety
+///
+/// `driver` must be a valid pointer to a Driver.
+///
+/// `lang`/`lang_len` and `locale_xml`/`locale_xml_len` must each refer to a UTF-8 byte array of
+/// that length.
+
+ErrorCode citeproc_rs_driver_store_locale(Driver *driver,
+                                          const char *lang,
+                                          uintptr_t lang_len,
+                                          const char *locale_xml,
+                                          uintptr_t locale_xml_len);
+
 /// Clear the last error (thread local).
  void citeproc_rs_last_error_clear();
 
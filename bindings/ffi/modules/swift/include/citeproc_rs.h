@@ -23,6 +23,7 @@ typedef CF_ENUM(int32_t, CRErrorCode) {
   CRErrorCode_ClusterNotInFlow = 10,
   CRErrorCode_InvalidStyle = 11,
   CRErrorCode_SetLogger = 12,
+  CRErrorCode_InvalidLang = 13,
 };
 
 typedef CF_ENUM(uintptr_t, CRLevelFilter) {
@@ -88,6 +89,7 @@ typedef CF_ENUM(uint8_t, CROutputFormat) {
   CROutputFormat_Html,
   CROutputFormat_Rtf,
   CROutputFormat_Plain,
+  CROutputFormat_Jats,
 };
 
 /**
@@ -244,6 +246,18 @@ struct CRDriver *citeproc_rs_driver_new(struct CRInitOptions init) CF_SWIFT_NAME
  */
 void citeproc_rs_driver_free(struct CRDriver *driver) CF_SWIFT_NAME(citeproc_rs_driver_free(driver:));
 
+/**
+ * Changes the output format, using [citeproc::Processor::set_output_format]. Everything will
+ * be recomputed in the new format, so subsequent calls to e.g.
+ * [citeproc_rs_driver_format_cluster] will use it.
+ *
+ * # Safety
+ *
+ * Driver must be a valid pointer to a Driver.
+ */
+CRErrorCode citeproc_rs_driver_set_output_format(struct CRDriver *driver,
+                                                 CROutputFormat format) CF_SWIFT_NAME(citeproc_rs_driver_set_output_format(driver:format:));
+
 /**
  * [citeproc::Processor::set_cluster_order], but using an ffi-compatible [ClusterPosition]
  *
@@ -268,6 +282,20 @@ CRErrorCode citeproc_rs_driver_format_cluster(struct CRDriver *driver,
                                               CRClusterId cluster_id,
                                               void *user_buf) CF_SWIFT_NAME(citeproc_rs_driver_format_cluster(driver:cluster_id:user_buf:));
 
+/**
+ * Writes every cluster in the document into a buffer as JSON, in document order, like
+ * `[{ "id": 1, "output": "..." }, ...]`. Only clusters given a position by
+ * [citeproc_rs_driver_set_cluster_order] are included. Useful for rendering a whole document
+ * at once, e.g. after opening it.
+ *
+ * # Safety
+ *
+ * Driver must be a valid pointer to a Driver, and `user_buf` must match the expected user
+ * data in the BufferOps struct passed to driver's init call.
+ */
+CRErrorCode citeproc_rs_driver_all_clusters(struct CRDriver *driver,
+                                            void *user_buf) CF_SWIFT_NAME(citeproc_rs_driver_all_clusters(driver:user_buf:));
+
 /**
  * Writes a bibliography into a buffer, using [citeproc::Processor::get_bibliography]
  */
@@ -306,6 +334,25 @@ CRErrorCode citeproc_rs_driver_insert_reference(struct CRDriver *driver,
                                                 const char *ref_json,
                                                 uintptr_t ref_json_len) CF_SWIFT_NAME(citeproc_rs_driver_insert_reference(driver:ref_json:ref_json_len:));
 
+/**
+ * Stores a locale's XML for a language, using [citeproc::Processor::store_locales]. Use this
+ * to supply locales you already have, at any time after [citeproc_rs_driver_new], instead of (or
+ * as well as) the locale fetch callback. Returns an error code if the language tag is invalid or
+ * the XML does not parse cleanly.
+ *
+ * # Safety
+ *
+ * `driver` must be a valid pointer to a Driver.
+ *
+ * `lang`/`lang_len` and `locale_xml`/`locale_xml_len` must each refer to a UTF-8 byte array of
+ * that length.
+ */
+CRErrorCode citeproc_rs_driver_store_locale(struct CRDriver *driver,
+                                           const char *lang,
+                                           uintptr_t lang_len,
+                                           const char *locale_xml,
+                                           uintptr_t locale_xml_len) CF_SWIFT_NAME(citeproc_rs_driver_store_locale(driver:lang:lang_len:locale_xml:locale_xml_len:));
+
 CRErrorCode test_panic(void) CF_SWIFT_NAME(test_panic());
 
 CRErrorCode test_panic_poison_driver(struct CRDriver *_driver) CF_SWIFT_NAME(test_panic_poison_driver(_driver:));
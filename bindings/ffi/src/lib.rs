@@ -57,6 +57,8 @@ pub enum FFIError {
     InvalidStyle(#[from] csl::StyleError),
     #[error("could not set logger: {0}")]
     SetLogger(#[from] log::SetLoggerError),
+    #[error("invalid language tag: {0:?}")]
+    InvalidLang(String),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -75,6 +77,7 @@ pub enum ErrorCode {
     ClusterNotInFlow = 10,
     InvalidStyle = 11,
     SetLogger = 12,
+    InvalidLang = 13,
 }
 
 impl FFIError {
@@ -101,6 +104,7 @@ impl FFIError {
             Self::ClusterNotInFlow(_) => ErrorCode::ClusterNotInFlow,
             Self::InvalidStyle(_) => ErrorCode::InvalidStyle,
             Self::SetLogger(_) => ErrorCode::SetLogger,
+            Self::InvalidLang(_) => ErrorCode::InvalidLang,
         }
     }
 }
@@ -218,6 +222,7 @@ pub enum OutputFormat {
     Html,
     Rtf,
     Plain,
+    Jats,
 }
 
 #[repr(C)]
@@ -236,6 +241,7 @@ impl OutputFormat {
             OutputFormat::Html => rust::SupportedFormat::Html,
             OutputFormat::Rtf => rust::SupportedFormat::Rtf,
             OutputFormat::Plain => rust::SupportedFormat::Plain,
+            OutputFormat::Jats => rust::SupportedFormat::Jats,
         }
     }
 }
@@ -287,6 +293,24 @@ ffi_fn_nullify! {
     }
 }
 
+ffi_fn_nullify! {
+    /// Changes the output format, using [citeproc::Processor::set_output_format]. Everything will
+    /// be recomputed in the new format, so subsequent calls to e.g.
+    /// [citeproc_rs_driver_format_cluster] will use it.
+    ///
+    /// # Safety
+    ///
+    /// Driver must be a valid pointer to a Driver.
+    @safety unsafe fn citeproc_rs_driver_set_output_format(#[nullify_on_panic] driver: *mut Driver, format: OutputFormat) -> ErrorCode {
+        result_to_error_code(|| {
+            let driver = unsafe { borrow_raw_ptr_mut(driver) } ?;
+            let proc = driver.processor.as_mut().ok_or(FFIError::Poisoned)?;
+            proc.set_output_format(format.to_supported_format(), Default::default());
+            Ok(ErrorCode::None)
+        })
+    }
+}
+
 #[repr(C)]
 pub struct ClusterPosition {
     pub is_preview_marker: bool,
@@ -354,6 +378,49 @@ ffi_fn_nullify! {
     }
 }
 
+ffi_fn_nullify! {
+    /// Writes every cluster in the document into a buffer as JSON, in document order, like
+    /// `[{ "id": 1, "output": "..." }, ...]`. Only clusters given a position by
+    /// [citeproc_rs_driver_set_cluster_order] are included. Useful for rendering a whole document
+    /// at once, e.g. after opening it. Returns [ErrorCode::ClusterNotInFlow] rather than leave a
+    /// cluster out if any of them can't be rendered.
+    ///
+    /// # Safety
+    ///
+    /// Driver must be a valid pointer to a Driver, and `user_buf` must match the expected user
+    /// data in the BufferOps struct passed to driver's init call.
+    @safety unsafe fn citeproc_rs_driver_all_clusters(#[nullify_on_panic] driver: *mut Driver, user_buf: *mut c_void) -> ErrorCode {
+        result_to_error_code(|| {
+            use rust::CiteDatabase;
+            let driver = unsafe { borrow_raw_ptr_mut(driver) } ?;
+            let proc = driver.processor.as_mut().ok_or(FFIError::Poisoned)?;
+            let mut buffer = unsafe { BufferWriter::new(driver.buffer_ops, user_buf) };
+            let ids: Vec<_> = proc.clusters_sorted().iter().map(|cluster| cluster.id).collect();
+            let clusters = cluster_outputs(proc, &ids)?;
+            buffer.clear();
+            serde_json::to_writer(&mut buffer, &clusters)?;
+            Ok(ErrorCode::None)
+        })
+    }
+}
+
+/// One `{ "id", "output" }` entry per cluster in `ids`, failing on the first that has no position in
+/// the document.
+fn cluster_outputs(
+    proc: &Processor,
+    ids: &[rust::ClusterId],
+) -> Result<Vec<serde_json::Value>, FFIError> {
+    ids.iter()
+        .map(|&id| {
+            let built = proc.get_cluster(id).ok_or(FFIError::ClusterNotInFlow(id))?;
+            Ok(serde_json::json!({
+                "id": id.0,
+                "output": built.as_str(),
+            }))
+        })
+        .collect()
+}
+
 ffi_fn_nullify! {
     /// Writes a bibliography into a buffer, using [citeproc::Processor::get_bibliography]
     @safety unsafe fn citeproc_rs_driver_format_bibliography(#[nullify_on_panic] driver: *mut Driver, user_buf: *mut c_void) -> ErrorCode {
@@ -428,6 +495,37 @@ ffi_fn_nullify! {
     }
 }
 
+ffi_fn_nullify! {
+    /// Stores a locale's XML for a language, using [citeproc::Processor::store_locales]. Use this
+    /// to supply locales you already have, at any time after [citeproc_rs_driver_new], instead of (or
+    /// as well as) the locale fetch callback. Returns an error code if the language tag is invalid or
+    /// the XML does not parse cleanly.
+    ///
+    /// # Safety
+    ///
+    /// `driver` must be a valid pointer to a Driver.
+    ///
+    /// `lang`/`lang_len` and `locale_xml`/`locale_xml_len` must each refer to a UTF-8 byte array of
+    /// that length.
+    @safety unsafe fn citeproc_rs_driver_store_locale(#[nullify_on_panic] driver: *mut Driver, lang: *const c_char, lang_len: usize, locale_xml: *const c_char, locale_xml_len: usize) -> ErrorCode {
+        result_to_error_code(|| {
+            // SAFETY: We assume people have passed a valid Driver pointer over FFI.
+            let driver = unsafe { borrow_raw_ptr_mut(driver) } ?;
+            let proc = driver.processor.as_mut().ok_or(FFIError::Poisoned)?;
+            // SAFETY: we asked folks to give us a language tag.
+            let lang = unsafe { borrow_utf8_slice(lang, lang_len) } ?;
+            let lang: Lang = lang.parse().map_err(|_| FFIError::InvalidLang(lang.to_owned()))?;
+            // SAFETY: we asked folks to give us an XML string.
+            let locale_xml = unsafe { borrow_utf8_slice(locale_xml, locale_xml_len) } ?;
+            // As with citeproc_rs_locale_slot_write, catch errors here rather than have them
+            // surface as a mysteriously missing locale
+            let _ = Locale::parse(locale_xml)?;
+            proc.store_locales(vec![(lang, locale_xml.to_owned())]);
+            Ok(ErrorCode::None)
+        })
+    }
+}
+
 #[cfg(feature = "testability")]
 ffi_fn! {
     fn test_panic() -> ErrorCode {
@@ -441,3 +539,71 @@ ffi_fn_nullify! {
         panic!("test_panic_poison_driver {}", 755);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust::{Cite, Cluster, ClusterPosition, InitOptions};
+
+    const STYLE: &str = r#"
+        <style class="in-text" version="1.0.1">
+            <citation>
+                <layout>
+                    <text variable="title" />
+                </layout>
+            </citation>
+        </style>
+    "#;
+
+    fn processor() -> Processor {
+        let mut proc = Processor::new(InitOptions {
+            style: STYLE,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut refr = Reference::empty("one".into(), csl::CslType::Book);
+        refr.ordinary
+            .insert(csl::Variable::Title, "Book one".into());
+        proc.insert_reference(refr);
+        proc
+    }
+
+    #[test]
+    fn cluster_outputs_in_order() {
+        let mut proc = processor();
+        let one = proc.cluster_id("one");
+        let two = proc.cluster_id("two");
+        proc.init_clusters(vec![
+            Cluster::new(one, vec![Cite::basic("one")], None),
+            Cluster::new(two, vec![], None),
+        ]);
+        proc.set_cluster_order(&[ClusterPosition::note(two, 1), ClusterPosition::note(one, 2)])
+            .unwrap();
+        let outputs = cluster_outputs(&proc, &[two, one]).unwrap();
+        assert_eq!(
+            outputs,
+            vec![
+                serde_json::json!({ "id": two.0, "output": "" }),
+                serde_json::json!({ "id": one.0, "output": "Book one" }),
+            ]
+        );
+    }
+
+    #[test]
+    fn cluster_outputs_unpositioned_is_an_error() {
+        let mut proc = processor();
+        let one = proc.cluster_id("one");
+        let two = proc.cluster_id("two");
+        proc.init_clusters(vec![
+            Cluster::new(one, vec![Cite::basic("one")], None),
+            Cluster::new(two, vec![Cite::basic("one")], None),
+        ]);
+        proc.set_cluster_order(&[ClusterPosition::note(one, 1)])
+            .unwrap();
+        match cluster_outputs(&proc, &[one, two]) {
+            Err(FFIError::ClusterNotInFlow(id)) => assert_eq!(id, two),
+            other => panic!("expected ClusterNotInFlow, got {:?}", other),
+        }
+    }
+}
@@ -121,5 +121,42 @@ fn bench_clusters(c: &mut Criterion) {
     });
 }
 
-criterion_group!(clusters, bench_clusters);
+static REPEATED_CONDITIONS: &'static str = r#"
+<style class="note" version="1.0.1">
+    <macro name="number">
+        <choose>
+            <if is-numeric="number volume issue page" match="any">
+                <number variable="number" />
+            </if>
+            <else-if variable="number" type="legal_case" match="all">
+                <text variable="number" />
+            </else-if>
+        </choose>
+    </macro>
+    <citation>
+        <layout delimiter="; ">
+            <group delimiter=", ">
+                <text variable="title" />
+                <text macro="number" />
+                <text macro="number" />
+                <text macro="number" />
+                <text macro="number" />
+                <text macro="number" />
+                <text macro="number" />
+                <text macro="number" />
+                <text macro="number" />
+            </group>
+        </layout>
+    </citation>
+</style>
+"#;
+
+/// Many `<choose>` blocks testing the same conditions, which are memoized per cite.
+fn bench_conditions(c: &mut Criterion) {
+    c.bench_function("Processor::built_cluster(repeated conditions)", |b| {
+        bench_build_cluster(b, REPEATED_CONDITIONS)
+    });
+}
+
+criterion_group!(clusters, bench_clusters, bench_conditions);
 criterion_main!(clusters);
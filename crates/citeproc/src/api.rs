@@ -10,7 +10,8 @@ use super::processor::Interner;
 use citeproc_db::ClusterId;
 use citeproc_io::output::{markup::Markup, OutputFormat};
 use citeproc_io::{Cite, ClusterMode, SmartString};
-use csl::Atom;
+use citeproc_proc::DisambPass;
+use csl::{Atom, DemoteNonDroppingParticle, Lang, PageRangeFormat};
 use fnv::FnvHashMap;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
@@ -48,9 +49,9 @@ use std::sync::Arc;
 ///               mode: Some(ClusterMode::Composite { infix: None, suppress_first: 2 }), },
 /// ]);
 /// ```
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(bound(
-    serialize = "Id: serde::Serialize",
+    serialize = "Id: serde::Serialize, O::Input: serde::Serialize",
     deserialize = "Id: serde::Deserialize<'de>"
 ))]
 pub struct Cluster<O: OutputFormat = Markup, Id = ClusterId> {
@@ -199,10 +200,10 @@ impl ReorderingError {
 pub mod string_id {
     //! This is the API using string IDs only, useful for exposing citeproc-rs to non-Rust
     //! consumers.
-    use super::{BibEntry, BibliographyUpdate};
+    use super::{BibEntry, BibliographyUpdate, IncludeUncited};
     use citeproc_io::{
         output::{markup::Markup, OutputFormat},
-        SmartString,
+        Reference, SmartString,
     };
     use fnv::FnvHashMap;
     use serde::{Deserialize, Serialize};
@@ -260,6 +261,18 @@ pub mod string_id {
         pub bib_entries: Vec<BibEntry<Markup>>,
     }
 
+    /// Everything a document has given the processor: references, clusters, their order and
+    /// uncited references. See [crate::Processor::export_state].
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DocumentState {
+        pub references: Vec<Reference>,
+        pub clusters: Vec<Cluster>,
+        pub cluster_order: Vec<ClusterPosition>,
+        #[serde(default)]
+        pub uncited: IncludeUncited,
+    }
+
     #[derive(Debug, thiserror::Error)]
     pub enum ReorderingError {
         #[error("{0}")]
@@ -299,6 +312,70 @@ pub struct BibliographyMeta<O: OutputFormat = Markup> {
     pub format_meta: O::BibMeta,
 }
 
+impl<O: OutputFormat> BibliographyMeta<O> {
+    /// Renders these options as CSS rules for the `csl-bib-body`, `csl-entry`, `csl-left-margin`
+    /// and `csl-right-inline` classes, one rule per line. Line heights follow Zotero in treating a
+    /// line as 1.35em.
+    pub fn css(&self) -> String {
+        let mut css = String::new();
+        let body_style = bib_body_style(self.line_spacing);
+        let entry_style =
+            bib_entry_style(self.line_spacing, self.entry_spacing, self.hanging_indent);
+        css.push_str(&format!(".csl-bib-body {{ {} }}\n", body_style));
+        css.push_str(&format!(".csl-entry {{ {} }}\n", entry_style));
+        match self.second_field_align {
+            Some(SecondFieldAlign::Flush) => {
+                css.push_str(".csl-left-margin { float: left; width: 2em; }\n");
+                css.push_str(".csl-right-inline { margin-left: 2em; }\n");
+            }
+            Some(SecondFieldAlign::Margin) => {
+                css.push_str(".csl-left-margin { float: left; width: 2em; margin-left: -2em; }\n");
+                css.push_str(".csl-right-inline { margin-left: 0; }\n");
+            }
+            None => {}
+        }
+        css
+    }
+}
+
+/// CSS declarations for the `csl-bib-body` wrapper.
+pub(crate) fn bib_body_style(line_spacing: u32) -> String {
+    format!(
+        "line-height: {};",
+        css_hundredths(line_height(line_spacing))
+    )
+}
+
+/// CSS declarations for each `csl-entry`.
+pub(crate) fn bib_entry_style(
+    line_spacing: u32,
+    entry_spacing: u32,
+    hanging_indent: bool,
+) -> String {
+    let entry_spacing = line_height(line_spacing) * entry_spacing;
+    let mut style = format!("margin-bottom: {}em;", css_hundredths(entry_spacing));
+    if hanging_indent {
+        style.push_str(" padding-left: 2em; text-indent: -2em;");
+    }
+    style
+}
+
+/// In hundredths of an em, to keep floats out of the output
+fn line_height(line_spacing: u32) -> u32 {
+    135 * line_spacing
+}
+
+fn css_hundredths(n: u32) -> String {
+    let (whole, frac) = (n / 100, n % 100);
+    if frac == 0 {
+        format!("{}", whole)
+    } else if frac % 10 == 0 {
+        format!("{}.{}", whole, frac / 10)
+    } else {
+        format!("{}.{:02}", whole, frac)
+    }
+}
+
 #[derive(Clone, Serialize, Default, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct BibliographyUpdate<O: OutputFormat = Markup> {
@@ -335,6 +412,80 @@ pub struct FullRender {
     pub bib_entries: Vec<BibEntry<Markup>>,
 }
 
+/// A summary of how much is in the document, e.g. for displaying progress.
+#[derive(Serialize, Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentStats {
+    /// Clusters that have been given a position via `set_cluster_order`
+    pub clusters: usize,
+    /// Cites contained in those clusters
+    pub cites: usize,
+    /// Non-empty bibliography entries. Zero if the style has no bibliography.
+    pub bib_entries: usize,
+}
+
+/// Style-wide options a host might want to display, from [crate::Processor::style_defaults].
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleDefaults {
+    /// The style's `default-locale`, if it has one
+    pub default_locale: Option<Lang>,
+    /// The style's `page-range-format`. None means page ranges are rendered as given.
+    pub page_range_format: Option<PageRangeFormat>,
+    pub demote_non_dropping_particle: DemoteNonDroppingParticle,
+}
+
+/// A kind of disambiguation applied to a cite, in the order the passes run.
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum DisambiguationKind {
+    /// `disambiguate-add-names`
+    AddNames,
+    /// `disambiguate-add-givenname`
+    AddGivenName,
+    /// `disambiguate-add-year-suffix`
+    AddYearSuffix,
+    /// `<if disambiguate="true">`
+    Conditionals,
+}
+
+impl From<DisambPass> for DisambiguationKind {
+    fn from(pass: DisambPass) -> Self {
+        match pass {
+            DisambPass::AddNames => DisambiguationKind::AddNames,
+            DisambPass::AddGivenName(_) => DisambiguationKind::AddGivenName,
+            DisambPass::AddYearSuffix(_) => DisambiguationKind::AddYearSuffix,
+            DisambPass::Conditionals => DisambiguationKind::Conditionals,
+        }
+    }
+}
+
+/// A rendered preview from [crate::Processor::preview_cluster].
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterPreview {
+    pub output: Arc<SmartString>,
+    /// Each kind of disambiguation that changed the output of at least one cite in the preview.
+    /// Empty if none of them were ambiguous.
+    pub disambiguation: Vec<DisambiguationKind>,
+}
+
+/// How a reference would be disambiguated if it were added to the library, from
+/// [crate::Processor::preview_disambiguation].
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DisambInfo {
+    /// The references already in the library that a basic cite to the new one would be confused
+    /// with before any disambiguation. Sorted.
+    pub ambiguous_with: Vec<Atom>,
+    /// Each kind of disambiguation that changed the cite's output. Empty if it was not ambiguous.
+    pub disambiguation: Vec<DisambiguationKind>,
+    /// The year suffix it would receive, counting from 1 for `a`.
+    pub year_suffix: Option<u32>,
+    /// A basic cite to the reference, fully disambiguated.
+    pub output: SmartString,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, Ord, PartialOrd, PartialEq)]
 pub enum IncludeUncited {
     /// The default
@@ -352,13 +503,19 @@ impl Default for IncludeUncited {
 }
 
 #[doc(inline)]
-pub use citeproc_io::output::markup::FormatOptions;
+pub use citeproc_io::output::markup::{CustomFormat, CustomMarkup, FormatOptions};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum SupportedFormat {
     Html,
     Rtf,
     Plain,
+    /// Inline JATS XML formatting tags, without any `<element-citation>` structure.
+    Jats,
+    /// HTML escaped the way the CSL test suite writes its expected output: only `&`, `<` and `>`
+    /// are escaped in text. For test harnesses comparing against fixtures from the test suite or
+    /// citeproc-js. Otherwise the same as `Html`, and sets [FormatOptions::test_escaping].
+    TestHtml,
 }
 
 impl Default for SupportedFormat {
@@ -373,6 +530,11 @@ impl SupportedFormat {
             SupportedFormat::Html => Markup::Html(options),
             SupportedFormat::Rtf => Markup::Rtf(options),
             SupportedFormat::Plain => Markup::Plain(options),
+            SupportedFormat::Jats => Markup::Jats(options),
+            SupportedFormat::TestHtml => Markup::Html(FormatOptions {
+                test_escaping: true,
+                ..options
+            }),
         }
     }
 }
@@ -384,6 +546,8 @@ impl FromStr for SupportedFormat {
             "html" => Ok(SupportedFormat::Html),
             "rtf" => Ok(SupportedFormat::Rtf),
             "plain" => Ok(SupportedFormat::Plain),
+            "jats" => Ok(SupportedFormat::Jats),
+            "test-html" => Ok(SupportedFormat::TestHtml),
             _ => Err(()),
         }
     }
@@ -26,7 +26,7 @@ pub mod prelude {
         LocaleFetcher, StyleDatabase,
     };
     pub use citeproc_io::output::{markup::Markup, OutputFormat};
-    pub use citeproc_io::{Cite, Reference, SmartString};
+    pub use citeproc_io::{Cite, Reference, ReferenceBuilder, SmartString};
     pub use citeproc_proc::db::{ImplementationDetails, IrDatabase};
     pub use csl::Atom;
 
@@ -11,15 +11,15 @@
 use crate::prelude::*;
 
 use crate::api::{
-    string_id, BibEntry, BibliographyMeta, BibliographyUpdate, ClusterPosition, IncludeUncited,
-    ReorderingError, SecondFieldAlign, UpdateSummary,
+    bib_body_style, bib_entry_style, string_id, BibEntry, BibliographyMeta, BibliographyUpdate,
+    ClusterPosition, ClusterPreview, DisambInfo, DisambiguationKind, DocumentStats, IncludeUncited,
+    ReorderingError, SecondFieldAlign, StyleDefaults, UpdateSummary,
 };
 use citeproc_db::{
     CiteData, CiteDatabaseStorage, HasFetcher, LocaleDatabaseStorage, StyleDatabaseStorage, Uncited,
 };
-use citeproc_io::output::markup::FormatOptions;
-use citeproc_proc::db::IrDatabaseStorage;
-use citeproc_proc::BibNumber;
+use citeproc_io::output::markup::{CustomFormat, CustomMarkup, FormatOptions};
+use citeproc_proc::db::{BibliographyIter, IrDatabaseStorage};
 use indexmap::set::IndexSet;
 
 use salsa::{Database, Durability, SweepStrategy};
@@ -28,7 +28,7 @@ use salsa::{ParallelDatabase, Snapshot};
 use std::sync::Arc;
 use std::sync::{Mutex, RwLock};
 
-use csl::{Lang, Style, StyleError};
+use csl::{CslType, Lang, Position, Style, StyleClass, StyleError, StyleMeta};
 
 use citeproc_io::output::{markup::Markup, OutputFormat};
 use citeproc_io::{Cite, ClusterMode, Reference, SmartString};
@@ -45,14 +45,14 @@ type MarkupOutput = <Markup as OutputFormat>::Output;
 use fnv::{FnvHashMap, FnvHashSet};
 
 struct SavedBib {
-    sorted_refs: Arc<(Vec<Atom>, FnvHashMap<Atom, BibNumber>)>,
+    entry_ids: Vec<Atom>,
     bib_entries: Arc<FnvHashMap<Atom, Arc<MarkupOutput>>>,
 }
 
 impl SavedBib {
     fn new() -> Self {
         SavedBib {
-            sorted_refs: Arc::new(Default::default()),
+            entry_ids: Vec::new(),
             bib_entries: Arc::new(Default::default()),
         }
     }
@@ -146,6 +146,15 @@ pub struct InitOptions<'a> {
     /// Disables sorting on the bibliography (enabled by default)
     pub bibliography_no_sort: bool,
 
+    /// Characters to count disambiguating year suffixes with, instead of `a` to `z`. After the
+    /// last one, suffixes roll over to two characters (`aa`, `ab`, ...). Ignored if empty.
+    pub year_suffix_alphabet: Option<Vec<char>>,
+
+    /// The type to give references whose CSL-JSON `type` is not one citeproc-rs knows, e.g. a
+    /// newer CSL type like `software`. Defaults to `document`, or `article` for styles declaring
+    /// `version="1.0"`, which predate `document`. See [Processor::unknown_reference_types].
+    pub unknown_type_fallback: Option<CslType>,
+
     #[doc(hidden)]
     pub use_default_default: private::CannotConstruct,
 }
@@ -190,6 +199,8 @@ impl Processor {
             csl_features,
             test_mode,
             bibliography_no_sort,
+            year_suffix_alphabet,
+            unknown_type_fallback,
             use_default_default: _,
         } = options;
 
@@ -208,6 +219,11 @@ impl Processor {
         db.set_output_format(format, format_options);
         db.set_default_lang_override_with_durability(locale_override, Durability::HIGH);
         db.set_bibliography_no_sort_with_durability(bibliography_no_sort, Durability::HIGH);
+        let year_suffix_alphabet = year_suffix_alphabet
+            .filter(|alphabet| !alphabet.is_empty())
+            .map(Arc::new);
+        db.set_year_suffix_alphabet_with_durability(year_suffix_alphabet, Durability::HIGH);
+        db.set_unknown_type_fallback_with_durability(unknown_type_fallback, Durability::HIGH);
         Ok(db)
     }
 
@@ -223,6 +239,23 @@ impl Processor {
         self.set_formatter_with_durability(formatter, Durability::HIGH);
     }
 
+    /// Sets the output format to one you implement yourself, for markup citeproc-rs does not
+    /// support. Like [Processor::set_output_format], this requires nearly everything to be
+    /// recomputed, unless `markup` is the same `Arc` as last time and the options are unchanged.
+    pub fn set_custom_output_format(
+        &mut self,
+        markup: Arc<dyn CustomMarkup>,
+        options: FormatOptions,
+    ) {
+        self.format_options = options;
+        let formatter = Markup::Custom(CustomFormat::new(markup, options));
+        if self.formatter == formatter {
+            return;
+        }
+        self.formatter = formatter.clone();
+        self.set_formatter_with_durability(formatter, Durability::HIGH);
+    }
+
     /// Sets the CSL style to be used. Will require nearly everything to be recomputed, so call sparingly.
     pub fn set_style_text(&mut self, style_text: &str) -> Result<(), StyleError> {
         let style = Style::parse(style_text)?;
@@ -384,6 +417,15 @@ impl Processor {
         self.set_all_keys_with_durability(Arc::new(keys), Durability::MEDIUM);
     }
 
+    /// Replaces the library like [Processor::reset_references], then returns the clusters and
+    /// bibliography entries that rendered differently as a result, like
+    /// [Processor::batched_updates]. Any other changes made since the last batched update or
+    /// [Processor::drain] are included too.
+    pub fn references_changed(&mut self, new_refs: Vec<Reference>) -> UpdateSummary {
+        self.reset_references(new_refs);
+        self.batched_updates()
+    }
+
     pub fn extend_references(&mut self, refs: Vec<Reference>) {
         let keys = self.all_keys();
         let mut keys = IndexSet::clone(&keys);
@@ -424,6 +466,13 @@ impl Processor {
         self.set_all_uncited_with_durability(Arc::new(db_uncited), Durability::MEDIUM);
     }
 
+    /// Leaves these references out of the bibliography, even if they are cited or included with
+    /// [Processor::include_uncited]. Their cites are unaffected. Replaces any previous list.
+    pub fn set_bibliography_exclude(&mut self, ids: Vec<Atom>) {
+        let exclude: FnvHashSet<Atom> = ids.into_iter().collect();
+        self.set_bibliography_excluded_with_durability(Arc::new(exclude), Durability::MEDIUM);
+    }
+
     pub fn init_clusters(&mut self, clusters: Vec<Cluster>) {
         let mut new_all = FnvHashSet::default();
         new_all.reserve(clusters.len());
@@ -573,36 +622,115 @@ impl Processor {
         }
     }
 
+    /// The author part of a cluster, rendered as `ClusterMode::AuthorOnly` would, whatever mode the
+    /// cluster actually has. For narrative citation UIs that print the author in the running text
+    /// ahead of the cite. Returns None if the cluster has not been assigned a position in the
+    /// document, or none of its cites has an author (or substitute) to render.
+    pub fn cluster_author_prefix(&self, cluster_id: ClusterId) -> Option<Arc<MarkupOutput>> {
+        self.cluster_note_number(cluster_id)?;
+        citeproc_proc::db::built_cluster_author_prefix(self, cluster_id)
+    }
+
+    pub fn cluster_author_prefix_str(&self, cluster_id: &str) -> Option<Arc<MarkupOutput>> {
+        let id = self.cluster_id(cluster_id);
+        self.cluster_author_prefix(id)
+    }
+
     pub fn get_cluster_note_number(&self, cluster_id: ClusterId) -> Option<ClusterNumber> {
         self.cluster_note_number(cluster_id)
     }
 
+    /// The footnote number a cluster was placed in by `set_cluster_order`. Clusters merged into
+    /// the same footnote share a number. Returns None for in-text clusters and for clusters that
+    /// have not been assigned a position in the document.
+    pub fn get_note_number(&self, cluster_id: ClusterId) -> Option<u32> {
+        match self.cluster_note_number(cluster_id)? {
+            ClusterNumber::Note(intra) => Some(intra.note_number()),
+            ClusterNumber::InText(_) | ClusterNumber::OutsideFlow => None,
+        }
+    }
+
+    pub fn get_note_number_str(&self, cluster_id: &str) -> Option<u32> {
+        let id = self.cluster_id(cluster_id);
+        self.get_note_number(id)
+    }
+
     /// Returns None if the cluster has not been assigned a position in the document.
     pub fn get_cluster_str(&self, cluster_id: &str) -> Option<Arc<MarkupOutput>> {
         let id = self.cluster_id(cluster_id);
         self.get_cluster(id)
     }
 
+    /// The position (first, ibid, subsequent, etc) computed for each cite in a cluster, in the
+    /// order the cites were inserted. Empty if the cluster has not been assigned a position in the
+    /// document.
+    pub fn cluster_cite_positions(&self, cluster_id: ClusterId) -> Vec<Position> {
+        if self.cluster_note_number(cluster_id).is_none() {
+            return Vec::new();
+        }
+        let positions = self.cite_positions();
+        self.cluster_cites(cluster_id)
+            .iter()
+            .filter_map(|cite_id| positions.get(cite_id))
+            .map(|&(position, _frnn)| position)
+            .collect()
+    }
+
+    pub fn cluster_cite_positions_str(&self, cluster_id: &str) -> Vec<Position> {
+        let id = self.cluster_id(cluster_id);
+        self.cluster_cite_positions(id)
+    }
+
+    /// The `first-reference-note-number` for a cite, i.e. the note in which its reference was
+    /// first cited. None for the first cite of a reference, for cites of references only ever
+    /// cited in-text, and for cites in clusters that have no position in the document.
+    pub fn first_reference_note_number(&self, cite_id: CiteId) -> Option<u32> {
+        self.cite_positions()
+            .get(&cite_id)
+            .and_then(|&(_position, frnn)| frnn)
+    }
+
+    /// The fully disambiguated IR tree for each cite in a cluster, in the order the cites were
+    /// inserted. Serialize the result to JSON to see the node structure, group vars and affixes a
+    /// style produced. Returns None if the cluster has not been assigned a position in the
+    /// document.
+    #[cfg(feature = "debug-ir")]
+    pub fn cluster_ir(&self, cluster_id: ClusterId) -> Option<Vec<citeproc_proc::IrDebugNode>> {
+        self.cluster_note_number(cluster_id)?;
+        let fmt = self.get_formatter();
+        let trees = self
+            .cluster_cites(cluster_id)
+            .iter()
+            .map(|&cite_id| self.ir_fully_disambiguated(cite_id).debug_tree(&fmt))
+            .collect();
+        Some(trees)
+    }
+
     pub fn get_bib_item(&self, ref_id: Atom) -> Arc<MarkupOutput> {
         self.bib_item(ref_id)
     }
 
     pub fn get_bibliography_meta(&self) -> Option<BibliographyMeta> {
+        self.bibliography_meta_with(&self.get_formatter())
+    }
+
+    fn bibliography_meta_with(&self, fmt: &Markup) -> Option<BibliographyMeta> {
         let style = self.get_style();
-        style.bibliography.as_ref().map(|bib| {
-            BibliographyMeta {
-                // TODO
-                max_offset: 0,
-                entry_spacing: bib.entry_spacing,
-                line_spacing: bib.line_spacing,
-                hanging_indent: bib.hanging_indent,
-                // To avoid serde derive in csl
-                second_field_align: bib.second_field_align.as_ref().map(|s| match s {
-                    csl::style::SecondFieldAlign::Flush => SecondFieldAlign::Flush,
-                    csl::style::SecondFieldAlign::Margin => SecondFieldAlign::Margin,
-                }),
-                format_meta: self.get_formatter().meta(),
-            }
+        style.bibliography.as_ref().map(|bib| BibliographyMeta {
+            // TODO
+            max_offset: 0,
+            entry_spacing: bib.entry_spacing,
+            line_spacing: bib.line_spacing,
+            hanging_indent: bib.hanging_indent,
+            // To avoid serde derive in csl
+            second_field_align: bib.second_field_align.as_ref().map(|s| match s {
+                csl::style::SecondFieldAlign::Flush => SecondFieldAlign::Flush,
+                csl::style::SecondFieldAlign::Margin => SecondFieldAlign::Margin,
+            }),
+            format_meta: fmt.meta_with_styles(
+                &bib_body_style(bib.line_spacing),
+                &bib_entry_style(bib.line_spacing, bib.entry_spacing, bib.hanging_indent),
+            ),
         })
     }
 
@@ -621,11 +749,11 @@ impl Processor {
             }
         }
         last_bibliography.bib_entries = new;
-        let sorted_refs = self.sorted_refs();
-        if sorted_refs.0 != old.sorted_refs.0 {
-            update.entry_ids = Some(sorted_refs.0.clone());
+        let entry_ids = self.bibliography_entry_ids();
+        if entry_ids != old.entry_ids {
+            update.entry_ids = Some(entry_ids.clone());
         }
-        last_bibliography.sorted_refs = sorted_refs;
+        last_bibliography.entry_ids = entry_ids;
         if update.updated_entries.is_empty() && update.entry_ids.is_none() {
             None
         } else {
@@ -660,25 +788,339 @@ impl Processor {
         mapping
     }
 
-    pub fn get_bibliography(&self) -> Vec<BibEntry> {
-        let bib_map = self.get_bibliography_map();
+    /// Counts the positioned clusters, the cites within them, and the rendered bibliography
+    /// entries.
+    pub fn document_stats(&self) -> DocumentStats {
+        let clusters = self.clusters_sorted();
+        let cites = clusters.iter().map(|cluster| cluster.cites.len()).sum();
+        let bib_entries = if self.get_style().bibliography.is_some() {
+            self.get_bibliography_map().len()
+        } else {
+            0
+        };
+        DocumentStats {
+            clusters: clusters.len(),
+            cites,
+            bib_entries,
+        }
+    }
+
+    /// Lists every cite in a positioned cluster whose `ref_id` does not match any reference that
+    /// has been inserted, as `(cluster, index of the cite within it, ref_id)`, in document order.
+    pub fn unresolved_cites(&self) -> Vec<(ClusterId, usize, Atom)> {
+        let all_keys = self.all_keys();
+        let mut unresolved = Vec::new();
+        for cluster in self.clusters_sorted().iter() {
+            for (index, cite_id) in cluster.cites.iter().enumerate() {
+                let cite = cite_id.lookup(self);
+                if !all_keys.contains(&cite.ref_id) {
+                    unresolved.push((cluster.id, index, cite.ref_id.clone()));
+                }
+            }
+        }
+        unresolved
+    }
+
+    /// Like [Processor::unresolved_cites], but with cluster ids converted back to strings.
+    pub fn unresolved_cites_str(&self) -> Vec<(SmartString, usize, Atom)> {
+        let unresolved = self.unresolved_cites();
+        let interner = self.interner.read().unwrap();
+        unresolved
+            .into_iter()
+            .filter_map(|(cid, index, ref_id)| {
+                let resolved = interner.resolve(cid)?;
+                Some((SmartString::from(resolved), index, ref_id))
+            })
+            .collect()
+    }
+
+    /// Maps each cited reference id to the note numbers of the clusters citing it, ascending and
+    /// without duplicates, e.g. for "cited in notes 2, 5" back-references in a bibliography. Only
+    /// clusters positioned as notes count, so this is empty for in-text documents.
+    pub fn back_references(&self) -> FnvHashMap<Atom, Vec<u32>> {
+        let all_keys = self.all_keys();
+        let mut back_refs: FnvHashMap<Atom, Vec<u32>> = FnvHashMap::default();
+        for cluster in self.clusters_sorted().iter() {
+            let note = match cluster.number {
+                ClusterNumber::Note(intra) => intra.note_number(),
+                ClusterNumber::InText(_) | ClusterNumber::OutsideFlow => continue,
+            };
+            for cite_id in cluster.cites.iter() {
+                let cite = cite_id.lookup(self);
+                if !all_keys.contains(&cite.ref_id) {
+                    continue;
+                }
+                let notes = back_refs.entry(cite.ref_id.clone()).or_default();
+                if notes.last() != Some(&note) {
+                    notes.push(note);
+                }
+            }
+        }
+        back_refs
+    }
+
+    /// Lists the references whose CSL-JSON `type` was not recognised, as `(id, type)`, in the order
+    /// they were inserted. These are rendered as [InitOptions::unknown_type_fallback].
+    pub fn unknown_reference_types(&self) -> Vec<(Atom, String)> {
+        self.all_keys()
+            .iter()
+            .filter_map(|key| {
+                let refr = self.reference_input(key.clone());
+                let unknown = refr.unknown_type.clone()?;
+                Some((key.clone(), unknown))
+            })
+            .collect()
+    }
+
+    /// Builds an inverted index from each disambiguation token (a piece of output that a cite
+    /// could render) to the references whose disambiguation DFAs can produce it. Only references
+    /// that participate in disambiguation, i.e. cited ones plus any included as uncited, appear.
+    /// Each list of references is sorted.
+    pub fn disambiguation_index(&self) -> FnvHashMap<SmartString, Vec<Atom>> {
+        let mut index: FnvHashMap<SmartString, Vec<Atom>> = FnvHashMap::default();
+        for (ref_id, dfa) in self.all_ref_dfas().iter() {
+            for token in dfa.output_tokens() {
+                let ids = index.entry(token.clone()).or_default();
+                if !ids.contains(ref_id) {
+                    ids.push(ref_id.clone());
+                }
+            }
+        }
+        for ids in index.values_mut() {
+            ids.sort();
+        }
+        index
+    }
+
+    /// Lists the references in the bibliography whose entries render to nothing, usually because
+    /// the style only prints fields they lack, in bibliography order. These are left out of
+    /// [Processor::get_bibliography]. Empty if the style has no bibliography.
+    pub fn empty_rendering_ids(&self) -> Vec<Atom> {
+        if self.get_style().bibliography.is_none() {
+            return Vec::new();
+        }
+        let rendered = self.get_bibliography_map();
+        self.bibliography_entry_ids()
+            .into_iter()
+            .filter(|id| !rendered.contains_key(id))
+            .collect()
+    }
+
+    /// Lists each reference in the bibliography, in bibliography order, with the sort key
+    /// components the style's bibliography `<sort>` computed for it. Useful for working out why a
+    /// bibliography sorted the way it did. Missing values are empty strings.
+    pub fn bibliography_sort_keys(&self) -> Vec<(Atom, Vec<SmartString>)> {
+        citeproc_proc::bibliography_sort_keys(self)
+    }
+
+    /// The references in the bibliography, in order, leaving out any passed to
+    /// [Processor::set_bibliography_exclude].
+    fn bibliography_entry_ids(&self) -> Vec<Atom> {
+        let exclude = self.bibliography_excluded();
         self.sorted_refs()
             .0
             .iter()
-            .filter_map(|k| bib_map.get(k).map(|v| (k, v)))
-            .map(|(k, v)| BibEntry {
-                id: k.clone(),
-                value: if v.is_empty() {
-                    Arc::new(SmartString::from(
-                        "[CSL STYLE ERROR: reference with no printed form.]",
-                    ))
-                } else {
-                    v.clone()
-                },
+            .filter(|id| !exclude.contains(*id))
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_bibliography(&self) -> Vec<BibEntry> {
+        self.bibliography_iter().collect()
+    }
+
+    /// Assembles the bibliography into one string in the processor's output format, wrapping each
+    /// entry the way that format separates them naturally (see [Markup::bib_entry_affixes]): a
+    /// `csl-entry` div for HTML, a `\par` paragraph for RTF, and a line for plain text. The wrappers
+    /// are the ones in [Processor::get_bibliography_meta], so they honour
+    /// [FormatOptions::bib_inline_styles]. Empty if the style has no bibliography.
+    pub fn get_bibliography_string(&self) -> String {
+        let wrap = match self.get_bibliography_meta() {
+            Some(meta) => meta.format_meta,
+            None => return String::new(),
+        };
+        let mut string = wrap.markup_pre;
+        for entry in self.bibliography_iter() {
+            string.push_str(&wrap.entry_pre);
+            string.push_str(&entry.value);
+            string.push_str(&wrap.entry_post);
+        }
+        string.push_str(&wrap.markup_post);
+        string
+    }
+
+    /// Renders the bibliography lazily, one entry at a time, in bibliography order. Useful for
+    /// writing out very large bibliographies without holding all of them in memory.
+    pub fn bibliography_iter(&self) -> impl Iterator<Item = BibEntry> + '_ {
+        BibliographyIter::new(self).map(|(id, value)| BibEntry { id, value })
+    }
+
+    /// Renders every positioned cluster in document order, followed by the bibliography if the
+    /// style has one, into a single HTML fragment for one-shot exports. The fragment starts with a
+    /// `<style>` element implementing the bibliography's layout options.
+    ///
+    /// Clusters and entries are always rendered as HTML, with the processor's format options,
+    /// whatever output format the processor is using.
+    pub fn render_full_html(&self) -> String {
+        let fmt = SupportedFormat::Html.make_markup(self.format_options);
+        let meta = self.bibliography_meta_with(&fmt);
+        let mut html = String::new();
+        if let Some(meta) = &meta {
+            html.push_str("<style>\n");
+            html.push_str(&meta.css());
+            html.push_str("</style>\n");
+        }
+        html.push_str("<div class=\"csl-citations\">\n");
+        for cluster in self.clusters_sorted().iter() {
+            html.push_str("  <p class=\"csl-citation\">");
+            html.push_str(&citeproc_proc::db::built_cluster_preview(
+                self, cluster.id, &fmt,
+            ));
+            html.push_str("</p>\n");
+        }
+        html.push_str("</div>\n");
+        if let Some(meta) = &meta {
+            let wrap = &meta.format_meta;
+            html.push_str(&wrap.markup_pre);
+            html.push('\n');
+            for (_id, value) in BibliographyIter::with_formatter(self, fmt.clone()) {
+                html.push_str("  ");
+                html.push_str(&wrap.entry_pre);
+                html.push_str(&value);
+                html.push_str(&wrap.entry_post);
+            }
+            html.push_str(&wrap.markup_post);
+            html.push('\n');
+        }
+        html
+    }
+
+    /// The clusters in the document, in the order last given to [Processor::set_cluster_order],
+    /// with the note number each was given. Clusters removed since then are left out.
+    pub fn get_cluster_order(&self) -> Vec<ClusterPosition> {
+        let all_cluster_ids = self.all_cluster_ids();
+        self.clusters_ordered()
+            .iter()
+            .filter(|cid| all_cluster_ids.contains(*cid))
+            .filter_map(|&cid| {
+                let note = match self.cluster_note_number(cid)? {
+                    ClusterNumber::Note(intra) => Some(intra.note_number()),
+                    ClusterNumber::InText(_) | ClusterNumber::OutsideFlow => None,
+                };
+                Some(ClusterPosition {
+                    id: Some(cid),
+                    note,
+                })
             })
             .collect()
     }
 
+    /// Like [Processor::get_cluster_order], but with cluster ids converted back to strings.
+    pub fn get_cluster_order_str(&self) -> Vec<string_id::ClusterPosition> {
+        let order = self.get_cluster_order();
+        let interner = self.interner.read().unwrap();
+        order
+            .into_iter()
+            .filter_map(|pos| {
+                let resolved = interner.resolve(pos.id?)?;
+                Some(string_id::ClusterPosition {
+                    id: Some(SmartString::from(resolved)),
+                    note: pos.note,
+                })
+            })
+            .collect()
+    }
+
+    /// Captures the references, clusters, cluster order and uncited references, so a document can
+    /// be persisted and later restored with [Processor::import_state]. Positioned clusters come
+    /// first, in document order.
+    pub fn export_state(&self) -> string_id::DocumentState {
+        let references = self
+            .all_keys()
+            .iter()
+            .filter_map(|id| self.reference(id.clone()))
+            .map(|refr| Reference::clone(&refr))
+            .collect();
+
+        let interner = self.interner.read().unwrap();
+        let resolve = |cid: ClusterId| interner.resolve(cid).map(SmartString::from);
+
+        let ordered = self.clusters_ordered();
+        let all_cluster_ids = self.all_cluster_ids();
+        let mut cluster_ids: Vec<ClusterId> = ordered
+            .iter()
+            .copied()
+            .filter(|cid| all_cluster_ids.contains(cid))
+            .collect();
+        let mut unpositioned: Vec<(SmartString, ClusterId)> = all_cluster_ids
+            .iter()
+            .filter(|cid| !ordered.contains(cid))
+            .filter_map(|&cid| Some((resolve(cid)?, cid)))
+            .collect();
+        unpositioned.sort_by(|a, b| a.0.cmp(&b.0));
+        cluster_ids.extend(unpositioned.into_iter().map(|(_, cid)| cid));
+
+        let clusters = cluster_ids
+            .iter()
+            .filter_map(|&cid| {
+                let cites = self
+                    .cluster_cites(cid)
+                    .iter()
+                    .map(|cite_id| Cite::clone(&cite_id.lookup(self)))
+                    .collect();
+                Some(string_id::Cluster::new(
+                    resolve(cid)?,
+                    cites,
+                    self.cluster_mode(cid),
+                ))
+            })
+            .collect();
+
+        let cluster_order = self
+            .get_cluster_order()
+            .into_iter()
+            .filter_map(|pos| {
+                Some(string_id::ClusterPosition {
+                    id: Some(resolve(pos.id?)?),
+                    note: pos.note,
+                })
+            })
+            .collect();
+
+        let uncited = match &*self.all_uncited() {
+            Uncited::All => IncludeUncited::All,
+            Uncited::Enumerated(list) if list.is_empty() => IncludeUncited::None,
+            Uncited::Enumerated(list) => {
+                IncludeUncited::Specific(list.iter().map(|id| id.to_string()).collect())
+            }
+        };
+
+        string_id::DocumentState {
+            references,
+            clusters,
+            cluster_order,
+            uncited,
+        }
+    }
+
+    /// Replaces the references, clusters, cluster order and uncited references with those from a
+    /// [Processor::export_state] snapshot.
+    pub fn import_state(
+        &mut self,
+        state: string_id::DocumentState,
+    ) -> Result<(), string_id::ReorderingError> {
+        let string_id::DocumentState {
+            references,
+            clusters,
+            cluster_order,
+            uncited,
+        } = state;
+        self.reset_references(references);
+        self.include_uncited(uncited);
+        self.init_clusters_str(clusters);
+        self.set_cluster_order_str(&cluster_order)
+    }
+
     pub fn get_reference(&self, ref_id: Atom) -> Option<Arc<Reference>> {
         self.reference(ref_id)
     }
@@ -687,6 +1129,48 @@ impl Processor {
         self.style()
     }
 
+    /// The style-wide options a host might display, as written in the style. The
+    /// `default_locale` does not reflect [Processor::set_locale_override].
+    pub fn style_defaults(&self) -> StyleDefaults {
+        let style = self.style();
+        StyleDefaults {
+            default_locale: style.default_locale.clone(),
+            page_range_format: style.page_range_format,
+            demote_non_dropping_particle: style.demote_non_dropping_particle,
+        }
+    }
+
+    /// The loaded style's metadata (`<info>`, `default-locale`, `class`, etc), as
+    /// [StyleMeta::parse] would give for the style's XML.
+    pub fn style_meta(&self) -> StyleMeta {
+        StyleMeta::from_style(&self.style())
+    }
+
+    /// Whether the style is a note or an in-text style.
+    pub fn style_class(&self) -> StyleClass {
+        self.style().class
+    }
+
+    /// True if the citation layout can render `citation-number`, which is how numeric styles
+    /// (e.g. `[1]`) are recognised.
+    pub fn is_numeric_style(&self) -> bool {
+        citeproc_proc::citation_renders_citation_number(&self.style())
+    }
+
+    /// Changes the default locale, as `InitOptions::locale_override` does at construction. `None`
+    /// goes back to the style's `default-locale`. Will require nearly everything to be recomputed,
+    /// so call sparingly.
+    ///
+    /// If the new locale has not been stored yet, it will appear in [Processor::get_langs_in_use],
+    /// so you can fetch it.
+    pub fn set_locale_override(&mut self, lang: Option<Lang>) {
+        if self.default_lang_override() == lang {
+            // Avoid recomputing everything if possible
+            return;
+        }
+        self.set_default_lang_override_with_durability(lang, Durability::HIGH);
+    }
+
     pub fn store_locales(&mut self, locales: Vec<(Lang, String)>) {
         let mut langs = (*self.locale_input_langs()).clone();
         for (lang, xml) in locales {
@@ -699,6 +1183,15 @@ impl Processor {
     pub fn get_langs_in_use(&self) -> Vec<Lang> {
         let dl = self.default_lang();
         let mut vec: Vec<Lang> = dl.iter_fetchable_langs().collect();
+        // with locale-by-language, each reference's language gets its own locale
+        if self.style().features.locale_by_language {
+            for key in self.all_keys().iter() {
+                let language = self.reference(key.clone()).and_then(|r| r.language.clone());
+                if let Some(lang) = language {
+                    vec.extend(lang.iter_fetchable_langs());
+                }
+            }
+        }
         vec.sort();
         vec.dedup();
         vec
@@ -791,6 +1284,18 @@ impl Processor {
         position: PreviewPosition<'a>,
         format: Option<SupportedFormat>,
     ) -> Result<Arc<MarkupOutput>, ReorderingError> {
+        self.preview_cluster(preview_cluster, position, format)
+            .map(|preview| preview.output)
+    }
+
+    /// Like [Processor::preview_citation_cluster], but also reports which kinds of
+    /// disambiguation the previewed cites needed, so a UI can hint that a cite is ambiguous.
+    pub fn preview_cluster<'a>(
+        &mut self,
+        preview_cluster: PreviewCluster,
+        position: PreviewPosition<'a>,
+        format: Option<SupportedFormat>,
+    ) -> Result<ClusterPreview, ReorderingError> {
         let (id, state) = match position {
             PreviewPosition::ReplaceCluster(cluster_id) => {
                 let ids = self.all_cluster_ids();
@@ -831,8 +1336,21 @@ impl Processor {
         let nn = self.cluster_note_number(id);
         log::debug!("cluster_cites_sorted: {:?}", cluster_cites_sorted);
         log::debug!("cluster_note_number: {:?}", nn);
+        let mut disambiguation = Vec::new();
+        for &cite_id in cluster_cites_sorted.iter().flat_map(|cites| cites.iter()) {
+            for &pass in self.ir_fully_disambiguated(cite_id).disamb_passes() {
+                let kind = DisambiguationKind::from(pass);
+                if !disambiguation.contains(&kind) {
+                    disambiguation.push(kind);
+                }
+            }
+        }
+        disambiguation.sort();
         self.restore_cluster_state(state);
-        Ok(markup)
+        Ok(ClusterPreview {
+            output: markup,
+            disambiguation,
+        })
     }
 
     pub fn preview_reference(
@@ -851,6 +1369,60 @@ impl Processor {
         citeproc_proc::bib_item_preview(self, preview_ref_id.clone(), arc.as_ref(), &formatter)
     }
 
+    /// Reports how a reference would be disambiguated against the references that currently
+    /// participate in disambiguation (see [Processor::disambiguation_index]), as if it were
+    /// inserted and included as uncited. The library is left as it was.
+    pub fn preview_disambiguation(&mut self, mut refr: Reference) -> DisambInfo {
+        const PREVIEW_REFERENCE_ID: &'static str = "REFERENCE-5d1c0a7e93f62";
+        let preview_ref_id = Atom::from(PREVIEW_REFERENCE_ID);
+        refr.id = preview_ref_id.clone();
+        self.set_reference_input(preview_ref_id.clone(), Arc::new(refr));
+
+        let old_keys = self.all_keys();
+        let old_uncited = self.all_uncited();
+        let mut keys = IndexSet::clone(&old_keys);
+        keys.insert(preview_ref_id.clone());
+        self.set_all_keys_with_durability(Arc::new(keys), Durability::MEDIUM);
+        if let Uncited::Enumerated(list) = &*old_uncited {
+            let mut list = list.clone();
+            list.insert(preview_ref_id.clone());
+            self.set_all_uncited_with_durability(
+                Arc::new(Uncited::Enumerated(list)),
+                Durability::MEDIUM,
+            );
+        }
+
+        let ghost = citeproc_proc::ghost_cite_disambiguation(
+            self,
+            preview_ref_id.clone(),
+            &self.get_formatter(),
+        );
+        let mut ambiguous_with: Vec<Atom> = ghost
+            .matching_refs
+            .into_iter()
+            .filter(|id| *id != preview_ref_id)
+            .collect();
+        ambiguous_with.sort();
+        let mut disambiguation: Vec<DisambiguationKind> = Vec::new();
+        for &pass in ghost.gen.disamb_passes() {
+            let kind = DisambiguationKind::from(pass);
+            if !disambiguation.contains(&kind) {
+                disambiguation.push(kind);
+            }
+        }
+        disambiguation.sort();
+        let info = DisambInfo {
+            ambiguous_with,
+            disambiguation,
+            year_suffix: self.year_suffix_for(preview_ref_id),
+            output: ghost.output,
+        };
+
+        self.set_all_keys_with_durability(old_keys, Durability::MEDIUM);
+        self.set_all_uncited_with_durability(old_uncited, Durability::MEDIUM);
+        info
+    }
+
     fn preview_marked_init<'a>(
         &mut self,
         positions: &[ClusterPosition],
@@ -894,8 +1466,14 @@ impl Processor {
     /// ```
     ///
     /// This case is recognised and the order they appear in the input here is the order used for
-    /// determining cite positions (ibid, subsequent, etc). But the position:first cites within
-    /// them will all have the same first-reference-note-number if FRNN is used in later cites.
+    /// determining cite positions (ibid, subsequent, etc). Each cluster is numbered
+    /// [IntraNote::Multi]`(note, index)`, with index counting up from 0 within the note, so two
+    /// calls that list a note's clusters in the same order always resolve them the same way. But
+    /// the position:first cites within them will all have the same first-reference-note-number if
+    /// FRNN is used in later cites.
+    ///
+    /// A note number lower than the one before it is a
+    /// [ReorderingError::NonMonotonicNoteNumber].
     ///
     /// May error without having set_clusters_ordered, but with some set_cluster_note_number-s executed.
     pub fn set_cluster_order(
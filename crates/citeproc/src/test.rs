@@ -20,24 +20,55 @@ macro_rules! assert_cluster {
     };
 }
 
+/// Renders each reference's title, in both the citation and the bibliography.
+const TITLE_STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout>
+                <text variable="title" />
+            </layout>
+        </citation>
+        <bibliography>
+            <layout>
+                <text variable="title" />
+            </layout>
+        </bibliography>
+    </style>
+"##;
+
 fn test_db(style: Option<&str>) -> Processor {
-    static INIT_ONCE: Once = Once::new();
-    INIT_ONCE.call_once(|| {
-        env_logger::init();
-    });
-    Processor::new(InitOptions {
+    test_db_with(InitOptions {
         style: style.unwrap_or(
             r#"<style version="1.0" class="in-text">
                                     <citation><layout></layout></citation>
                                   </style>"#,
         ),
+        ..Default::default()
+    })
+}
+
+/// Always plain text in test mode; use `set_output_format` for anything else.
+fn test_db_with(options: InitOptions) -> Processor {
+    static INIT_ONCE: Once = Once::new();
+    INIT_ONCE.call_once(|| {
+        env_logger::init();
+    });
+    Processor::new(InitOptions {
         format: SupportedFormat::Plain,
         test_mode: true,
-        ..Default::default()
+        ..options
     })
     .unwrap()
 }
 
+/// A library of basic `refs`, and `cited` cited one per note, in order.
+fn fixture(style: Option<&str>, refs: &[&str], cited: &[&str]) -> Processor {
+    let mut db = test_db(style);
+    insert_basic_refs(&mut db, refs);
+    insert_ascending_notes(&mut db, cited);
+    db
+}
+
 fn insert_basic_refs(db: &mut Processor, ref_ids: &[&str]) {
     for &id in ref_ids {
         let mut refr = Reference::empty(Atom::from(id), CslType::Book);
@@ -81,6 +112,34 @@ mod cluster_order {
         db.set_cluster_order(&[ClusterPosition::in_text(one), ClusterPosition::in_text(two)])
             .unwrap();
     }
+
+    #[test]
+    fn get_cluster_order() {
+        let mut db = test_db(None);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        let three = cid(&mut db, 3);
+        for &id in &[one, two, three] {
+            db.insert_cites(id, &[Cite::basic("r1")]);
+        }
+        let order = vec![
+            ClusterPosition::note(three, 1),
+            ClusterPosition::in_text(one),
+            ClusterPosition::note(two, 1),
+        ];
+        db.set_cluster_order(&order).unwrap();
+        assert_eq!(db.get_cluster_order(), order);
+        assert_eq!(
+            db.get_cluster_order_str(),
+            vec![
+                string_id::ClusterPosition::note("3".into(), 1),
+                string_id::ClusterPosition::in_text("1".into()),
+                string_id::ClusterPosition::note("2".into(), 1),
+            ]
+        );
+        db.remove_cluster(three);
+        assert_eq!(db.get_cluster_order(), &order[1..]);
+    }
 }
 
 mod position {
@@ -157,9 +216,83 @@ mod position {
         test_ibid_1_2(
             |one, two| vec![ClusterPosition::note(one, 1), ClusterPosition::in_text(two)],
             (Position::First, None),
-            // XXX: should probably preserve relative ordering of notes and in-text clusters,
-            // so that this gets (Position::Subsequent, Some(1))
-            (Position::First, None),
+            // Relative ordering of notes and in-text clusters is preserved
+            (Position::Subsequent, Some(1)),
+        );
+    }
+
+    #[test]
+    fn cite_positions_mixed_intext_between_notes() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        let three = cid(&mut db, 3);
+        for &id in &[one, two, three] {
+            db.insert_cites(id, &[Cite::basic("one")]);
+        }
+        db.set_cluster_order(&[
+            ClusterPosition::note(one, 1),
+            ClusterPosition::in_text(two),
+            ClusterPosition::note(three, 2),
+        ])
+        .unwrap();
+        let poss = db.cite_positions();
+        let id1 = db.cluster_cites(one.raw())[0];
+        let id2 = db.cluster_cites(two.raw())[0];
+        let id3 = db.cluster_cites(three.raw())[0];
+        assert_eq!(poss[&id1], (Position::First, None));
+        // No note number of its own, but FRNN still refers to the note.
+        assert_eq!(poss[&id2], (Position::Subsequent, Some(1)));
+        // The in-text cluster came in between, so this is not ibid.
+        assert_eq!(poss[&id3], (Position::NearNote, Some(1)));
+        assert_eq!(db.cluster_note_number(two), Some(ClusterNumber::InText(1)));
+    }
+
+    #[test]
+    fn same_note_keeps_input_order() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        for &id in &[one, two] {
+            db.insert_cites(id, &[Cite::basic("one")]);
+        }
+        let id1 = db.cluster_cites(one.raw())[0];
+        let id2 = db.cluster_cites(two.raw())[0];
+
+        db.set_cluster_order(&[ClusterPosition::note(one, 1), ClusterPosition::note(two, 1)])
+            .unwrap();
+        assert_eq!(
+            db.cluster_note_number(one),
+            Some(ClusterNumber::Note(IntraNote::Multi(1, 0)))
+        );
+        assert_eq!(
+            db.cluster_note_number(two),
+            Some(ClusterNumber::Note(IntraNote::Multi(1, 1)))
+        );
+        assert_eq!(db.cite_positions()[&id2].0, Position::IbidNear);
+
+        db.set_cluster_order(&[ClusterPosition::note(two, 1), ClusterPosition::note(one, 1)])
+            .unwrap();
+        assert_eq!(
+            db.cluster_note_number(two),
+            Some(ClusterNumber::Note(IntraNote::Multi(1, 0)))
+        );
+        assert_eq!(db.cite_positions()[&id1].0, Position::IbidNear);
+    }
+
+    #[test]
+    fn decreasing_note_number() {
+        let mut db = test_db(None);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        for &id in &[one, two] {
+            db.insert_cites(id, &[Cite::basic("one")]);
+        }
+        assert_eq!(
+            db.set_cluster_order(&[ClusterPosition::note(one, 2), ClusterPosition::note(two, 1)]),
+            Err(ReorderingError::NonMonotonicNoteNumber(1))
         );
     }
 
@@ -417,3 +550,1520 @@ mod terms {
         )
     }
 }
+
+mod document_stats {
+    use super::*;
+
+    #[test]
+    fn counts_positioned_clusters() {
+        let mut db = fixture(
+            Some(TITLE_STYLE),
+            &["one", "two", "three"],
+            &["one", "two", "one"],
+        );
+        // not positioned, so not counted
+        let unpositioned = cid(&mut db, 4);
+        db.insert_cites(unpositioned, &[Cite::basic("three")]);
+        let stats = db.document_stats();
+        assert_eq!(
+            stats,
+            DocumentStats {
+                clusters: 3,
+                cites: 3,
+                bib_entries: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn no_bibliography() {
+        let db = fixture(None, &["one"], &["one"]);
+        assert_eq!(db.document_stats().bib_entries, 0);
+    }
+}
+
+mod disambiguation_index {
+    use super::*;
+    use citeproc_io::{Name, PersonName};
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout delimiter="; ">
+                <group delimiter=", ">
+                    <names variable="author">
+                        <name form="short" />
+                    </names>
+                    <text variable="title" />
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn insert_authored(db: &mut Processor, id: &str, family: &str, title: &str) {
+        let mut refr = Reference::empty(Atom::from(id), CslType::Book);
+        refr.ordinary.insert(Variable::Title, title.to_string());
+        refr.name.insert(
+            NameVariable::Author,
+            vec![Name::Person(PersonName {
+                family: Some(family.into()),
+                given: Some("Alice".into()),
+                ..Default::default()
+            })],
+        );
+        db.insert_reference(refr);
+    }
+
+    #[test]
+    fn shared_surname() {
+        let mut db = test_db(Some(STYLE));
+        insert_authored(&mut db, "r2", "Smith", "Second");
+        insert_authored(&mut db, "r1", "Smith", "First");
+        insert_authored(&mut db, "r3", "Jones", "Third");
+        insert_ascending_notes(&mut db, &["r1", "r2", "r3"]);
+        let index = db.disambiguation_index();
+        let ids = |token: &str| index.get(token).cloned().unwrap_or_default();
+        assert_eq!(ids("Smith"), vec![Atom::from("r1"), Atom::from("r2")]);
+        assert_eq!(ids("Jones"), vec![Atom::from("r3")]);
+        assert_eq!(ids("First"), vec![Atom::from("r1")]);
+    }
+
+    #[test]
+    fn uncited_refs_excluded() {
+        let mut db = test_db(Some(STYLE));
+        insert_authored(&mut db, "r1", "Smith", "First");
+        insert_authored(&mut db, "r2", "Smith", "Second");
+        insert_ascending_notes(&mut db, &["r1"]);
+        let index = db.disambiguation_index();
+        assert_eq!(index.get("Smith"), Some(&vec![Atom::from("r1")]));
+        assert_eq!(index.get("Second"), None);
+    }
+}
+
+mod unresolved_cites {
+    use super::*;
+
+    #[test]
+    fn lists_missing_refs() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one", "two"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        let unpositioned = cid(&mut db, 3);
+        db.insert_cites(one, &[Cite::basic("one"), Cite::basic("missing")]);
+        db.insert_cites(two, &[Cite::basic("gone"), Cite::basic("two")]);
+        db.insert_cites(unpositioned, &[Cite::basic("ignored")]);
+        db.set_cluster_order(&[ClusterPosition::note(one, 1), ClusterPosition::note(two, 2)])
+            .unwrap();
+        assert_eq!(
+            db.unresolved_cites(),
+            vec![
+                (one, 1, Atom::from("missing")),
+                (two, 0, Atom::from("gone")),
+            ]
+        );
+        assert_eq!(
+            db.unresolved_cites_str(),
+            vec![
+                (SmartString::from("1"), 1, Atom::from("missing")),
+                (SmartString::from("2"), 0, Atom::from("gone")),
+            ]
+        );
+        // loading the reference resolves it
+        insert_basic_refs(&mut db, &["missing"]);
+        assert_eq!(db.unresolved_cites(), vec![(two, 0, Atom::from("gone"))]);
+    }
+}
+
+mod bibliography_iter {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout>
+                <text variable="title" />
+            </layout>
+        </citation>
+        <bibliography>
+            <sort>
+                <key variable="title" />
+            </sort>
+            <layout>
+                <text variable="title" />
+            </layout>
+        </bibliography>
+    </style>
+"##;
+
+    #[test]
+    fn counts_entries() {
+        let db = fixture(Some(STYLE), &["c", "a", "b", "uncited"], &["c", "a", "b"]);
+        let mut count = 0;
+        for entry in db.bibliography_iter() {
+            assert!(!entry.value.is_empty());
+            count += 1;
+        }
+        assert_eq!(count, 3);
+        let ids: Vec<_> = db.bibliography_iter().map(|entry| entry.id).collect();
+        assert_eq!(ids, vec![Atom::from("a"), Atom::from("b"), Atom::from("c")]);
+    }
+}
+
+mod cluster_author_prefix {
+    use super::*;
+    use citeproc_io::{Name, PersonName};
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout prefix="(" suffix=")" delimiter="; ">
+                <group delimiter=" ">
+                    <names variable="author" />
+                    <text variable="title" />
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn insert_authored(db: &mut Processor, id: &str, family: &str) {
+        let mut refr = Reference::empty(Atom::from(id), CslType::Book);
+        refr.ordinary.insert(Variable::Title, id.to_string());
+        refr.name.insert(
+            NameVariable::Author,
+            vec![Name::Person(PersonName {
+                family: Some(family.into()),
+                given: Some("John".into()),
+                ..Default::default()
+            })],
+        );
+        db.insert_reference(refr);
+    }
+
+    #[test]
+    fn authors_only() {
+        let mut db = test_db(Some(STYLE));
+        insert_authored(&mut db, "one", "Doe");
+        insert_authored(&mut db, "two", "Roe");
+        insert_basic_refs(&mut db, &["anon"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        let anon = cid(&mut db, 3);
+        let unpositioned = cid(&mut db, 4);
+        db.insert_cites(one, &[Cite::basic("one")]);
+        db.insert_cites(two, &[Cite::basic("one"), Cite::basic("two")]);
+        db.insert_cites(anon, &[Cite::basic("anon")]);
+        db.insert_cites(unpositioned, &[Cite::basic("one")]);
+        db.set_cluster_order(&[
+            ClusterPosition::in_text(one),
+            ClusterPosition::in_text(two),
+            ClusterPosition::in_text(anon),
+        ])
+        .unwrap();
+        assert_cluster!(db.cluster_author_prefix(one), Some("John Doe"));
+        assert_cluster!(
+            db.cluster_author_prefix_str("2"),
+            Some("John Doe; John Roe")
+        );
+        assert_cluster!(db.cluster_author_prefix(anon), None);
+        assert_cluster!(db.cluster_author_prefix(unpositioned), None);
+        // The cluster itself is unchanged
+        assert_cluster!(db.get_cluster(one), Some("(John Doe one)"));
+    }
+}
+
+mod note_number {
+    use super::*;
+
+    #[test]
+    fn merged_footnotes() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one", "two"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        let three = cid(&mut db, 3);
+        db.insert_cites(one, &[Cite::basic("one")]);
+        db.insert_cites(two, &[Cite::basic("two")]);
+        db.insert_cites(three, &[Cite::basic("one")]);
+        db.set_cluster_order(&[
+            ClusterPosition::note(one, 1),
+            ClusterPosition::note(two, 1),
+            ClusterPosition::note(three, 2),
+        ])
+        .unwrap();
+        assert_eq!(db.get_note_number(one), Some(1));
+        assert_eq!(db.get_note_number(two), Some(1));
+        assert_eq!(db.get_note_number(three), Some(2));
+        assert_eq!(db.get_note_number_str("3"), Some(2));
+    }
+
+    #[test]
+    fn in_text_and_unpositioned() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        db.insert_cites(one, &[Cite::basic("one")]);
+        db.insert_cites(two, &[Cite::basic("one")]);
+        db.set_cluster_order(&[ClusterPosition::in_text(one)])
+            .unwrap();
+        assert_eq!(db.get_note_number(one), None);
+        assert_eq!(db.get_note_number(two), None);
+    }
+}
+
+mod cluster_cite_positions {
+    use super::*;
+    use csl::Position;
+
+    #[test]
+    fn subsequent_and_ibid() {
+        let mut db = fixture(None, &["one", "two"], &["one", "two", "one", "one"]);
+        let one = cid(&mut db, 1);
+        let three = cid(&mut db, 3);
+        let four = cid(&mut db, 4);
+        assert_eq!(db.cluster_cite_positions(one), vec![Position::First]);
+        assert_eq!(db.cluster_cite_positions(three), vec![Position::NearNote]);
+        assert_eq!(db.cluster_cite_positions(four), vec![Position::IbidNear]);
+        assert_eq!(db.cluster_cite_positions_str("4"), vec![Position::IbidNear]);
+    }
+
+    #[test]
+    fn within_a_cluster() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one", "two"]);
+        let one = cid(&mut db, 1);
+        db.insert_cites(
+            one,
+            &[Cite::basic("one"), Cite::basic("two"), Cite::basic("one")],
+        );
+        db.set_cluster_order(&[ClusterPosition::note(one, 1)])
+            .unwrap();
+        assert_eq!(
+            db.cluster_cite_positions(one),
+            vec![Position::First, Position::First, Position::NearNote]
+        );
+    }
+
+    #[test]
+    fn unpositioned() {
+        let mut db = test_db(None);
+        let one = cid(&mut db, 1);
+        db.insert_cites(one, &[Cite::basic("one")]);
+        assert_eq!(db.cluster_cite_positions(one), vec![]);
+    }
+}
+
+mod locale_override {
+    use super::*;
+
+    const STYLE: &'static str = r#"<style class="in-text" version="1.0.1">
+        <citation><layout><text term="and" /></layout></citation>
+    </style>"#;
+
+    const DE_DE: &'static str = r#"<?xml version="1.0" encoding="utf-8"?>
+        <locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="de-DE">
+        <terms><term name="and">und</term></terms></locale>"#;
+
+    #[test]
+    fn switch_to_unfetched_locale() {
+        let mut db = fixture(Some(STYLE), &["one"], &["one"]);
+        let one = cid(&mut db, 1);
+        let de_de: Lang = "de-DE".parse().unwrap();
+        assert!(!db.get_langs_in_use().contains(&de_de));
+        assert_cluster!(db.get_cluster(one), Some("and"));
+
+        db.set_locale_override(Some(de_de.clone()));
+        // the consumer should be told to fetch it
+        assert!(db.get_langs_in_use().contains(&de_de));
+        assert!(!db.has_cached_locale(&de_de));
+        db.store_locales(vec![(de_de.clone(), DE_DE.to_string())]);
+        assert_cluster!(db.get_cluster(one), Some("und"));
+
+        db.set_locale_override(None);
+        assert!(!db.get_langs_in_use().contains(&de_de));
+        assert_cluster!(db.get_cluster(one), Some("and"));
+    }
+}
+
+mod locale_by_language {
+    use super::*;
+    use citeproc_db::PredefinedLocales;
+
+    const STYLE: &'static str = r#"<style class="in-text" version="1.0.1">
+        <features><feature name="locale-by-language" /></features>
+        <citation><layout delimiter="; "><text term="and" /></layout></citation>
+    </style>"#;
+
+    const FR_FR: &'static str = r#"<?xml version="1.0" encoding="utf-8"?>
+        <locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="fr-FR">
+        <terms><term name="and">et</term></terms></locale>"#;
+
+    #[test]
+    fn reference_languages_are_fetched() {
+        let fr_fr: Lang = "fr-FR".parse().unwrap();
+        let mut map = HashMap::new();
+        map.insert(fr_fr.clone(), FR_FR.to_string());
+        let mut db = test_db_with(InitOptions {
+            style: STYLE,
+            fetcher: Some(Arc::new(PredefinedLocales(map))),
+            ..Default::default()
+        });
+        insert_basic_refs(&mut db, &["one"]);
+        assert!(!db.get_langs_in_use().contains(&fr_fr));
+
+        let mut refr = Reference::empty(Atom::from("two"), CslType::Book);
+        refr.language = Some(fr_fr.clone());
+        db.insert_reference(refr);
+        // the consumer should be told to fetch it
+        assert!(db.get_langs_in_use().contains(&fr_fr));
+
+        insert_ascending_notes(&mut db, &["one", "two"]);
+        let two = cid(&mut db, 2);
+        assert_cluster!(db.get_cluster(two), Some("et"));
+    }
+}
+
+mod macro_recursion {
+    use super::*;
+
+    // The parser refuses recursive macros, so make one by hand: `b` ends up calling itself.
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <macro name="a">
+            <group delimiter=" ">
+                <text value="a" />
+                <text macro="b" />
+            </group>
+        </macro>
+        <macro name="b">
+            <text value="b" />
+        </macro>
+        <citation>
+            <layout>
+                <text macro="a" />
+            </layout>
+        </citation>
+        <bibliography>
+            <sort>
+                <key macro="b" />
+            </sort>
+            <layout>
+                <text macro="b" />
+            </layout>
+        </bibliography>
+    </style>
+"##;
+
+    #[test]
+    fn renders_inner_call_as_empty() {
+        let mut db = fixture(Some(STYLE), &["one"], &["one"]);
+        let mut style = Style::clone(&db.get_style());
+        let calls_b = style.macros["a"].clone();
+        style.macros.insert("b".into(), calls_b);
+        db.set_style(Arc::new(style));
+        let one = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(one), Some("a a"));
+        let bib = db.get_bibliography();
+        assert_eq!(bib.len(), 1);
+        assert_eq!(bib[0].value.as_str(), "a");
+    }
+}
+
+mod document_state {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout delimiter="; ">
+                <group delimiter=", ">
+                    <text variable="title" />
+                    <text variable="locator" />
+                </group>
+            </layout>
+        </citation>
+        <bibliography>
+            <sort>
+                <key variable="title" />
+            </sort>
+            <layout>
+                <text variable="title" />
+            </layout>
+        </bibliography>
+    </style>
+"##;
+
+    fn populate(db: &mut Processor) {
+        insert_basic_refs(db, &["one", "two", "three"]);
+        let one = cid(db, 1);
+        let two = cid(db, 2);
+        let unpositioned = cid(db, 3);
+        let mut with_locator = Cite::basic("two");
+        with_locator.prefix = Some("see ".into());
+        with_locator.locators = Some(Locators::Single(Locator {
+            locator: NumberLike::Str("5".into()),
+            loc_type: LocatorType::Page,
+        }));
+        db.init_clusters(vec![
+            Cluster::new(one, vec![Cite::basic("one")], None),
+            Cluster::new(two, vec![Cite::basic("one"), with_locator], None),
+            Cluster::new(unpositioned, vec![Cite::basic("three")], None),
+        ]);
+        db.set_cluster_order(&[ClusterPosition::note(one, 1), ClusterPosition::note(two, 1)])
+            .unwrap();
+        db.include_uncited(IncludeUncited::Specific(vec!["three".into()]));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut db = test_db(Some(STYLE));
+        populate(&mut db);
+        let state = db.export_state();
+        let json = serde_json::to_string(&state).unwrap();
+
+        let mut restored = test_db(Some(STYLE));
+        restored
+            .import_state(serde_json::from_str(&json).unwrap())
+            .unwrap();
+        assert_eq!(restored.export_state(), state);
+        assert_eq!(restored.get_bibliography(), db.get_bibliography());
+        assert_eq!(restored.all_clusters_str(), db.all_clusters_str());
+        let two = restored.cluster_id("2");
+        assert_eq!(restored.get_note_number(two), Some(1));
+        assert_cluster!(restored.get_cluster(two), Some("Book one; see Book two, 5"));
+    }
+
+    #[test]
+    fn custom_object() {
+        let mut db = test_db(Some(STYLE));
+        let refr: Reference = serde_json::from_str(
+            r#"{ "id": "one", "title": "Book one", "custom": { "shelf": "B3" } }"#,
+        )
+        .unwrap();
+        db.insert_reference(refr);
+        let custom = Some(serde_json::json!({ "shelf": "B3" }));
+        assert_eq!(db.get_reference("one".into()).unwrap().custom, custom);
+
+        let json = serde_json::to_string(&db.export_state()).unwrap();
+        let mut restored = test_db(Some(STYLE));
+        restored
+            .import_state(serde_json::from_str(&json).unwrap())
+            .unwrap();
+        assert_eq!(restored.get_reference("one".into()).unwrap().custom, custom);
+    }
+}
+
+mod render_full_html {
+    use super::*;
+    use citeproc_io::output::markup::FormatOptions;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout>
+                <text variable="title" />
+            </layout>
+        </citation>
+        <bibliography hanging-indent="true" entry-spacing="2">
+            <sort>
+                <key variable="title" />
+            </sort>
+            <layout>
+                <text variable="title" font-style="italic" />
+            </layout>
+        </bibliography>
+    </style>
+"##;
+
+    #[test]
+    fn snapshot() {
+        let mut db = fixture(Some(STYLE), &["b", "a", "uncited"], &["b", "a"]);
+        db.set_output_format(SupportedFormat::Html, FormatOptions::default());
+        assert_eq!(
+            db.render_full_html(),
+            r#"<style>
+.csl-bib-body { line-height: 1.35; }
+.csl-entry { margin-bottom: 2.7em; padding-left: 2em; text-indent: -2em; }
+</style>
+<div class="csl-citations">
+  <p class="csl-citation">Book b</p>
+  <p class="csl-citation">Book a</p>
+</div>
+<div class="csl-bib-body">
+  <div class="csl-entry"><i>Book a</i></div>
+  <div class="csl-entry"><i>Book b</i></div>
+</div>
+"#
+        );
+    }
+
+    #[test]
+    fn non_html_format() {
+        let mut db = test_db(Some(STYLE));
+        let mut refr = Reference::empty(Atom::from("one"), CslType::Book);
+        refr.ordinary.insert(Variable::Title, "Q&A".into());
+        db.insert_reference(refr);
+        insert_ascending_notes(&mut db, &["one"]);
+        for &format in &[SupportedFormat::Plain, SupportedFormat::Rtf] {
+            db.set_output_format(format, FormatOptions::default());
+            let html = db.render_full_html();
+            assert!(
+                html.contains("<p class=\"csl-citation\">Q&amp;A</p>"),
+                "{}",
+                html
+            );
+            assert!(
+                html.contains("<div class=\"csl-entry\"><i>Q&amp;A</i></div>"),
+                "{}",
+                html
+            );
+        }
+        // the processor's own output format is left alone
+        assert_eq!(db.get_bib_item(Atom::from("one")).as_str(), "{\\i Q&A}");
+    }
+
+    #[test]
+    fn bib_inline_styles() {
+        let mut db = test_db(Some(STYLE));
+        db.set_output_format(
+            SupportedFormat::Html,
+            FormatOptions {
+                bib_inline_styles: true,
+                ..Default::default()
+            },
+        );
+        let meta = db.get_bibliography_meta().unwrap();
+        assert_eq!(
+            serde_json::to_value(&meta.format_meta).unwrap(),
+            serde_json::json!({
+                "markupPre": "<div class=\"csl-bib-body\" style=\"line-height: 1.35;\">",
+                "markupPost": "</div>",
+                "entryPre": "<div class=\"csl-entry\" style=\"margin-bottom: 2.7em; padding-left: 2em; text-indent: -2em;\">",
+                "entryPost": "</div>\n",
+            })
+        );
+        // and the bibliography string and full HTML export use the same wrappers
+        insert_basic_refs(&mut db, &["a"]);
+        db.include_uncited(IncludeUncited::All);
+        let body = "<div class=\"csl-bib-body\" style=\"line-height: 1.35;\">";
+        let entry = "<div class=\"csl-entry\" style=\"margin-bottom: 2.7em; padding-left: 2em; text-indent: -2em;\"><i>Book a</i></div>\n";
+        assert_eq!(
+            db.get_bibliography_string(),
+            format!("{}{}</div>", body, entry)
+        );
+        assert!(db
+            .render_full_html()
+            .ends_with(&format!("{}\n  {}</div>\n", body, entry)));
+        // off by default
+        db.set_output_format(SupportedFormat::Html, FormatOptions::default());
+        let meta = db.get_bibliography_meta().unwrap();
+        assert_eq!(
+            serde_json::to_value(&meta.format_meta).unwrap()["markupPre"],
+            "<div class=\"csl-bib-body\">"
+        );
+    }
+
+    #[test]
+    fn no_bibliography() {
+        let mut db = test_db(None);
+        db.set_output_format(SupportedFormat::Html, FormatOptions::default());
+        assert_eq!(
+            db.render_full_html(),
+            "<div class=\"csl-citations\">\n</div>\n"
+        );
+    }
+}
+
+mod cite_anchors {
+    use super::*;
+    use citeproc_io::output::markup::FormatOptions;
+
+    #[test]
+    fn bib_item_matches_bibliography() {
+        let mut db = fixture(Some(TITLE_STYLE), &["one"], &["one"]);
+        db.set_output_format(
+            SupportedFormat::Html,
+            FormatOptions {
+                cite_anchors: true,
+                ..Default::default()
+            },
+        );
+        let bib = db.get_bibliography();
+        assert_eq!(bib[0].value.as_str(), r#"<a id="ref-one"></a>Book one"#);
+        assert_eq!(db.get_bib_item(Atom::from("one")), bib[0].value);
+
+        db.set_output_format(SupportedFormat::Html, FormatOptions::default());
+        assert_eq!(db.get_bib_item(Atom::from("one")).as_str(), "Book one");
+    }
+}
+
+mod year_suffix_alphabet {
+    use super::*;
+    use citeproc_io::DateOrRange;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation disambiguate-add-year-suffix="true">
+            <layout delimiter="; ">
+                <date variable="issued" form="numeric" date-parts="year" />
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn same_year_db(alphabet: Option<Vec<char>>, count: usize) -> (Processor, ClusterId) {
+        let mut db = test_db_with(InitOptions {
+            style: STYLE,
+            year_suffix_alphabet: alphabet,
+            ..Default::default()
+        });
+        let mut cites = Vec::with_capacity(count);
+        for i in 1..=count {
+            let id = Atom::from(format!("r{}", i));
+            let mut refr = Reference::empty(id.clone(), CslType::Book);
+            refr.date
+                .insert(DateVariable::Issued, DateOrRange::new(2000, 0, 0));
+            db.insert_reference(refr);
+            cites.push(Cite::basic(id));
+        }
+        let one = cid(&mut db, 1);
+        db.insert_cites(one, &cites);
+        db.set_cluster_order(&[ClusterPosition::note(one, 1)])
+            .unwrap();
+        (db, one)
+    }
+
+    #[test]
+    fn custom_alphabet() {
+        let (db, one) = same_year_db(Some(vec!['α', 'β', 'γ']), 5);
+        assert_cluster!(
+            db.get_cluster(one),
+            Some("2000α; 2000β; 2000γ; 2000αα; 2000αβ")
+        );
+    }
+
+    #[test]
+    fn empty_alphabet_is_ignored() {
+        let (db, one) = same_year_db(Some(Vec::new()), 2);
+        assert_cluster!(db.get_cluster(one), Some("2000a; 2000b"));
+    }
+}
+
+mod style_defaults {
+    use super::*;
+
+    #[test]
+    fn from_style_attributes() {
+        let db = test_db(Some(
+            r#"<style class="in-text" version="1.0.1" default-locale="de-AT"
+                page-range-format="chicago" demote-non-dropping-particle="never">
+                <citation><layout></layout></citation>
+            </style>"#,
+        ));
+        assert_eq!(
+            db.style_defaults(),
+            StyleDefaults {
+                default_locale: Some("de-AT".parse().unwrap()),
+                page_range_format: Some(PageRangeFormat::Chicago),
+                demote_non_dropping_particle: DemoteNonDroppingParticle::Never,
+            }
+        );
+    }
+
+    #[test]
+    fn unset() {
+        let db = test_db(None);
+        assert_eq!(
+            db.style_defaults(),
+            StyleDefaults {
+                default_locale: None,
+                page_range_format: None,
+                demote_non_dropping_particle: DemoteNonDroppingParticle::DisplayAndSort,
+            }
+        );
+    }
+}
+
+mod style_class {
+    use super::*;
+
+    #[test]
+    fn footnote() {
+        let db = test_db(Some(
+            r#"<style class="note" version="1.0.1">
+                <citation><layout><text variable="title" /></layout></citation>
+            </style>"#,
+        ));
+        assert_eq!(db.style_class(), StyleClass::Note);
+        assert!(!db.is_numeric_style());
+    }
+
+    #[test]
+    fn author_date() {
+        let db = test_db(Some(
+            r#"<style class="in-text" version="1.0.1">
+                <citation>
+                    <layout delimiter="; ">
+                        <names variable="author" />
+                        <date variable="issued" form="numeric" date-parts="year" />
+                    </layout>
+                </citation>
+            </style>"#,
+        ));
+        assert_eq!(db.style_class(), StyleClass::InText);
+        assert!(!db.is_numeric_style());
+    }
+
+    #[test]
+    fn numeric_via_macro() {
+        let db = test_db(Some(
+            r#"<style class="in-text" version="1.0.1">
+                <macro name="num">
+                    <choose>
+                        <if type="book"><text value="book" /></if>
+                        <else><text variable="citation-number" /></else>
+                    </choose>
+                </macro>
+                <citation>
+                    <layout prefix="[" suffix="]"><text macro="num" /></layout>
+                </citation>
+                <bibliography><layout><text variable="title" /></layout></bibliography>
+            </style>"#,
+        ));
+        assert_eq!(db.style_class(), StyleClass::InText);
+        assert!(db.is_numeric_style());
+    }
+}
+
+mod style_meta {
+    use super::*;
+
+    const STYLE: &'static str = r#"<style class="in-text" version="1.0.1" default-locale="en-GB">
+        <info>
+            <id>https://example.com/my-style</id>
+            <title>My Style</title>
+            <updated>2020-01-01T00:00:00Z</updated>
+            <category citation-format="author-date" />
+        </info>
+        <locale xml:lang="de-DE"><terms><term name="and">und</term></terms></locale>
+        <citation><layout><text variable="title" /></layout></citation>
+        <bibliography><layout><text variable="title" /></layout></bibliography>
+    </style>"#;
+
+    #[test]
+    fn matches_loaded_style() {
+        let db = test_db(Some(STYLE));
+        let meta = db.style_meta();
+        assert_eq!(meta.info.title.value, "My Style");
+        assert_eq!(meta.default_locale, Some("en-GB".parse().unwrap()));
+        assert_eq!(meta.class, Some(StyleClass::InText));
+        assert!(!meta.is_dependent());
+        assert_eq!(meta, StyleMeta::parse(STYLE).unwrap());
+    }
+}
+
+mod unknown_type {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout>
+                <choose>
+                    <if type="article"><text value="article" /></if>
+                    <else-if type="book"><text value="book" /></else-if>
+                    <else-if type="document"><text value="document" /></else-if>
+                    <else><text value="other" /></else>
+                </choose>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn insert_software(db: &mut Processor) {
+        let refs: Vec<Reference> = serde_json::from_str(
+            r#"[
+                { "id": "one", "type": "software", "title": "A program" },
+                { "id": "two", "type": "book", "title": "A book" }
+            ]"#,
+        )
+        .unwrap();
+        db.reset_references(refs);
+        insert_ascending_notes(db, &["one", "two"]);
+    }
+
+    #[test]
+    fn lists_unknown_types() {
+        let mut db = test_db(Some(STYLE));
+        insert_software(&mut db);
+        assert_eq!(
+            db.unknown_reference_types(),
+            vec![(Atom::from("one"), "software".to_owned())]
+        );
+    }
+
+    #[test]
+    fn configured_fallback() {
+        let mut db = test_db_with(InitOptions {
+            style: STYLE,
+            unknown_type_fallback: Some(CslType::Book),
+            ..Default::default()
+        });
+        insert_software(&mut db);
+        assert_cluster!(db.get_cluster(cid(&mut db, 1)), Some("book"));
+    }
+}
+
+mod first_reference_note_number {
+    use super::*;
+
+    #[test]
+    fn first_note_of_reference() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one", "two"]);
+        let first = cid(&mut db, 1);
+        let other = cid(&mut db, 2);
+        let second = cid(&mut db, 3);
+        db.insert_cites(first, &[Cite::basic("one")]);
+        db.insert_cites(other, &[Cite::basic("two")]);
+        db.insert_cites(second, &[Cite::basic("one")]);
+        db.set_cluster_order(&[
+            ClusterPosition::note(first, 3),
+            ClusterPosition::note(other, 5),
+            ClusterPosition::note(second, 7),
+        ])
+        .unwrap();
+        let first_cite = db.cluster_cites(first)[0];
+        let second_cite = db.cluster_cites(second)[0];
+        assert_eq!(db.first_reference_note_number(first_cite), None);
+        assert_eq!(db.first_reference_note_number(second_cite), Some(3));
+    }
+
+    #[test]
+    fn in_text() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one"]);
+        let first = cid(&mut db, 1);
+        let second = cid(&mut db, 2);
+        db.insert_cites(first, &[Cite::basic("one")]);
+        db.insert_cites(second, &[Cite::basic("one")]);
+        db.set_cluster_order(&[
+            ClusterPosition::in_text(first),
+            ClusterPosition::in_text(second),
+        ])
+        .unwrap();
+        let second_cite = db.cluster_cites(second)[0];
+        assert_eq!(db.first_reference_note_number(second_cite), None);
+    }
+}
+
+mod back_references {
+    use super::*;
+
+    #[test]
+    fn note_numbers_per_reference() {
+        let db = fixture(
+            None,
+            &["one", "other"],
+            &["other", "one", "other", "other", "one", "missing"],
+        );
+        let back_refs = db.back_references();
+        assert_eq!(back_refs.len(), 2);
+        assert_eq!(back_refs[&Atom::from("one")], vec![2, 5]);
+        assert_eq!(back_refs[&Atom::from("other")], vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn same_note_counted_once() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one"]);
+        let first = cid(&mut db, 1);
+        let second = cid(&mut db, 2);
+        db.insert_cites(first, &[Cite::basic("one"), Cite::basic("one")]);
+        db.insert_cites(second, &[Cite::basic("one")]);
+        db.set_cluster_order(&[
+            ClusterPosition::note(first, 3),
+            ClusterPosition::note(second, 3),
+        ])
+        .unwrap();
+        assert_eq!(db.back_references()[&Atom::from("one")], vec![3]);
+    }
+}
+
+mod preview_disambiguation {
+    use super::*;
+    use citeproc_io::DateOrRange;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation disambiguate-add-year-suffix="true">
+            <layout delimiter="; ">
+                <date variable="issued" form="numeric" date-parts="year" />
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn mk_db() -> (Processor, ClusterId) {
+        let mut db = test_db(Some(STYLE));
+        for &(id, year) in &[("one", 2000), ("two", 2000), ("three", 2001)] {
+            let mut refr = Reference::empty(Atom::from(id), CslType::Book);
+            refr.date
+                .insert(DateVariable::Issued, DateOrRange::new(year, 0, 0));
+            db.insert_reference(refr);
+        }
+        let one = cid(&mut db, 1);
+        db.insert_cites(one, &[Cite::basic("one")]);
+        db.set_cluster_order(&[ClusterPosition::in_text(one)])
+            .unwrap();
+        (db, one)
+    }
+
+    fn preview(db: &mut Processor, one: ClusterId, ref_id: &str) -> ClusterPreview {
+        let positions = &[
+            ClusterPosition::in_text(one),
+            ClusterPosition::preview_in_text(),
+        ];
+        db.preview_cluster(
+            PreviewCluster::new(vec![Cite::basic(ref_id)], None),
+            PreviewPosition::MarkWithZero(positions),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn ambiguous_preview() {
+        let (mut db, one) = mk_db();
+        let preview = preview(&mut db, one, "two");
+        assert_eq!(preview.output.as_str(), "2000b");
+        assert_eq!(
+            preview.disambiguation,
+            vec![DisambiguationKind::AddYearSuffix]
+        );
+        // the document itself is untouched
+        assert_cluster!(db.get_cluster(one), Some("2000"));
+    }
+
+    #[test]
+    fn unambiguous_preview() {
+        let (mut db, one) = mk_db();
+        let preview = preview(&mut db, one, "three");
+        assert_eq!(preview.output.as_str(), "2001");
+        assert_eq!(preview.disambiguation, Vec::new());
+    }
+}
+
+mod preview_reference_disambiguation {
+    use super::*;
+    use citeproc_io::{DateOrRange, Name, PersonName};
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation disambiguate-add-givenname="true" givenname-disambiguation-rule="by-cite"
+                  disambiguate-add-year-suffix="true">
+            <layout delimiter="; ">
+                <group delimiter=", ">
+                    <names variable="author">
+                        <name form="short" />
+                    </names>
+                    <date variable="issued" form="numeric" date-parts="year" />
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn authored(id: &str, given: &str, family: &str) -> Reference {
+        let mut refr = Reference::empty(Atom::from(id), CslType::Book);
+        refr.date
+            .insert(DateVariable::Issued, DateOrRange::new(2000, 0, 0));
+        refr.name.insert(
+            NameVariable::Author,
+            vec![Name::Person(PersonName {
+                family: Some(family.into()),
+                given: Some(given.into()),
+                ..Default::default()
+            })],
+        );
+        refr
+    }
+
+    fn mk_db() -> (Processor, ClusterId) {
+        let mut db = test_db(Some(STYLE));
+        db.insert_reference(authored("one", "Alice", "Smith"));
+        let one = cid(&mut db, 1);
+        db.insert_cites(one, &[Cite::basic("one")]);
+        db.set_cluster_order(&[ClusterPosition::in_text(one)])
+            .unwrap();
+        (db, one)
+    }
+
+    #[test]
+    fn colliding_surname() {
+        let (mut db, one) = mk_db();
+        let info = db.preview_disambiguation(authored("new", "Bob", "Smith"));
+        assert_eq!(info.ambiguous_with, vec![Atom::from("one")]);
+        assert_eq!(info.disambiguation, vec![DisambiguationKind::AddGivenName]);
+        assert_eq!(info.year_suffix, None);
+        assert_eq!(info.output.as_str(), "Bob Smith, 2000");
+        // the library is untouched
+        assert_cluster!(db.get_cluster(one), Some("Smith, 2000"));
+        assert!(db.disambiguation_index().values().all(|ids| ids.len() == 1));
+    }
+
+    #[test]
+    fn identical_author() {
+        let (mut db, _one) = mk_db();
+        let info = db.preview_disambiguation(authored("new", "Alice", "Smith"));
+        assert_eq!(info.ambiguous_with, vec![Atom::from("one")]);
+        assert!(info
+            .disambiguation
+            .contains(&DisambiguationKind::AddYearSuffix));
+        assert_eq!(info.year_suffix, Some(2));
+    }
+
+    #[test]
+    fn unambiguous() {
+        let (mut db, _one) = mk_db();
+        let info = db.preview_disambiguation(authored("new", "Carol", "Jones"));
+        assert_eq!(info.ambiguous_with, Vec::<Atom>::new());
+        assert_eq!(info.disambiguation, Vec::new());
+        assert_eq!(info.year_suffix, None);
+        assert_eq!(info.output.as_str(), "Jones, 2000");
+    }
+}
+
+mod bibliography_string {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation><layout></layout></citation>
+        <bibliography>
+            <layout>
+                <text variable="title" font-style="italic" />
+            </layout>
+        </bibliography>
+    </style>
+"##;
+
+    fn bib_string(format: SupportedFormat) -> String {
+        let mut db = fixture(Some(STYLE), &["one", "two"], &["one", "two"]);
+        db.set_output_format(format, Default::default());
+        db.get_bibliography_string()
+    }
+
+    #[test]
+    fn rtf_paragraphs() {
+        assert_eq!(
+            bib_string(SupportedFormat::Rtf),
+            "{\\i Book one}\\par\n{\\i Book two}\\par\n"
+        );
+    }
+
+    #[test]
+    fn plain_lines() {
+        assert_eq!(bib_string(SupportedFormat::Plain), "Book one\nBook two\n");
+    }
+
+    #[test]
+    fn html_divs() {
+        assert_eq!(
+            bib_string(SupportedFormat::Html),
+            "<div class=\"csl-bib-body\">\
+             <div class=\"csl-entry\"><i>Book one</i></div>\n\
+             <div class=\"csl-entry\"><i>Book two</i></div>\n\
+             </div>"
+        );
+    }
+
+    #[test]
+    fn no_bibliography() {
+        let db = test_db(None);
+        assert_eq!(db.get_bibliography_string(), "");
+    }
+}
+
+mod reference_builder {
+    use super::*;
+    use citeproc_io::{DateOrRange, Name, PersonName};
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation><layout></layout></citation>
+        <bibliography>
+            <layout delimiter=". ">
+                <names variable="author"><name /></names>
+                <text variable="title" />
+                <date variable="issued"><date-part name="year" /></date>
+            </layout>
+        </bibliography>
+    </style>
+"##;
+
+    #[test]
+    fn preview_built_book() {
+        let mut db = test_db(Some(STYLE));
+        let refr = ReferenceBuilder::new()
+            .id("doe2000")
+            .ty(CslType::Book)
+            .title("A Book")
+            .author(vec![Name::Person(PersonName {
+                family: Some("Doe".into()),
+                given: Some("John".into()),
+                is_latin_cyrillic: true,
+                ..Default::default()
+            })])
+            .issued(DateOrRange::new(2000, 0, 0))
+            .build()
+            .unwrap();
+        assert_eq!(refr.csl_type, CslType::Book);
+        assert_eq!(
+            db.preview_reference(refr, None).as_str(),
+            "John Doe. A Book. 2000"
+        );
+    }
+}
+
+mod test_html {
+    use super::*;
+
+    #[test]
+    fn from_str() {
+        use std::str::FromStr;
+        assert_eq!(
+            SupportedFormat::from_str("test-html"),
+            Ok(SupportedFormat::TestHtml)
+        );
+    }
+}
+
+mod custom_format {
+    use super::*;
+    use citeproc_io::output::FormatCmd;
+
+    /// Uppercases all text, and marks italics with underscores.
+    #[derive(Debug)]
+    struct Shouting;
+
+    impl CustomMarkup for Shouting {
+        fn write_text(&self, dest: &mut SmartString, text: &str) {
+            dest.push_str(&text.to_uppercase());
+        }
+        fn open(&self, dest: &mut SmartString, cmd: FormatCmd) {
+            if cmd == FormatCmd::FontStyleItalic {
+                dest.push('_');
+            }
+        }
+        fn close(&self, dest: &mut SmartString, cmd: FormatCmd) {
+            if cmd == FormatCmd::FontStyleItalic {
+                dest.push('_');
+            }
+        }
+    }
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout delimiter="; ">
+                <text value="see" suffix=" " />
+                <text variable="title" font-style="italic" />
+            </layout>
+        </citation>
+        <bibliography>
+            <layout>
+                <text variable="title" />
+            </layout>
+        </bibliography>
+    </style>
+"##;
+
+    #[test]
+    fn uppercases_text() {
+        let mut db = fixture(Some(STYLE), &["one"], &["one"]);
+        db.set_custom_output_format(Arc::new(Shouting), FormatOptions::default());
+        let one = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(one), Some("SEE _BOOK ONE_"));
+        assert_eq!(db.get_bibliography_string(), "BOOK ONE\n");
+    }
+}
+
+mod empty_rendering_ids {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout>
+                <text variable="title" />
+            </layout>
+        </citation>
+        <bibliography>
+            <layout>
+                <text variable="URL" />
+            </layout>
+        </bibliography>
+    </style>
+"##;
+
+    #[test]
+    fn lists_refs_missing_every_field() {
+        let mut db = test_db(Some(STYLE));
+        insert_basic_refs(&mut db, &["one", "two"]);
+        let mut refr = Reference::empty(Atom::from("three"), CslType::Book);
+        refr.ordinary
+            .insert(Variable::URL, "https://example.com".to_string());
+        db.insert_reference(refr);
+        insert_ascending_notes(&mut db, &["one", "two", "three"]);
+        assert_eq!(
+            db.empty_rendering_ids(),
+            vec![Atom::from("one"), Atom::from("two")]
+        );
+        assert_eq!(db.get_bibliography().len(), 1);
+    }
+}
+
+mod references_changed {
+    use super::*;
+
+    #[test]
+    fn only_dependents_updated() {
+        let mut db = fixture(Some(TITLE_STYLE), &["one", "two"], &["one", "two"]);
+        let one = cid(&mut db, 1);
+        db.batched_updates();
+
+        let mut refr = Reference::empty(Atom::from("one"), CslType::Book);
+        refr.ordinary
+            .insert(Variable::Title, "Retitled".to_string());
+        let two = db.reference_input(Atom::from("two"));
+        let update = db.references_changed(vec![refr, Reference::clone(&two)]);
+        assert_eq!(
+            update.clusters,
+            vec![(one, Arc::new(SmartString::from("Retitled")))]
+        );
+        let bib = update.bibliography.unwrap();
+        assert_eq!(
+            bib.updated_entries.keys().collect::<Vec<_>>(),
+            vec![&Atom::from("one")]
+        );
+        assert_eq!(bib.entry_ids, None);
+    }
+}
+
+mod bibliography_exclude {
+    use super::*;
+
+    #[test]
+    fn keeps_cites() {
+        let mut db = fixture(Some(TITLE_STYLE), &["one", "two"], &["one", "two"]);
+        db.batched_updates();
+
+        db.set_bibliography_exclude(vec![Atom::from("one")]);
+        let update = db.batched_updates();
+        let bib = update.bibliography.unwrap();
+        assert_eq!(bib.entry_ids, Some(vec![Atom::from("two")]));
+        let entries: Vec<_> = db.get_bibliography().into_iter().map(|e| e.id).collect();
+        assert_eq!(entries, vec![Atom::from("two")]);
+        assert!(db.empty_rendering_ids().is_empty());
+        let one = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(one), Some("Book one"));
+
+        db.set_bibliography_exclude(vec![]);
+        assert_eq!(db.get_bibliography().len(), 2);
+    }
+}
+
+mod bibliography_sort_keys {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <macro name="sorter">
+            <group delimiter=" ">
+                <text value="Sorted" />
+                <text variable="title" />
+            </group>
+        </macro>
+        <citation>
+            <layout>
+                <text variable="title" />
+            </layout>
+        </citation>
+        <bibliography>
+            <sort>
+                <key macro="sorter" sort="descending" />
+                <key variable="container-title" />
+            </sort>
+            <layout>
+                <text variable="title" />
+            </layout>
+        </bibliography>
+    </style>
+"##;
+
+    #[test]
+    fn macro_key() {
+        let db = fixture(Some(STYLE), &["one", "two"], &["one", "two"]);
+        let key = |s: &str| SmartString::from(s);
+        assert_eq!(
+            db.bibliography_sort_keys(),
+            vec![
+                (Atom::from("two"), vec![key("Sorted Book two"), key("")]),
+                (Atom::from("one"), vec![key("Sorted Book one"), key("")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn citation_number_key_is_pre_sort() {
+        let db = fixture(
+            Some(
+                r##"
+            <style class="in-text" version="1.0.1">
+                <citation><layout><text variable="title" /></layout></citation>
+                <bibliography>
+                    <sort>
+                        <key variable="title" sort="descending" />
+                        <key variable="citation-number" />
+                    </sort>
+                    <layout><text variable="title" /></layout>
+                </bibliography>
+            </style>
+        "##,
+            ),
+            &["one", "two"],
+            &["one", "two"],
+        );
+        let key = |s: &str| SmartString::from(s);
+        // "two" is numbered 1 in the bibliography, but was sorted as the second cited
+        assert_eq!(
+            db.bibliography_sort_keys(),
+            vec![
+                (Atom::from("two"), vec![key("Book two"), key("2")]),
+                (Atom::from("one"), vec![key("Book one"), key("1")]),
+            ]
+        );
+    }
+}
+
+#[cfg(feature = "debug-ir")]
+mod cluster_ir {
+    use super::*;
+    use serde_json::json;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout>
+                <group prefix="(" suffix=")" delimiter=", ">
+                    <text variable="title" />
+                    <text value="cited" />
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    #[test]
+    fn json_shape() {
+        let mut db = fixture(Some(STYLE), &["one"], &["one"]);
+        let id = cid(&mut db, 1);
+        let trees = db.cluster_ir(id).unwrap();
+        assert_eq!(trees.len(), 1);
+        let layout = serde_json::to_value(&trees[0]).unwrap();
+        assert_eq!(layout["kind"], "seq");
+        assert_eq!(
+            layout["children"][0],
+            json!({
+                "kind": "seq",
+                "groupVars": "Important",
+                "prefix": "(",
+                "suffix": ")",
+                "delimiter": ", ",
+                "children": [
+                    {
+                        "kind": "rendered",
+                        "groupVars": "Important",
+                        "edge": "Output",
+                        "output": "Book one",
+                    },
+                    {
+                        "kind": "rendered",
+                        "groupVars": "Plain",
+                        "edge": "Output",
+                        "output": "cited",
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn unplaced_cluster() {
+        let db = test_db(Some(STYLE));
+        let id = db.cluster_id("unplaced");
+        assert_eq!(db.cluster_ir(id), None);
+    }
+}
+
+mod localized_date_fallback {
+    use super::*;
+    use citeproc_db::PredefinedLocales;
+    use citeproc_io::DateOrRange;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout>
+                <group delimiter=", ">
+                    <text variable="title" />
+                    <date variable="issued" form="numeric" />
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn db_with_en_us(dates: &str) -> (Processor, ClusterId) {
+        let en_us = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+        <locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="en-US">
+        {}</locale>"#,
+            dates
+        );
+        let mut map = HashMap::new();
+        map.insert(Lang::en_us(), en_us);
+        let mut db = test_db_with(InitOptions {
+            style: STYLE,
+            fetcher: Some(Arc::new(PredefinedLocales(map))),
+            ..Default::default()
+        });
+        let mut refr = Reference::empty(Atom::from("one"), CslType::Book);
+        refr.ordinary.insert(Variable::Title, "Book one".into());
+        refr.date
+            .insert(DateVariable::Issued, DateOrRange::new(2000, 0, 0));
+        db.insert_reference(refr);
+        let one = cid(&mut db, 1);
+        db.insert_cites(one, &[Cite::basic("one")]);
+        db.set_cluster_order(&[ClusterPosition::note(one, 1)])
+            .unwrap();
+        (db, one)
+    }
+
+    #[test]
+    fn numeric_falls_back_to_text() {
+        let (db, one) =
+            db_with_en_us(r#"<date form="text"><date-part name="year" prefix="in " /></date>"#);
+        assert_cluster!(db.get_cluster(one), Some("Book one, in 2000"));
+    }
+
+    #[test]
+    fn no_date_forms_renders_nothing() {
+        let (db, one) = db_with_en_us("");
+        assert_cluster!(db.get_cluster(one), Some("Book one"));
+    }
+}
@@ -457,6 +457,10 @@ pub fn parse_human_test(contents: &str, csl_features: Option<csl::Features>) ->
         format_options: FormatOptions {
             // disable these for txt format tests
             link_anchors: false,
+            cite_anchors: false,
+            test_escaping: false,
+            bib_inline_styles: false,
+            cluster_data_attributes: false,
         },
         csl_features,
         bibliography_no_sort: mode.map_or(false, |(_, _, nosort)| nosort),
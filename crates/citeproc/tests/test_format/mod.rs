@@ -314,7 +314,6 @@ pub fn normalise_html(strg: &str, options: &TestInitOptions) -> String {
         return strg.to_string();
     }
     let rep = strg
-        .replace("&#x2f;", "/")
         .replace("&#x27;", "'")
         .replace("&#60;", "&lt;")
         .replace("&#62;", "&gt;")
@@ -38,6 +38,14 @@ fn bool_true() -> bool {
 struct KebabFormatOpts {
     #[serde(default = "bool_true")]
     link_anchors: bool,
+    #[serde(default)]
+    cite_anchors: bool,
+    #[serde(default)]
+    test_escaping: bool,
+    #[serde(default)]
+    bib_inline_styles: bool,
+    #[serde(default)]
+    cluster_data_attributes: bool,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Default, Clone)]
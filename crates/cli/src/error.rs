@@ -57,6 +57,7 @@ pub(crate) fn diagnostics(err: &StyleError, file_map: &FileMap) -> Vec<Result<Di
                 vec![]
             }
         }
+        _ => vec![Err(err.to_string())],
     }
 }
 
@@ -17,8 +17,7 @@ cfg_if! {
     }
 }
 
-use citeproc::input::Reference;
-use clap::{App, Arg, SubCommand};
+use clap::{App, Arg, ArgMatches, SubCommand};
 use directories::ProjectDirs;
 use std::fs;
 use std::path::PathBuf;
@@ -29,7 +28,7 @@ mod error;
 mod pandoc;
 use pandoc_types::definition::{Inline, MetaValue, Pandoc as PandocDocument};
 
-use citeproc::{LocaleFetchError, LocaleFetcher, Processor};
+use citeproc::prelude::*;
 use csl::{Lang, Locale};
 
 fn main() {
@@ -59,6 +58,37 @@ fn main() {
             "Force Pandoc JSON filter mode. Operates on stdin > stdout.\
              \nNormally, you can just use `pandoc -F citeproc-rs`.",
         ))
+        .subcommand(
+            SubCommand::with_name("bibliography")
+                .about("Renders a bibliography containing every reference in a CSL-JSON library")
+                .arg(
+                    Arg::with_name("csl")
+                        .short("c")
+                        .long("csl")
+                        .value_name("FILE")
+                        .help("A CSL style")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("library")
+                        .short("l")
+                        .long("library")
+                        .value_name("FILE.json")
+                        .help("A CSL-JSON file")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .short("f")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .possible_values(&["html", "rtf", "plain"])
+                        .default_value("html")
+                        .takes_value(true),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("disamb-index")
                 .about("Prints the inverted disambiguation index for the reference library"),
@@ -177,6 +207,11 @@ fn main() {
         return;
     }
 
+    if let Some(matches) = matches.subcommand_matches("bibliography") {
+        do_bibliography(matches, filesystem_fetcher);
+        return;
+    }
+
     // if let Some(_) = matches.subcommand_matches("disamb-index") {
     //     let mut db = Processor::new(filesystem_fetcher);
     //     db.insert_references(refs);
@@ -193,32 +228,33 @@ fn main() {
     if let Some(csl_path) = matches.value_of("csl") {
         let key = matches
             .value_of("key")
-            .map(citeproc::Atom::from)
+            .map(Atom::from)
             .unwrap_or("quagmire2018".into());
 
         let text = fs::read_to_string(&csl_path).expect("No CSL file found at that path");
 
-        match Processor::new(&text, filesystem_fetcher) {
+        let init = InitOptions {
+            style: &text,
+            fetcher: Some(filesystem_fetcher),
+            ..Default::default()
+        };
+        match Processor::new(init) {
             Ok(mut db) => {
                 let refs = if let Some(library_path) = matches.value_of("library") {
                     expect_refs(library_path)
                 } else {
                     serde_json::from_str(&lib_text).expect("sample lib_text not parseable")
                 };
+                db.reset_references(refs);
 
-                db.init_clusters(vec![citeproc::input::Cluster {
-                    id: 0,
-                    note_number: 1,
-                    cites: vec![citeproc::input::Cite::basic(key)],
-                }]);
-                db.inssert_references(refs);
-
-                let inlines = db.get_cluster(0).to_vec();
+                let id = db.cluster_id("cluster");
+                db.insert_cites(id, &[Cite::basic(key)]);
+                db.set_cluster_order(&[ClusterPosition::note(id, 1)])
+                    .expect("a single note cluster is always in order");
 
-                use pandoc_types::definition::{Block, Meta, Pandoc};
-                let doc = Pandoc(Meta::null(), vec![Block::Para(inlines)]);
-                let out = serde_json::to_string(&doc).unwrap();
-                println!("{}", out);
+                if let Some(built) = db.get_cluster(id) {
+                    println!("{}", built);
+                }
             }
             Err(e) => {
                 self::error::file_diagnostics(&e, &csl_path, &text);
@@ -250,7 +286,11 @@ fn do_pandoc() {
     let filter_args = App::new("pandoc_filter")
         .arg(Arg::with_name("output_format").required(false).index(1))
         .get_matches();
-    let _output_format = filter_args.value_of("output_format").unwrap_or("none");
+    // pandoc passes the output format it is writing, e.g. `html` or `latex`
+    let format = match filter_args.value_of("output_format") {
+        Some("html") | Some("html4") | Some("html5") => SupportedFormat::Html,
+        _ => SupportedFormat::Plain,
+    };
     let input = std::io::stdin();
     // already LineWriter buffered, but we're only writing one line of JSON so not a problem
     let output = std::io::stdout();
@@ -261,14 +301,19 @@ fn do_pandoc() {
     let csl_path = pandoc_meta_str(&doc, "csl").expect("No csl path provided through metadata");
     let text = fs::read_to_string(&csl_path).expect("No CSL file found at that path");
 
-    match Processor::new(&text, Arc::new(Filesystem::default())) {
+    let init = InitOptions {
+        style: &text,
+        fetcher: Some(Arc::new(Filesystem::default())),
+        format,
+        ..Default::default()
+    };
+    match Processor::new(init) {
         Ok(mut db) => {
             if let Some(library_path) = pandoc_meta_str(&doc, "bibliography") {
                 db.reset_references(expect_refs(library_path));
             }
-            db.init_clusters(pandoc::get_clusters(&mut doc));
-            db.compute();
-            pandoc::write_clusters(&mut doc, &db);
+            pandoc::insert_clusters(&mut doc, &mut db);
+            pandoc::write_clusters(&mut doc, &db, format);
             serde_json::to_writer(output, &doc).expect("could not write pandoc json");
         }
         Err(e) => {
@@ -277,6 +322,36 @@ fn do_pandoc() {
     }
 }
 
+/// Prints each bibliography entry on its own line, treating every reference in the library as
+/// uncited.
+fn do_bibliography(matches: &ArgMatches, fetcher: Arc<Filesystem>) {
+    let csl_path = matches.value_of("csl").expect("--csl is required");
+    let library_path = matches.value_of("library").expect("--library is required");
+    let format = matches
+        .value_of("format")
+        .and_then(|f| SupportedFormat::from_str(f).ok())
+        .unwrap_or(SupportedFormat::Html);
+    let text = fs::read_to_string(&csl_path).expect("No CSL file found at that path");
+    let init = InitOptions {
+        style: &text,
+        fetcher: Some(fetcher),
+        format,
+        ..Default::default()
+    };
+    let mut db = match Processor::new(init) {
+        Ok(db) => db,
+        Err(e) => {
+            self::error::file_diagnostics(&e, &csl_path, &text);
+            std::process::exit(1);
+        }
+    };
+    db.reset_references(expect_refs(library_path));
+    db.include_uncited(IncludeUncited::All);
+    for entry in db.get_bibliography() {
+        println!("{}", entry.value);
+    }
+}
+
 pub struct Filesystem {
     root: PathBuf,
 }
@@ -5,136 +5,226 @@
 // Copyright © 2019 Corporation for Digital Scholarship
 
 use pandoc_types::definition::CitationMode;
-pub fn suppression_from_pandoc_mode(mode: CitationMode) -> Option<Suppression> {
+pub fn mode_from_pandoc_mode(mode: &CitationMode) -> Option<CiteMode> {
     match mode {
-        CitationMode::AuthorInText => Some(Suppression::InText),
-        CitationMode::SuppressAuthor => Some(Suppression::Rest),
-        CitationMode::NormalCitation => None,
+        CitationMode::SuppressAuthor => Some(CiteMode::SuppressAuthor),
+        // handled for the whole cluster, see cluster_mode_from_pandoc
+        CitationMode::AuthorInText | CitationMode::NormalCitation => None,
+    }
+}
+
+/// Pandoc's author-in-text (`@doe`) is a narrative cite, "Doe (2000)", not the author alone. Pandoc only lets the first cite in a cluster be author-in-text.
+pub fn cluster_mode_from_pandoc(citations: &[Citation]) -> Option<ClusterMode> {
+    match citations.first()?.citation_mode {
+        CitationMode::AuthorInText => Some(ClusterMode::Composite {
+            infix: None,
+            suppress_first: 1,
+        }),
+        _ => None,
     }
 }
 
 use pandoc_types::{
-    definition::{Block, Citation, Inline, Pandoc as PandocDocument},
+    definition::{Block, Citation, Format, Inline, Pandoc as PandocDocument},
     walk::MutVisitor,
 };
 
-use citeproc::input::{Cite, CiteId, Cluster, ClusterId, Suppression};
-use citeproc::output::Pandoc;
-use citeproc::Processor;
+use citeproc::prelude::*;
 use csl::StyleClass;
 
-struct GetClusters {
-    next_cluster_id: ClusterId,
-    next_cite_id: CiteId,
-    clusters: Vec<Cluster<Pandoc>>,
+/// Flattens pandoc inlines (e.g. a citation prefix) into a plain string.
+fn stringify(inlines: &[Inline]) -> String {
+    let mut s = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Str(ref string) => s.push_str(string),
+            Inline::Space | Inline::SoftBreak | Inline::LineBreak => s.push(' '),
+            Inline::Code(_, ref string) | Inline::Math(_, ref string) => s.push_str(string),
+            Inline::Emph(ref inner)
+            | Inline::Strong(ref inner)
+            | Inline::Strikeout(ref inner)
+            | Inline::Superscript(ref inner)
+            | Inline::Subscript(ref inner)
+            | Inline::SmallCaps(ref inner)
+            | Inline::Quoted(_, ref inner)
+            | Inline::Link(_, ref inner, _)
+            | Inline::Span(_, ref inner) => s.push_str(&stringify(inner)),
+            _ => {}
+        }
+    }
+    s
+}
+
+fn non_empty(string: String) -> Option<String> {
+    if string.is_empty() {
+        None
+    } else {
+        Some(string)
+    }
+}
+
+struct GetClusters<'a> {
+    db: &'a mut Processor,
+    next_cluster_id: u32,
+    positions: Vec<ClusterPosition>,
+    is_note: bool,
 }
 
-pub fn get_clusters(pandoc: &mut PandocDocument) -> Vec<Cluster<Pandoc>> {
-    // pandoc-citeproc starts at 1
+/// Inserts a cluster into the processor for each `Inline::Cite` in the document, and sets the
+/// cluster order. Cluster ids are the 1-based index of the cite in the document, like
+/// pandoc-citeproc.
+pub fn insert_clusters(pandoc: &mut PandocDocument, db: &mut Processor) {
+    let is_note = db.style_class() == StyleClass::Note;
     let mut gc = GetClusters {
+        db,
         next_cluster_id: 1,
-        next_cite_id: 1,
-        clusters: vec![],
+        positions: vec![],
+        is_note,
     };
     gc.walk_pandoc(pandoc);
-    gc.clusters
+    gc.db
+        .set_cluster_order(&gc.positions)
+        .expect("pandoc clusters are always in order");
 }
 
-impl MutVisitor for GetClusters {
+impl<'a> MutVisitor for GetClusters<'a> {
     fn walk_inline(&mut self, inline: &mut Inline) {
-        match *inline {
-            Inline::Note(_) => {
-                // just trying to mirror the cite hashes of pandoc-citeproc
-                self.next_cite_id += 1;
-            }
-            Inline::Cite(ref p_cites, ref _literal) => {
-                let mut note_number = 0;
-                let cites = p_cites
-                    .iter()
-                    .map(|p| {
-                        note_number = p.citation_note_num;
-                        let id = self.next_cite_id;
-                        self.next_cite_id += 1;
-                        Cite {
-                            id,
-                            ref_id: p.citation_id.clone().into(),
-                            suppression: suppression_from_pandoc_mode(p.citation_mode.clone()),
-                            prefix: p.citation_prefix.clone(),
-                            suffix: p.citation_suffix.clone(),
-                            // XXX: parse these out of the suffix, and drop the rest in "suffix"
-                            locators: vec![],
-                            locator_extra: None,
-                            locator_date: None,
-                        }
-                    })
-                    .collect();
-                let cluster = Cluster {
-                    // id == note_number for Pandoc, but not necessarily with user-supplied
-                    // ids.
-                    id: self.next_cluster_id,
-                    note_number: self.next_cluster_id as u32,
-                    cites,
-                };
-                self.clusters.push(cluster);
-                self.next_cluster_id += 1;
-            }
-            _ => {}
+        if let Inline::Cite(ref p_cites, ref _literal) = *inline {
+            let cites = p_cites
+                .iter()
+                .map(|p| {
+                    let mut cite = Cite::basic(p.citation_id.as_str());
+                    cite.prefix = non_empty(stringify(&p.citation_prefix));
+                    // XXX: parse locators out of the suffix, and drop the rest in "suffix"
+                    cite.suffix = non_empty(stringify(&p.citation_suffix));
+                    cite.mode = mode_from_pandoc_mode(&p.citation_mode);
+                    cite
+                })
+                .collect();
+            let number = self.next_cluster_id;
+            let id = self.db.cluster_id(number.to_string());
+            let mode = cluster_mode_from_pandoc(p_cites);
+            self.db.insert_cluster(Cluster::new(id, cites, mode));
+            // note number == cluster number for Pandoc, where every note style cite becomes a
+            // footnote of its own
+            self.positions.push(if self.is_note {
+                ClusterPosition::note(id, number)
+            } else {
+                ClusterPosition::in_text(id)
+            });
+            self.next_cluster_id += 1;
         }
     }
 }
 
 struct WriteClusters<'a> {
-    next_cluster_id: ClusterId,
-    next_cite_id: CiteId,
+    next_cluster_id: u32,
     db: &'a Processor,
+    format: SupportedFormat,
+    is_note: bool,
 }
 
-/// Only works if you run it on a PandocDocument that hasn't been modified since you ingested the
-/// clusters into the database. The Inline::Cite-s & Inline::Note-s have to be in the same order.
-/// If you're adding a bibliography, do it after a get_clusters/write_clusters pair.
-pub fn write_clusters(pandoc: &mut PandocDocument, db: &Processor) {
+/// Only works if you run it on a PandocDocument that hasn't been modified since you ran
+/// insert_clusters on it. The Inline::Cite-s have to be in the same order.
+pub fn write_clusters(pandoc: &mut PandocDocument, db: &Processor, format: SupportedFormat) {
     let mut wc = WriteClusters {
         next_cluster_id: 1,
-        next_cite_id: 1,
         db,
+        format,
+        is_note: db.style_class() == StyleClass::Note,
     };
     wc.walk_pandoc(pandoc);
 }
 
 impl<'a> MutVisitor for WriteClusters<'a> {
     fn walk_inline(&mut self, inline: &mut Inline) {
-        match *inline {
-            Inline::Note(_) => {
-                // just trying to mirror the cite hashes of pandoc-citeproc
-                self.next_cite_id += 1;
+        if let Inline::Cite(_, ref mut literal) = *inline {
+            let id = self.db.cluster_id(self.next_cluster_id.to_string());
+            let built = self
+                .db
+                .get_cluster(id)
+                .map(|b| b.to_string())
+                .unwrap_or_default();
+            let built = match self.format {
+                SupportedFormat::Html => Inline::RawInline(Format("html".into()), built),
+                _ => Inline::Str(built),
+            };
+            if self.is_note {
+                *literal = vec![Inline::Note(vec![Block::Para(vec![built])])];
+            } else {
+                *literal = vec![built];
             }
-            Inline::Cite(ref mut p_cites, ref mut literal) => {
-                let cites = p_cites
-                    .iter()
-                    .map(|p| {
-                        let id = self.next_cite_id;
-                        self.next_cite_id += 1;
-                        let rust_cite = self.db.get_cite(id);
-                        Citation {
-                            citation_hash: id as i32,
-                            citation_prefix: rust_cite.prefix.clone(),
-                            citation_suffix: rust_cite.suffix.clone(),
-                            ..p.clone()
-                        }
-                    })
-                    .collect();
-                *p_cites = cites;
-                let built = (*self.db.get_cluster(self.next_cluster_id)).clone();
-                if self.db.get_style().class == StyleClass::Note {
-                    *literal = vec![Inline::Note(vec![Block::Para(built)])];
-                } else {
-                    *literal = built;
-                }
-                self.next_cluster_id += 1;
-            }
-            _ => {}
+            self.next_cluster_id += 1;
         }
     }
 }
 
-// TODO: parse locators!
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pandoc_types::definition::Meta;
+
+    const STYLE: &'static str = r#"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout delimiter="; " prefix="(" suffix=")">
+                <group delimiter=" ">
+                    <names variable="author" />
+                    <date form="numeric" variable="issued" />
+                </group>
+            </layout>
+        </citation>
+    </style>"#;
+
+    fn render(mode: CitationMode) -> Vec<Inline> {
+        let mut db = Processor::new(InitOptions {
+            style: STYLE,
+            format: SupportedFormat::Plain,
+            test_mode: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let refr: Reference = serde_json::from_str(
+            r#"{ "id": "doe", "type": "book", "author": [{ "family": "Doe" }], "issued": { "raw": "2000" } }"#,
+        )
+        .unwrap();
+        db.insert_reference(refr);
+        let citation = Citation {
+            citation_id: "doe".into(),
+            citation_prefix: vec![],
+            citation_suffix: vec![],
+            citation_mode: mode,
+            citation_note_num: 1,
+            citation_hash: 0,
+        };
+        let mut doc = PandocDocument(
+            Meta::null(),
+            vec![Block::Para(vec![Inline::Cite(vec![citation], vec![])])],
+        );
+        insert_clusters(&mut doc, &mut db);
+        write_clusters(&mut doc, &db, SupportedFormat::Plain);
+        match &doc.1[..] {
+            [Block::Para(inlines)] => match &inlines[..] {
+                [Inline::Cite(_, literal)] => literal.clone(),
+                _ => panic!("expected a single cite"),
+            },
+            _ => panic!("expected a single paragraph"),
+        }
+    }
+
+    #[test]
+    fn author_in_text_keeps_the_year() {
+        assert_eq!(
+            render(CitationMode::AuthorInText),
+            vec![Inline::Str("Doe (2000)".into())]
+        );
+        assert_eq!(
+            render(CitationMode::NormalCitation),
+            vec![Inline::Str("(Doe 2000)".into())]
+        );
+        assert_eq!(
+            render(CitationMode::SuppressAuthor),
+            vec![Inline::Str("(2000)".into())]
+        );
+    }
+}
@@ -0,0 +1,42 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+use std::process::Command;
+
+fn bibliography(format: &str) -> String {
+    let data = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+    let output = Command::new(env!("CARGO_BIN_EXE_citeproc-rs"))
+        // keep the user's locale cache out of it; this style needs no locale terms
+        .args(&["--locales-dir", &format!("{}/no-locales", data)])
+        .arg("bibliography")
+        .args(&["--csl", &format!("{}/style.csl", data)])
+        .args(&["--library", &format!("{}/library.json", data)])
+        .args(&["--format", format])
+        .output()
+        .expect("could not run citeproc-rs");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn plain() {
+    assert_eq!(
+        bibliography("plain"),
+        "T. Adorno, Minima Moralia.\nH. Arendt, The Origins of Totalitarianism.\n"
+    );
+}
+
+#[test]
+fn html() {
+    assert_eq!(
+        bibliography("html"),
+        "T. Adorno, <i>Minima Moralia</i>.\nH. Arendt, <i>The Origins of Totalitarianism</i>.\n"
+    );
+}
@@ -236,3 +236,262 @@ pub(crate) fn attribute_array<T: GetAttribute>(
         None => Ok(vec![]),
     }
 }
+
+const FORMATTING: &[&str] = &[
+    "font-style",
+    "font-variant",
+    "font-weight",
+    "vertical-align",
+    "text-decoration",
+];
+const AFFIXES: &[&str] = &["prefix", "suffix"];
+/// Inheritable name options on `<style>`, `<citation>` and `<bibliography>`
+const NAME_OPTIONS: &[&str] = &[
+    "and",
+    "name-delimiter",
+    "delimiter-precedes-et-al",
+    "delimiter-precedes-last",
+    "et-al-min",
+    "et-al-use-first",
+    "et-al-use-last",
+    "et-al-subsequent-min",
+    "et-al-subsequent-use-first",
+    "name-form",
+    "initialize",
+    "initialize-with",
+    "name-as-sort-order",
+    "sort-separator",
+    "names-delimiter",
+];
+const CONDITIONS: &[&str] = &[
+    "match",
+    "variable",
+    "type",
+    "position",
+    "locator",
+    "is-numeric",
+    "is-uncertain-date",
+    "is-plural",
+    "disambiguate",
+    "context",
+    "jurisdiction",
+    "subjurisdictions",
+    "has-year-only",
+    "has-month-or-season",
+    "has-day",
+];
+
+/// The attributes each element accepts, as `(own attributes, shared groups)`. Elements with
+/// the same name in different places (e.g. `<date>` in a layout and in a locale) share a list.
+/// None for elements this parser does not know, which are reported elsewhere.
+fn known_attributes(
+    tag: &str,
+) -> Option<(&'static [&'static str], &'static [&'static [&'static str]])> {
+    const NONE: &[&str] = &[];
+    const FA: &[&[&str]] = &[FORMATTING, AFFIXES];
+    let known: (&[&str], &[&[&str]]) = match tag {
+        "style" => (
+            &[
+                "class",
+                "version",
+                "variant",
+                "default-locale",
+                "page-range-format",
+                "demote-non-dropping-particle",
+                "initialize-with-hyphen",
+                "citation-label-trigraph",
+            ],
+            &[NAME_OPTIONS],
+        ),
+        "citation" => (
+            &[
+                "disambiguate-add-names",
+                "disambiguate-add-givenname",
+                "givenname-disambiguation-rule",
+                "disambiguate-add-year-suffix",
+                "near-note-distance",
+                "cite-group-delimiter",
+                "year-suffix-delimiter",
+                "after-collapse-delimiter",
+                "collapse",
+            ],
+            &[NAME_OPTIONS],
+        ),
+        "bibliography" => (
+            &[
+                "hanging-indent",
+                "second-field-align",
+                "line-spacing",
+                "entry-spacing",
+                "subsequent-author-substitute",
+                "subsequent-author-substitute-rule",
+            ],
+            &[NAME_OPTIONS],
+        ),
+        "intext" => (
+            &["cite-group-delimiter", "and", "after-collapse-delimiter"],
+            &[],
+        ),
+        "layout" => (&["delimiter", "locale"], FA),
+        "macro" | "feature" => (&["name"], &[]),
+        "key" => (
+            &[
+                "variable",
+                "macro",
+                "names-min",
+                "names-use-first",
+                "names-use-last",
+                "sort",
+            ],
+            &[],
+        ),
+        "text" => (
+            &[
+                "variable",
+                "macro",
+                "term",
+                "value",
+                "form",
+                "plural",
+                "quotes",
+                "strip-periods",
+                "text-case",
+                "display",
+            ],
+            FA,
+        ),
+        "number" => (&["variable", "form", "text-case", "display"], FA),
+        "label" => (
+            &["variable", "form", "plural", "strip-periods", "text-case"],
+            FA,
+        ),
+        "group" => (&["delimiter", "display", "is-parallel"], FA),
+        "date" => (
+            &[
+                "variable",
+                "form",
+                "date-parts",
+                "delimiter",
+                "display",
+                "text-case",
+            ],
+            FA,
+        ),
+        "date-part" => (
+            &[
+                "name",
+                "form",
+                "strip-periods",
+                "text-case",
+                "range-delimiter",
+            ],
+            FA,
+        ),
+        "names" => (&["variable", "delimiter", "display"], FA),
+        "name" => (
+            &[
+                "and",
+                "delimiter",
+                "delimiter-precedes-et-al",
+                "delimiter-precedes-last",
+                "et-al-min",
+                "et-al-use-first",
+                "et-al-use-last",
+                "et-al-subsequent-min",
+                "et-al-subsequent-use-first",
+                "form",
+                "initialize",
+                "initialize-with",
+                "name-as-sort-order",
+                "sort-separator",
+            ],
+            FA,
+        ),
+        "name-part" => (&["name", "text-case"], FA),
+        "et-al" => (&["term"], &[FORMATTING]),
+        "with" => (NONE, FA),
+        "institution" => (
+            &[
+                "and",
+                "delimiter",
+                "use-first",
+                "substitute-use-first",
+                "use-last",
+                "reverse-order",
+                "institution-parts",
+            ],
+            &[],
+        ),
+        "institution-part" => (&["name", "if-short", "strip-periods"], FA),
+        "if" | "else-if" | "condition" => (NONE, &[CONDITIONS]),
+        "conditions" => (&["match"], &[]),
+        "term" => (&["name", "form", "gender", "gender-form", "match"], &[]),
+        "style-options" => (
+            &[
+                "limit-day-ordinals-to-day-1",
+                "punctuation-in-quote",
+                "leading-noise-words",
+            ],
+            &[],
+        ),
+        "features" | "sort" | "choose" | "else" | "substitute" | "locale" | "terms" | "single"
+        | "multiple" => (NONE, &[]),
+        _ => return None,
+    };
+    Some(known)
+}
+
+/// For [crate::ParseOptions::strict_attributes]. Reports any un-namespaced attribute on `node`
+/// or its descendants that [known_attributes] does not list. The `<info>` block is metadata and
+/// is not checked.
+pub(crate) fn whitelist_attributes(node: &Node, errors: &mut Vec<InvalidCsl>) {
+    let tag = node.tag_name().name();
+    if tag == "info" {
+        return;
+    }
+    let (own, shared) = match known_attributes(tag) {
+        Some(known) => known,
+        None => return,
+    };
+    for attr in node.attributes() {
+        let name = attr.name();
+        let known = attr.namespace().is_some()
+            || own.contains(&name)
+            || shared.iter().any(|group| group.contains(&name));
+        if !known {
+            let candidates = own
+                .iter()
+                .chain(shared.iter().flat_map(|group| group.iter()));
+            let did_you_mean = closest_attribute(name, candidates.copied());
+            errors.push(InvalidCsl::unknown_attribute(node, attr, did_you_mean));
+        }
+    }
+    for child in node.children().filter(|n| n.is_element()) {
+        whitelist_attributes(&child, errors);
+    }
+}
+
+/// The candidate within two edits of `name`, if any, for suggesting a fix to a typo.
+fn closest_attribute<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = Vec::with_capacity(b.len() + 1);
+        cur.push(i + 1);
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + if ca == *cb { 0 } else { 1 };
+            cur.push(substitute.min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
@@ -257,6 +257,25 @@ impl InvalidCsl {
         }
     }
 
+    pub fn unknown_attribute(
+        node: &Node,
+        attr: &roxmltree::Attribute,
+        did_you_mean: Option<&str>,
+    ) -> Self {
+        InvalidCsl {
+            range: attr.range(),
+            message: format!(
+                "Unknown attribute `{}` on <{}>",
+                attr.name(),
+                node.tag_name().name()
+            ),
+            hint: did_you_mean
+                .map(|known| format!("Hint: did you mean `{}`?", known))
+                .unwrap_or_default(),
+            severity: Severity::Error,
+        }
+    }
+
     pub fn wrong_var_type(
         node: &Node,
         attr: &str,
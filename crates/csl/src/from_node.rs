@@ -42,6 +42,9 @@ pub struct ParseOptions {
     /// Feature overrides. Allows you to enable features programmatically. Features declared in the
     /// style will be added to this.
     pub features: Option<Features>,
+    /// Treat attributes this parser does not recognise on known elements as errors, to catch
+    /// typos like `delimeter=", "`. By default they are ignored.
+    pub strict_attributes: bool,
     #[doc(hidden)]
     pub use_default_default: private::CannotConstruct,
 }
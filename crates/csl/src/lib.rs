@@ -306,17 +306,7 @@ impl FromNode for Citation {
     }
     const CHILD_DESC: &'static str = "citation";
     fn from_node(node: &Node, info: &ParseInfo) -> FromNodeResult<Self> {
-        // TODO: remove collect() using Peekable
-        let layouts: Vec<_> = node
-            .children()
-            .filter(|n| n.has_tag_name("layout"))
-            .collect();
-        if layouts.len() != 1 {
-            return Err(
-                InvalidCsl::new(node, "<citation> must contain exactly one <layout>").into(),
-            );
-        }
-        let layout_node = layouts[0];
+        let (layout, locale_layouts) = layouts_from_node(node, info, "citation")?;
         let sorts: Vec<_> = node.children().filter(|n| n.has_tag_name("sort")).collect();
         if sorts.len() > 1 {
             return Err(InvalidCsl::new(node, "<citation> can only contain one <sort>").into());
@@ -350,7 +340,8 @@ impl FromNode for Citation {
                 info,
                 false,
             )?,
-            layout: Layout::from_node(&layout_node, info)?,
+            layout,
+            locale_layouts,
             name_inheritance: Name::from_node(&node, info)?,
             names_delimiter: attribute_option(node, "names-delimiter", info)?,
             near_note_distance: attribute_option_int(node, "near-note-distance")?.unwrap_or(5),
@@ -439,19 +430,9 @@ impl FromNode for Bibliography {
     }
     const CHILD_DESC: &'static str = "bibliography";
     fn from_node(node: &Node, info: &ParseInfo) -> FromNodeResult<Self> {
-        // TODO: layouts matching locales in CSL-M mode
         // TODO: make sure that all elements are under the control of a display attribute
         //       if any of them are
-        let layouts: Vec<_> = node
-            .children()
-            .filter(|n| n.has_tag_name("layout"))
-            .collect();
-        if layouts.len() != 1 {
-            return Err(
-                InvalidCsl::new(node, "<citation> must contain exactly one <layout>").into(),
-            );
-        }
-        let layout_node = layouts[0];
+        let (layout, locale_layouts) = layouts_from_node(node, info, "bibliography")?;
         let line_spacing = attribute_int(node, "line-spacing", 1)?;
         if line_spacing < 1 {
             return Err(InvalidCsl::new(node, "line-spacing must be >= 1").into());
@@ -468,7 +449,8 @@ impl FromNode for Bibliography {
         };
         Ok(Bibliography {
             sort,
-            layout: Layout::from_node(&layout_node, info)?,
+            layout,
+            locale_layouts,
             hanging_indent: bool::attribute_default_val(node, "hanging-indent", info, false)?,
             second_field_align: attribute_option(node, "second-field-align", info)?,
             line_spacing,
@@ -489,6 +471,39 @@ impl FromNode for Bibliography {
     }
 }
 
+/// Reads the `<layout>` children of a `<citation>` or `<bibliography>`. There must be exactly one,
+/// unless the `layout_locales` feature allows any number with a `locale` attribute alongside the
+/// one without.
+fn layouts_from_node(
+    node: &Node,
+    info: &ParseInfo,
+    parent: &str,
+) -> FromNodeResult<(Layout, Vec<Layout>)> {
+    let layouts = node.children().filter(|n| n.has_tag_name("layout"));
+    let (localized, default): (Vec<_>, Vec<_>) = if info.features.layout_locales {
+        layouts.partition(|n| n.has_attribute("locale"))
+    } else {
+        (Vec::new(), layouts.collect())
+    };
+    if default.len() != 1 {
+        let msg = if info.features.layout_locales {
+            format!(
+                "<{}> must contain exactly one <layout> without a locale",
+                parent
+            )
+        } else {
+            format!("<{}> must contain exactly one <layout>", parent)
+        };
+        return Err(InvalidCsl::new(node, msg).into());
+    }
+    let layout = Layout::from_node(&default[0], info)?;
+    let locale_layouts = localized
+        .iter()
+        .map(|n| Layout::from_node(n, info))
+        .partition_results()?;
+    Ok((layout, locale_layouts))
+}
+
 impl FromNode for Layout {
     const CHILD_DESC: &'static str = "layout";
     fn select_child(node: &Node) -> bool {
@@ -1300,7 +1315,7 @@ impl FromNode for Name {
 impl FromNode for NameEtAl {
     fn from_node(node: &Node, info: &ParseInfo) -> FromNodeResult<Self> {
         Ok(NameEtAl {
-            term: attribute_string(node, "term"),
+            term: attribute_optional(node, "term", info)?,
             formatting: Option::from_node(node, info)?,
         })
     }
@@ -1563,6 +1578,9 @@ impl Style {
             },
             &mut errors,
         );
+        if default_info.options.strict_attributes {
+            whitelist_attributes(node, &mut errors);
+        }
 
         // We will check again later (for MacroMap) if there are macros without names.
         let mut throwaway = Vec::new();
@@ -1634,6 +1652,11 @@ impl Style {
                 true,
             )?,
             names_delimiter: attribute_option(node, "names-delimiter", &parse_info)?,
+            citation_label_trigraph: if parse_info.features.citation_label_trigraph {
+                attribute_option(node, "citation-label-trigraph", &parse_info)?
+            } else {
+                None
+            },
         })
     }
 }
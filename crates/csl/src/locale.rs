@@ -23,6 +23,9 @@ pub const EN_US: &str = include_str!("locales-en-US.xml");
 pub struct LocaleOptionsNode {
     pub limit_day_ordinals_to_day_1: Option<bool>,
     pub punctuation_in_quote: Option<bool>,
+    /// CSL-M `leading-noise-words="a,an,the"`, a comma-separated list of words to skip at the
+    /// start of titles when sorting.
+    pub leading_noise_words: Option<SmartString>,
 }
 
 impl LocaleOptionsNode {
@@ -31,6 +34,10 @@ impl LocaleOptionsNode {
             .limit_day_ordinals_to_day_1
             .or(self.limit_day_ordinals_to_day_1);
         self.punctuation_in_quote = other.punctuation_in_quote.or(self.punctuation_in_quote);
+        self.leading_noise_words = other
+            .leading_noise_words
+            .clone()
+            .or_else(|| self.leading_noise_words.clone());
     }
 }
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
@@ -288,11 +295,36 @@ impl FromNode for LocaleOptionsNode {
                 info,
             )?,
             punctuation_in_quote: attribute_option(node, "punctuation-in-quote", info)?,
+            leading_noise_words: attribute_option(node, "leading-noise-words", info)?,
         })
     }
 }
 
 impl Locale {
+    /// Removes one leading noise word (`leading-noise-words`, or "a,an,the" for English) from a
+    /// title, for sorting. A title that is nothing but a noise word is left alone.
+    pub fn strip_leading_noise_word<'s>(&self, title: &'s str) -> &'s str {
+        let words = match self.options_node.leading_noise_words {
+            Some(ref words) => words.as_str(),
+            None if self.lang.as_ref().map_or(false, Lang::is_english) => "a,an,the",
+            None => return title,
+        };
+        let first_len = match title.find(char::is_whitespace) {
+            Some(len) => len,
+            None => return title,
+        };
+        let first = &title[..first_len];
+        if words
+            .split(',')
+            .map(str::trim)
+            .any(|word| !word.is_empty() && word.eq_ignore_ascii_case(first))
+        {
+            title[first_len..].trim_start()
+        } else {
+            title
+        }
+    }
+
     /// May return Some("") if the term is defined but empty. Not all code renders None in that
     /// case, so each call site should decide whether to slap .filter(|x| !x.is_empty()) after
     /// .get_text_term().
@@ -367,7 +399,7 @@ impl Locale {
         let mut default = "et al";
         let mut formatting = None;
         if let Some(el) = element {
-            if el.term == "and others" {
+            if el.term == crate::EtAlTerm::AndOthers {
                 term = MiscTerm::AndOthers;
                 default = "and others";
             }
@@ -416,7 +448,10 @@ impl Locale {
         ) {
             map.extend(other.iter().map(|(k, v)| (k.clone(), v.clone())));
         }
-        self.lang = with.lang.clone();
+        // a style's `<locale>` without xml:lang refines, but doesn't unset, the language
+        if with.lang.is_some() {
+            self.lang = with.lang.clone();
+        }
         extend(&mut self.simple_terms, &with.simple_terms);
         extend(&mut self.gendered_terms, &with.gendered_terms);
         extend(&mut self.role_terms, &with.role_terms);
@@ -9,7 +9,7 @@ use crate::attr::*;
 use crate::error::{CslError, InvalidCsl, StyleError};
 use crate::from_node::*;
 use crate::info::ParentLink;
-use crate::{Bibliography, CslVersionReq, Features, Lang, Locale, StyleClass};
+use crate::{Bibliography, CslVersionReq, Features, Lang, Locale, Style, StyleClass};
 use roxmltree::{Document, Node};
 
 /// A stripped-down version of `Style` that can also represent a dependent style.
@@ -57,6 +57,23 @@ impl StyleMeta {
         let meta = StyleMeta::from_node(node, &parse_info)?;
         Ok(meta)
     }
+    /// The metadata of an already-parsed (therefore independent) style, without reparsing it.
+    pub fn from_style(style: &Style) -> Self {
+        let mut locale_overrides: Vec<Lang> =
+            style.locale_overrides.keys().flatten().cloned().collect();
+        locale_overrides.sort();
+        StyleMeta {
+            info: style.info.clone(),
+            features: style.features.clone(),
+            default_locale: style.default_locale.clone(),
+            class: Some(style.class),
+            csl_version_required: style.version_req.clone(),
+            independent_meta: Some(IndependentMeta {
+                locale_overrides,
+                has_bibliography: style.bibliography.is_some(),
+            }),
+        }
+    }
 }
 
 impl FromNode for StyleMeta {
@@ -22,7 +22,9 @@ use std::sync::Arc;
 
 pub mod dependent;
 pub mod info;
+mod trigraph;
 use info::Info;
+pub use trigraph::*;
 
 type TermPlural = bool;
 type StripPeriods = bool;
@@ -850,13 +852,30 @@ pub struct NameLabel {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct NameEtAl {
-    // TODO: only accept "et-al" or "and others"
-    pub term: String,
+    pub term: EtAlTerm,
     pub formatting: Option<Formatting>,
 }
 
+/// The terms `<et-al term="...">` may select.
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EtAlTerm {
+    #[strum(serialize = "et-al")]
+    EtAl,
+    #[strum(serialize = "and others")]
+    AndOthers,
+}
+
+impl EnumGetAttribute for EtAlTerm {}
+impl Default for EtAlTerm {
+    fn default() -> Self {
+        EtAlTerm::EtAl
+    }
+}
+
+#[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab_case")]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum DemoteNonDroppingParticle {
     Never,
     SortOnly,
@@ -957,6 +976,8 @@ pub struct Citation {
     pub givenname_disambiguation_rule: GivenNameDisambiguationRule,
     pub disambiguate_add_year_suffix: bool,
     pub layout: Layout,
+    /// CSL-M `<layout locale="...">` alternatives to `layout`, with the `layout_locales` feature
+    pub locale_layouts: Vec<Layout>,
     pub name_inheritance: Name,
     pub names_delimiter: Option<SmartString>,
     pub near_note_distance: u32,
@@ -975,6 +996,7 @@ impl Default for Citation {
             givenname_disambiguation_rule: Default::default(),
             disambiguate_add_year_suffix: false,
             layout: Default::default(),
+            locale_layouts: Vec::new(),
             name_inheritance: Default::default(),
             names_delimiter: None,
             near_note_distance: 5,
@@ -988,6 +1010,10 @@ impl Default for Citation {
 }
 
 impl Citation {
+    /// The layout for a reference in this language. See [Layout::matches_lang].
+    pub fn layout_for(&self, lang: Option<&Lang>) -> &Layout {
+        select_layout(&self.layout, &self.locale_layouts, lang)
+    }
     /// Implements fallback to Year when disambiguate-add-year-suffix is false.
     pub fn collapse_fallback(&self) -> Option<Collapse> {
         let addyearsuf = self.disambiguate_add_year_suffix;
@@ -1017,6 +1043,8 @@ impl Citation {
 pub struct Bibliography {
     pub sort: Option<Sort>,
     pub layout: Layout,
+    /// CSL-M `<layout locale="...">` alternatives to `layout`, with the `layout_locales` feature
+    pub locale_layouts: Vec<Layout>,
     pub hanging_indent: bool, // default is false
     pub second_field_align: Option<SecondFieldAlign>,
     pub line_spacing: u32,  // >= 1 only. default is 1
@@ -1027,6 +1055,22 @@ pub struct Bibliography {
     pub names_delimiter: Option<SmartString>,
 }
 
+impl Bibliography {
+    /// The layout for a reference in this language. See [Layout::matches_lang].
+    pub fn layout_for(&self, lang: Option<&Lang>) -> &Layout {
+        select_layout(&self.layout, &self.locale_layouts, lang)
+    }
+}
+
+fn select_layout<'a>(
+    default: &'a Layout,
+    localized: &'a [Layout],
+    lang: Option<&Lang>,
+) -> &'a Layout {
+    lang.and_then(|lang| localized.iter().find(|layout| layout.matches_lang(lang)))
+        .unwrap_or(default)
+}
+
 /// cs:intext element
 #[derive(Debug, Eq, Clone, PartialEq)]
 pub struct InText {
@@ -1113,7 +1157,6 @@ impl Default for SortDirection {
     }
 }
 
-// TODO: Multiple layouts in CSL-M with locale="en es de" etc
 #[derive(Default, Debug, Eq, Clone, PartialEq)]
 pub struct Layout {
     pub affixes: Option<Affixes>,
@@ -1124,6 +1167,19 @@ pub struct Layout {
     pub locale: Vec<Lang>,
 }
 
+impl Layout {
+    /// Whether `lang` is listed in this layout's `locale` attribute. A bare language like `en`
+    /// matches any region, e.g. `en-GB`.
+    pub fn matches_lang(&self, lang: &Lang) -> bool {
+        self.locale
+            .iter()
+            .any(|layout_lang| match (layout_lang, lang) {
+                (Lang::Iso(layout_iso, None), Lang::Iso(iso, _)) => layout_iso == iso,
+                _ => layout_lang == lang,
+            })
+    }
+}
+
 // Not actually part of a style tree, just a useful place to implement FromNode.
 #[derive(Debug, Eq, Clone, PartialEq)]
 pub struct MacroMap {
@@ -1165,6 +1221,10 @@ pub struct Style {
     pub page_range_format: Option<PageRangeFormat>,
     pub demote_non_dropping_particle: DemoteNonDroppingParticle,
     pub initialize_with_hyphen: bool, // default is true
+    /// How to generate the `citation-label` variable. This is a citeproc-rs extension to CSL,
+    /// read from `<style citation-label-trigraph="...">` only with the `citation-label-trigraph`
+    /// feature enabled. `None` means [`Trigraph::default`], `Aaaa00:AaAa00:AaAA00:AAAA00`.
+    pub citation_label_trigraph: Option<Trigraph>,
 }
 
 impl Default for Style {
@@ -1185,6 +1245,7 @@ impl Default for Style {
             page_range_format: None,
             demote_non_dropping_particle: Default::default(),
             initialize_with_hyphen: true,
+            citation_label_trigraph: None,
         }
     }
 }
@@ -1448,7 +1509,9 @@ impl Position {
 
 /// [Spec](https://docs.citationstyles.org/en/stable/specification.html#appendix-v-page-range-formats)
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 #[strum(serialize_all = "kebab_case")]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum PageRangeFormat {
     Chicago,
     Expanded,
@@ -1469,6 +1532,8 @@ pub enum CslType {
     Broadcast,
     Chapter,
     Dataset,
+    /// CSL 1.0.2's generic type, for anything that fits none of the others
+    Document,
     Entry,
     EntryDictionary,
     EntryEncyclopedia,
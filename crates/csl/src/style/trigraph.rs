@@ -0,0 +1,132 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+//! The format for generating the `citation-label` variable, from the non-standard
+//! `<style citation-label-trigraph="...">` attribute. citeproc-js has the same setting as a
+//! processor option, but it is not part of CSL.
+
+use crate::attr::GetAttribute;
+use crate::error::UnknownAttributeValue;
+use crate::version::Features;
+
+/// One `:`-separated row per number of authors, where `Aaa` takes the first three letters of an
+/// author's name, and `00` the last two digits of the year. References with more authors than
+/// there are rows use the last row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trigraph(Vec<Vec<TrigraphCell>>);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TrigraphCell {
+    Author { first_n_letters: u32 },
+    Year { last_n_digits: u32 },
+}
+
+impl Trigraph {
+    pub fn parse(s: &str) -> Result<Self, ()> {
+        parser::colon_separated(s)
+            .map_err(|_| ())
+            .and_then(|(remain, x)| {
+                if remain.is_empty() {
+                    Ok(Trigraph(x))
+                } else {
+                    Err(())
+                }
+            })
+    }
+
+    pub fn rows(&self) -> &[Vec<TrigraphCell>] {
+        &self.0
+    }
+}
+
+impl Default for Trigraph {
+    fn default() -> Self {
+        Trigraph::parse("Aaaa00:AaAa00:AaAA00:AAAA00")
+            .expect("Trigraph ought to parse the default!")
+    }
+}
+
+impl GetAttribute for Trigraph {
+    fn get_attr(s: &str, _features: &Features) -> Result<Self, UnknownAttributeValue> {
+        Trigraph::parse(s).map_err(|_| UnknownAttributeValue::new(s))
+    }
+}
+
+mod parser {
+    use super::TrigraphCell;
+    use nom::{
+        branch::alt,
+        bytes::complete::{take_while, take_while1},
+        character::complete::char,
+        combinator::recognize,
+        multi::{many1, separated_list1},
+        IResult,
+    };
+
+    fn author(inp: &str) -> IResult<&str, TrigraphCell> {
+        let (rest, _a) = char('A')(inp)?;
+        let (rest, lowers) = recognize(take_while(|c: char| c == 'a'))(rest)?;
+        Ok((
+            rest,
+            TrigraphCell::Author {
+                first_n_letters: 1 + lowers.len() as u32,
+            },
+        ))
+    }
+
+    fn year(inp: &str) -> IResult<&str, TrigraphCell> {
+        let (rest, zeroes) = recognize(take_while1(|c| c == '0'))(inp)?;
+        Ok((
+            rest,
+            TrigraphCell::Year {
+                last_n_digits: zeroes.len() as u32,
+            },
+        ))
+    }
+
+    #[test]
+    fn test_author() {
+        assert_eq!(
+            author("Aaaa"),
+            Ok(("", TrigraphCell::Author { first_n_letters: 4 }))
+        )
+    }
+
+    pub(super) fn colon_separated(inp: &str) -> IResult<&str, Vec<Vec<TrigraphCell>>> {
+        separated_list1(char(':'), many1(alt((author, year))))(inp)
+    }
+}
+
+#[test]
+fn test_parse_trigraph() {
+    assert_eq!(
+        Trigraph::parse("Aaaa00:AaAa00:AaAA00:AAAA00"),
+        Ok(Trigraph(vec![
+            vec![
+                TrigraphCell::Author { first_n_letters: 4 },
+                TrigraphCell::Year { last_n_digits: 2 },
+            ],
+            vec![
+                TrigraphCell::Author { first_n_letters: 2 },
+                TrigraphCell::Author { first_n_letters: 2 },
+                TrigraphCell::Year { last_n_digits: 2 },
+            ],
+            vec![
+                TrigraphCell::Author { first_n_letters: 2 },
+                TrigraphCell::Author { first_n_letters: 1 },
+                TrigraphCell::Author { first_n_letters: 1 },
+                TrigraphCell::Year { last_n_digits: 2 },
+            ],
+            vec![
+                TrigraphCell::Author { first_n_letters: 1 },
+                TrigraphCell::Author { first_n_letters: 1 },
+                TrigraphCell::Author { first_n_letters: 1 },
+                TrigraphCell::Author { first_n_letters: 1 },
+                TrigraphCell::Year { last_n_digits: 2 },
+            ],
+        ]))
+    )
+}
@@ -170,3 +170,134 @@ fn wrong_tag_name() {
     "#
     );
 }
+
+#[test]
+fn citation_label_trigraph() {
+    let parse = |trigraph: &str| {
+        Style::parse_for_test(
+            &format!(
+                r#"<style class="in-text" citation-label-trigraph="{}">
+                    <features><feature name="citation-label-trigraph" /></features>
+                    <citation><layout></layout></citation>
+                </style>"#,
+                trigraph
+            ),
+            None,
+        )
+        .map(|style| style.citation_label_trigraph)
+    };
+    assert_eq!(
+        parse("Aaa0000:AaAa00").unwrap(),
+        Some(Trigraph::parse("Aaa0000:AaAa00").unwrap())
+    );
+    assert!(parse("aA00").is_err());
+    assert!(parse("Aaa00:").is_err());
+    assert!(parse("A0a").is_err());
+    assert!(parse("Aaa-00").is_err());
+    // ignored without the feature
+    let style = Style::parse_for_test(
+        r#"<style class="in-text" citation-label-trigraph="Aaa00">
+            <citation><layout></layout></citation>
+        </style>"#,
+        None,
+    )
+    .unwrap();
+    assert_eq!(style.citation_label_trigraph, None);
+}
+
+#[test]
+fn et_al_term() {
+    let parse = |term: &str| {
+        Style::parse_for_test(
+            &format!(
+                r#"<style class="in-text">
+                    <citation><layout>
+                        <names variable="author"><et-al term="{}" /></names>
+                    </layout></citation>
+                </style>"#,
+                term
+            ),
+            None,
+        )
+        .map(|style| match &style.citation.layout.elements[0] {
+            Element::Names(names) => names.et_al.as_ref().map(|et_al| et_al.term),
+            _ => None,
+        })
+    };
+    assert_eq!(parse("et-al").unwrap(), Some(EtAlTerm::EtAl));
+    assert_eq!(parse("and others").unwrap(), Some(EtAlTerm::AndOthers));
+    assert!(parse("et alii").is_err());
+}
+
+#[test]
+fn strict_attributes() {
+    let xml = r#"<style class="in-text" version="1.0" default-locale="en-GB">
+        <citation et-al-min="3">
+            <layout prefix="(" suffix=")" delimiter="; ">
+                <group delimeter=", ">
+                    <names variable="author"><name form="short" /></names>
+                    <date variable="issued" form="numeric" date-parts="year" />
+                </group>
+            </layout>
+        </citation>
+    </style>"#;
+    let strict = || {
+        Some(ParseOptions {
+            strict_attributes: true,
+            ..Default::default()
+        })
+    };
+    // ignored by default
+    assert!(Style::parse_for_test(xml, None).is_ok());
+    match Style::parse_for_test(xml, strict()) {
+        Err(StyleError::Invalid(CslError(errors))) => {
+            let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+            assert_eq!(messages, vec!["Unknown attribute `delimeter` on <group>"]);
+            assert_eq!(errors[0].hint, "Hint: did you mean `delimiter`?");
+        }
+        other => panic!("expected an unknown attribute error, got {:?}", other),
+    }
+    let fixed = xml.replace("delimeter", "delimiter");
+    assert!(Style::parse_for_test(&fixed, strict()).is_ok());
+}
+
+/// Checks the strict attribute table against real styles, so it doesn't fall behind what the
+/// parser reads.
+#[test]
+fn strict_attributes_real_styles() {
+    let styles = [
+        include_str!("../../proc/tests/data/apa.csl"),
+        include_str!("../../citeproc/tests/data/styles/modern-humanities-research-association.csl"),
+        include_str!("../../citeproc/tests/data/styles/year-suffix.csl"),
+    ];
+    for xml in styles.iter() {
+        let options = ParseOptions {
+            strict_attributes: true,
+            ..Default::default()
+        };
+        if let Err(e) = Style::parse_for_test(xml, Some(options)) {
+            panic!("strict_attributes rejected a real style: {:?}", e);
+        }
+    }
+}
+
+#[test]
+fn leading_noise_words() {
+    let en_us = Locale::parse(
+        r#"<locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="en-US"></locale>"#,
+    )
+    .unwrap();
+    assert_eq!(en_us.strip_leading_noise_word("The Beatles"), "Beatles");
+    assert_eq!(en_us.strip_leading_noise_word("an  Apple"), "Apple");
+    assert_eq!(en_us.strip_leading_noise_word("Theatre"), "Theatre");
+    assert_eq!(en_us.strip_leading_noise_word("The"), "The");
+
+    let fr_fr = Locale::parse(
+        r#"<locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="fr-FR">
+            <style-options leading-noise-words="la, le,les" />
+        </locale>"#,
+    )
+    .unwrap();
+    assert_eq!(fr_fr.strip_leading_noise_word("Le Monde"), "Monde");
+    assert_eq!(fr_fr.strip_leading_noise_word("The Beatles"), "The Beatles");
+}
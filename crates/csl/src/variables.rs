@@ -283,6 +283,24 @@ impl Variable {
     pub fn should_replace_hyphens(self) -> bool {
         false
     }
+    /// Titles, whose leading noise words are skipped when sorting.
+    pub fn is_title(self) -> bool {
+        match self {
+            Variable::Title
+            | Variable::TitleShort
+            | Variable::ContainerTitle
+            | Variable::ContainerTitleShort
+            | Variable::CollectionTitle
+            | Variable::CollectionTitleShort
+            | Variable::OriginalTitle
+            | Variable::ReviewedTitle
+            | Variable::EventTitle
+            | Variable::PartTitle
+            | Variable::VolumeTitle
+            | Variable::VolumeTitleShort => true,
+            _ => false,
+        }
+    }
     pub fn hyperlink(self, value: &str) -> Option<&str> {
         match self {
             Variable::URL => Some(value),
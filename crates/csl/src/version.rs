@@ -46,6 +46,10 @@ impl CslVersionReq {
     pub(crate) fn current_csl() -> Self {
         CslVersionReq(VersionReq::exact(&COMPILED_VERSION))
     }
+    /// Whether the style declares plain `version="1.0"`, rather than 1.0.1 or later.
+    pub fn is_csl_1_0(&self) -> bool {
+        self.0.matches(&Version::new(1, 0, 0))
+    }
 }
 
 #[derive(AsRefStr, EnumString, EnumProperty, Debug, PartialEq, Eq, Copy, Clone)]
@@ -207,16 +211,13 @@ declare_features!(
     (placeholder, condition_page, "1.0.1", None, None),
     (placeholder, condition_context, "1.0.1", None, None),
     (placeholder, condition_genre, "1.0.1", None, None),
-    // should include Authority being an institutional author?
-    (placeholder, institutions, "1.0.1", None, None),
-    // layout locale matching, default-locale-sort, name-as-sort-order languages, name-never-sort
+    // default-locale-sort, name-as-sort-order languages, name-never-sort
     (placeholder, multilingual, "1.0.1", None, None),
     (placeholder, hereinafter, "1.0.1", None, None),
     (placeholder, date_form_imperial, "1.0.1", None, None),
     // (currently includes the dodgy macro label-form="..." business)
     (placeholder, multiple_locators, "1.0.1", None, None),
     (placeholder, locator_extras, "1.0.1", None, None),
-    (placeholder, leading_noise_words, "1.0.1", None, None),
     (placeholder, name_as_reverse_order, "1.0.1", None, None),
     (placeholder, skip_words, "1.0.1", None, None),
     (placeholder, subgroup_delimiter, "1.0.1", None, None),
@@ -266,6 +267,24 @@ declare_features!(
     (active, legal_locators, "1.0.1", None, None),
     /// `<text term="unpublished">`
     (active, term_unpublished, "1.0.1", None, None),
+    /// `<style citation-label-trigraph="Aaaa00:AaAa00:AaAA00:AAAA00">`, to configure how the
+    /// `citation-label` variable is generated for references that don't supply one. Not part of
+    /// any CSL version; citeproc-js takes the same format as a processor option.
+    (active, citation_label_trigraph, "1.0.1", None, None),
+    /// Render each reference with the locale matching its `language` field (terms, date formats,
+    /// etc), instead of the style's default locale. The cluster itself still uses the default.
+    (active, locale_by_language, "1.0.1", None, None),
+    /// CSL-M `<layout locale="en es">` in `<citation>` and `<bibliography>`. Each reference uses
+    /// the first layout matching its `language` field, or the one layout without a locale.
+    (active, layout_locales, "1.0.1", None, None),
+    /// CSL-M `<style-options leading-noise-words="a,an,the">` in a locale. Sorting by a title
+    /// variable skips a leading noise word, so "The Beatles" sorts under B. English locales that
+    /// don't configure the list use "a,an,the".
+    (active, leading_noise_words, "1.0.1", None, None),
+    /// CSL-M `<institution>` in `<names>`. A name with no given name is an institution, whose
+    /// subunits are separated by `|`, largest first. `use-first`, `use-last` and `reverse-order`
+    /// pick and order the subunits, which are joined with the institution's `delimiter`.
+    (active, institutions, "1.0.1", None, None),
 );
 
 // status, name, first added version, tracking issue, edition, None
@@ -11,7 +11,7 @@ use std::sync::Arc;
 
 use citeproc_io::output::markup::Markup;
 use citeproc_io::{Cite, ClusterMode, Reference};
-use csl::Atom;
+use csl::{Atom, CslType};
 
 use fnv::FnvHashSet;
 
@@ -21,8 +21,14 @@ use indexmap::set::IndexSet;
 pub trait CiteDatabase: LocaleDatabase + StyleDatabase {
     #[salsa::input]
     fn reference_input(&self, key: Atom) -> Arc<Reference>;
+    /// Returns the reference with any unknown `type` replaced by [CiteDatabase::unknown_type_fallback]
     fn reference(&self, key: Atom) -> Option<Arc<Reference>>;
 
+    /// The type used for references whose CSL-JSON `type` was not recognised. `None` means
+    /// `document`, or `article` for CSL 1.0 styles.
+    #[salsa::input]
+    fn unknown_type_fallback(&self) -> Option<CslType>;
+
     #[salsa::input]
     fn all_keys(&self) -> Arc<IndexSet<Atom>>;
 
@@ -119,7 +125,21 @@ pub struct ClusterData {
 
 fn reference(db: &dyn CiteDatabase, key: Atom) -> Option<Arc<Reference>> {
     if db.all_keys().contains(&key) {
-        Some(db.reference_input(key))
+        let refr = db.reference_input(key);
+        let fallback = db.unknown_type_fallback().unwrap_or_else(|| {
+            // CSL 1.0 styles predate the generic `document` type
+            if db.style().version_req.is_csl_1_0() {
+                CslType::Article
+            } else {
+                CslType::Document
+            }
+        });
+        if refr.unknown_type.is_some() && refr.csl_type != fallback {
+            let mut refr = Reference::clone(&refr);
+            refr.csl_type = fallback;
+            return Some(Arc::new(refr));
+        }
+        Some(refr)
     } else {
         None
     }
@@ -184,13 +204,13 @@ fn all_cite_ids(db: &dyn CiteDatabase) -> Arc<Vec<CiteId>> {
 
 fn clusters_sorted(db: &dyn CiteDatabase) -> Arc<Vec<ClusterData>> {
     let cluster_ids = db.clusters_ordered();
-    let mut clusters: Vec<_> = cluster_ids
+    // Already in document order, with in-text and note clusters interleaved.
+    let clusters: Vec<_> = cluster_ids
         .iter()
         // No number? Not considered to be in document, position participant.
         // Although may be disamb participant.
         .filter_map(|&id| get_cluster_data(db, id))
         .collect();
-    clusters.sort_by_key(|cluster| cluster.number);
     Arc::new(clusters)
 }
 
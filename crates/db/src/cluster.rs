@@ -33,10 +33,14 @@ impl Symbol for ClusterId {
     }
 }
 
+/// Where a cluster sits among the notes. Clusters are ordered by note number, then by their index
+/// within that note.
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Ord)]
 #[serde(untagged)]
 pub enum IntraNote {
     Single(u32),
+    /// `[note, index]`. `set_cluster_order` numbers the clusters sharing a note 0, 1, 2... in the
+    /// order it was given them, so that order alone decides which one comes first.
     Multi(u32, u32),
 }
 
@@ -17,6 +17,7 @@ pub fn safe_default(db: &mut (impl cite::CiteDatabase + xml::LocaleDatabase + xm
     db.set_style_with_durability(Default::default(), Durability::HIGH);
     db.set_formatter_with_durability(Markup::html(), Durability::HIGH);
     db.set_all_keys_with_durability(Default::default(), Durability::MEDIUM);
+    db.set_unknown_type_fallback_with_durability(None, Durability::HIGH);
     db.set_all_uncited(Default::default());
     db.set_all_cluster_ids(Arc::new(Default::default()));
     db.set_clusters_ordered(Arc::new(Default::default()));
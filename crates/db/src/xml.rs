@@ -116,7 +116,8 @@ fn name_configurations_inner(
 fn name_configurations_middle(style: &Style) -> Vec<(NameVariable, Name)> {
     let base = style.name_citation();
     let mut vec = Vec::new();
-    for el in &style.citation.layout.elements {
+    let layouts = std::iter::once(&style.citation.layout).chain(&style.citation.locale_layouts);
+    for el in layouts.flat_map(|layout| &layout.elements) {
         name_configurations_inner(style, &base, el, &mut vec);
     }
     vec
@@ -194,6 +195,12 @@ pub trait LocaleDatabase: StyleDatabase + HasFetcher {
 
     #[salsa::transparent]
     fn default_lang(&self) -> Lang;
+
+    /// The locale to render a reference with. With the `locale-by-language` feature, a reference
+    /// with a `language` field gets the locale for that language; otherwise this is the default
+    /// locale.
+    #[salsa::transparent]
+    fn reference_locale(&self, language: Option<Lang>) -> Arc<Locale>;
 }
 
 fn default_lang(db: &dyn LocaleDatabase) -> Lang {
@@ -209,6 +216,13 @@ fn default_locale(db: &dyn LocaleDatabase) -> Arc<Locale> {
     db.merged_locale(db.default_lang())
 }
 
+fn reference_locale(db: &dyn LocaleDatabase, language: Option<Lang>) -> Arc<Locale> {
+    match language {
+        Some(lang) if db.style().features.locale_by_language => db.merged_locale(lang),
+        _ => db.default_locale(),
+    }
+}
+
 fn locale_xml(db: &dyn LocaleDatabase, key: Lang) -> Option<Arc<String>> {
     let stored = db.locale_input_langs();
     if stored.contains(&key) {
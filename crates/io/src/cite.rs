@@ -5,6 +5,7 @@
 // Copyright © 2018 Corporation for Digital Scholarship
 
 use super::output::{markup::Markup, OutputFormat};
+use crate::DateOrRange;
 use crate::NumberLike;
 use crate::String;
 use csl::Atom;
@@ -41,16 +42,19 @@ use serde::de::{Deserialize, Deserializer};
 ///     basic_mode("smith", CiteMode::AuthorOnly),
 /// ])
 /// ```
-#[derive(Clone, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", bound(deserialize = ""))]
+#[derive(Clone, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(
+    rename_all = "camelCase",
+    bound(deserialize = "", serialize = "O::Input: serde::Serialize")
+)]
 pub struct Cite<O: OutputFormat> {
     #[serde(rename = "id", deserialize_with = "get_ref_id")]
     pub ref_id: Atom,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub prefix: Option<O::Input>,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub suffix: Option<O::Input>,
 
     /// Multiple locator functionality needs CSL support, so it is disabled via using
@@ -58,6 +62,20 @@ pub struct Cite<O: OutputFormat> {
     #[serde(default, flatten, deserialize_with = "Locators::single_locator")]
     pub locators: Option<Locators>,
 
+    /// CSL-M's `locator-extra`, rendered with `<text variable="locator-extra"/>`. Ignored unless
+    /// the cite also has a locator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locator_extra: Option<String>,
+
+    /// A date for the locator, in the same format as a reference's dates, rendered with
+    /// `<date variable="locator-date"/>`. Ignored unless the cite also has a locator.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::csl_json::opt_date"
+    )]
+    pub locator_date: Option<DateOrRange>,
+
     #[serde(default, flatten)]
     pub mode: Option<CiteMode>,
 }
@@ -77,6 +95,12 @@ impl<O: OutputFormat> fmt::Debug for Cite<O> {
         if let Some(locators) = self.locators.as_ref() {
             write!(f, ", locators: {:?}", locators)?;
         }
+        if let Some(locator_extra) = self.locator_extra.as_ref() {
+            write!(f, ", locator_extra: {:?}", locator_extra)?;
+        }
+        if let Some(locator_date) = self.locator_date.as_ref() {
+            write!(f, ", locator_date: {:?}", locator_date)?;
+        }
         if let Some(mode) = self.mode.as_ref() {
             write!(f, ", mode: {:?}", mode)?;
         }
@@ -126,6 +150,16 @@ pub struct CiteCompat {
     #[serde(default, flatten, deserialize_with = "Locators::single_locator")]
     pub locators: Option<Locators>,
 
+    #[serde(default, rename = "locator-extra")]
+    pub locator_extra: Option<String>,
+
+    #[serde(
+        default,
+        rename = "locator-date",
+        deserialize_with = "crate::csl_json::opt_date::deserialize"
+    )]
+    pub locator_date: Option<DateOrRange>,
+
     #[serde(default, flatten, deserialize_with = "CiteMode::compat")]
     pub mode: Option<CiteMode>,
 }
@@ -144,7 +178,7 @@ pub mod cite_compat_vec {
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize)]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
 pub struct Locator {
     pub locator: NumberLike,
     #[serde(default, rename = "label")]
@@ -172,7 +206,7 @@ where
 /// Accepts either
 /// `{ "locator": "54", "label": "page" }` or
 /// `{ "locators": [["chapter", "19"], ["page", "581"]] }`.
-#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(untagged)]
 pub enum Locators {
     Single(Locator),
@@ -227,7 +261,7 @@ impl Locators {
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize)]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
 #[serde(tag = "mode")]
 pub enum CiteMode {
     AuthorOnly,
@@ -311,6 +345,8 @@ impl<O: OutputFormat> Hash for Cite<O> {
         self.prefix.hash(h);
         self.suffix.hash(h);
         self.locators.hash(h);
+        self.locator_extra.hash(h);
+        self.locator_date.hash(h);
     }
 }
 
@@ -321,6 +357,8 @@ impl<O: OutputFormat> Cite<O> {
             prefix: Default::default(),
             suffix: Default::default(),
             locators: None,
+            locator_extra: None,
+            locator_date: None,
             mode: None,
         }
     }
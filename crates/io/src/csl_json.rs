@@ -13,6 +13,7 @@ mod cow_str;
 use crate::names::Name;
 use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
 use serde::de::{Error, IgnoredAny};
+use serde::ser::{Serialize, SerializeMap, Serializer};
 use std::borrow::Cow;
 use std::fmt;
 use std::str::FromStr;
@@ -95,12 +96,13 @@ enum Field<'a> {
     Id,
     Type,
     Language,
+    Custom,
     // don't use plain `&'a str`, because that would fail when parsing from a serde::Value.
     #[serde(borrow, deserialize_with = "cow_str::deserialize_cow_str")]
     Any(Cow<'a, str>),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Hash)]
 #[serde(untagged)]
 pub enum NumberLike {
     Str(String),
@@ -149,21 +151,18 @@ impl RelaxedBool {
     }
 }
 
-struct WrapType(CslType);
+/// Types this version doesn't know are kept as `Err(type)`, so that one reference using a newer
+/// CSL type doesn't fail the whole batch.
+struct WrapType(Result<CslType, String>);
 
 impl<'de> Deserialize<'de> for WrapType {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        const FIELDS: &[&str] = &["a legal CSL type"];
-        deserializer
-            .deserialize_identifier(CslVariantVisitor(
-                Features::new(),
-                FIELDS,
-                Default::default(),
-            ))
-            .map(WrapType)
+        let string: Cow<'de, str> = cow_str::deserialize_cow_str(deserializer)?;
+        let parsed = CslType::get_attr(&string, &Features::new()).map_err(|_| string.into_owned());
+        Ok(WrapType(parsed))
     }
 }
 
@@ -205,24 +204,33 @@ impl<'de> Deserialize<'de> for Reference {
                 V: MapAccess<'de>,
             {
                 let mut id: Option<NumberLike> = None;
-                let mut csl_type: Option<WrapType> = None;
+                let mut csl_type: Option<CslType> = None;
+                let mut unknown_type = None;
                 let mut language = None;
                 let mut ordinary = FnvHashMap::default();
                 let mut number = FnvHashMap::default();
                 let mut name = FnvHashMap::default();
                 let mut date = FnvHashMap::default();
+                let mut custom = None;
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::Id => {
                             id = Some(map.next_value()?);
                         }
-                        Field::Type => {
-                            csl_type = Some(map.next_value()?);
-                        }
+                        Field::Type => match map.next_value()? {
+                            WrapType(Ok(known)) => csl_type = Some(known),
+                            WrapType(Err(unknown)) => {
+                                log::warn!("reference had unknown type `{}`", unknown);
+                                unknown_type = Some(unknown);
+                            }
+                        },
                         Field::Language => {
                             let wrap: WrapLang = map.next_value()?;
                             language = wrap.0;
                         }
+                        Field::Custom => {
+                            custom = Some(map.next_value()?);
+                        }
                         Field::Any(var_name) => {
                             match AnyVariable::get_attr(&var_name, &Features::default()) {
                                 Err(_unknown) => {
@@ -253,12 +261,14 @@ impl<'de> Deserialize<'de> for Reference {
                     id: id
                         .map(|i| csl::Atom::from(i.into_string()))
                         .ok_or_else(|| de::Error::missing_field("id"))?,
-                    csl_type: csl_type.unwrap_or(WrapType(CslType::Article)).0,
+                    csl_type: csl_type.unwrap_or(CslType::Article),
+                    unknown_type,
                     language,
                     ordinary,
                     number,
                     name,
                     date,
+                    custom,
                 })
             }
         }
@@ -619,3 +629,91 @@ impl<'de> Deserialize<'de> for MaybeDate {
         deserializer.deserialize_struct("DateOrRange", DATE_TYPES, DateVisitor)
     }
 }
+
+/// For dates outside a reference, like [crate::Cite::locator_date]. Use with
+/// `#[serde(default, with = "...")]`.
+pub(crate) mod opt_date {
+    use super::*;
+    pub fn deserialize<'de, D>(d: D) -> Result<Option<DateOrRange>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        MaybeDate::deserialize(d).map(|MaybeDate(date)| date)
+    }
+    pub fn serialize<S>(date: &Option<DateOrRange>, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match date {
+            Some(date) => SerializeDate(date).serialize(s),
+            None => s.serialize_none(),
+        }
+    }
+}
+
+// Writing a Reference back out as CSL-JSON, such that it deserializes to the same thing.
+
+impl Serialize for Reference {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("id", &*self.id)?;
+        match &self.unknown_type {
+            Some(unknown) => map.serialize_entry("type", unknown)?,
+            None => map.serialize_entry("type", self.csl_type.as_ref())?,
+        }
+        if let Some(language) = &self.language {
+            map.serialize_entry("language", language)?;
+        }
+        for (var, value) in &self.ordinary {
+            map.serialize_entry(var.as_ref(), value)?;
+        }
+        for (var, value) in &self.number {
+            map.serialize_entry(var.as_ref(), value)?;
+        }
+        for (var, names) in &self.name {
+            map.serialize_entry(var.as_ref(), names)?;
+        }
+        for (var, date) in &self.date {
+            map.serialize_entry(var.as_ref(), &SerializeDate(date))?;
+        }
+        if let Some(custom) = &self.custom {
+            map.serialize_entry("custom", custom)?;
+        }
+        map.end()
+    }
+}
+
+struct SerializeDate<'a>(&'a DateOrRange);
+
+impl Serialize for SerializeDate<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        fn parts(date: &Date) -> Vec<i32> {
+            let mut parts = vec![date.year];
+            if date.day != 0 {
+                parts.push(date.month as i32);
+                parts.push(date.day as i32);
+            } else if date.month != 0 {
+                parts.push(date.month as i32);
+            }
+            parts
+        }
+        let mut map = serializer.serialize_map(None)?;
+        match self.0 {
+            DateOrRange::Single(d) => map.serialize_entry("date-parts", &[parts(d)])?,
+            DateOrRange::Range(d1, d2) => {
+                map.serialize_entry("date-parts", &[parts(d1), parts(d2)])?
+            }
+            DateOrRange::Literal { literal, .. } => map.serialize_entry("literal", literal)?,
+        }
+        if self.0.is_uncertain_date() {
+            map.serialize_entry("circa", &true)?;
+        }
+        map.end()
+    }
+}
@@ -54,7 +54,7 @@ enum NameInput {
 }
 
 #[derive(Debug, Eq, PartialEq, Hash, Deserialize, Serialize, Clone)]
-#[serde(from = "NameInput")]
+#[serde(from = "NameInput", untagged)]
 pub enum Name {
     // Put literal first, because PersonName's properties are all Options and derived
     // Deserialize impls run in order.
@@ -499,3 +499,40 @@ fn test_is_latin() {
     };
     assert!(pn_is_latin_cyrillic(&pn));
 }
+
+#[test]
+fn parse_compound_family() {
+    // Two capitalised words are both the family name
+    let pn: PersonName = PersonNameInput {
+        given: Some("Gabriel".into()),
+        family: Some("García Márquez".into()),
+        ..Default::default()
+    }
+    .into();
+    assert_eq!(
+        pn,
+        PersonName {
+            given: Some("Gabriel".into()),
+            family: Some("García Márquez".into()),
+            is_latin_cyrillic: true,
+            ..Default::default()
+        }
+    );
+
+    let pn: PersonName = PersonNameInput {
+        given: Some("Juana Inés".into()),
+        family: Some("de la Cruz".into()),
+        ..Default::default()
+    }
+    .into();
+    assert_eq!(
+        pn,
+        PersonName {
+            given: Some("Juana Inés".into()),
+            non_dropping_particle: Some("de la".into()),
+            family: Some("Cruz".into()),
+            is_latin_cyrillic: true,
+            ..Default::default()
+        }
+    );
+}
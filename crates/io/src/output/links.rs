@@ -13,6 +13,37 @@ pub enum Link {
     // Href(String),
 }
 
+/// An internal link between a cite and its bibliography entry, see
+/// [FormatOptions::cite_anchors](super::markup::FormatOptions::cite_anchors), or the identifying
+/// data on a cluster, see
+/// [FormatOptions::cluster_data_attributes](super::markup::FormatOptions::cluster_data_attributes).
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub enum Anchor {
+    /// Links a cite to the entry for the reference with this id.
+    CiteHref(String),
+    /// Marks the entry for the reference with this id as a link target.
+    BibTarget(String),
+    /// Tags a cluster with its id and the reference ids of its cites.
+    ClusterData {
+        cluster_id: String,
+        cites: Vec<String>,
+    },
+}
+
+impl Anchor {
+    /// The fragment identifier shared by both ends of a link, e.g. `ref-smith2000`. `None` for
+    /// [Anchor::ClusterData].
+    pub fn fragment(&self) -> Option<String> {
+        let ref_id = match self {
+            Anchor::CiteHref(id) | Anchor::BibTarget(id) => id,
+            Anchor::ClusterData { .. } => return None,
+        };
+        let mut fragment = String::from("ref-");
+        fragment.push_str(ref_id);
+        Some(fragment)
+    }
+}
+
 impl Link {
     fn url(url: Url, orig: &str) -> Self {
         Self::Url {
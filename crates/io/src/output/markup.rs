@@ -5,7 +5,7 @@
 // Copyright © 2019 Corporation for Digital Scholarship
 
 use self::InlineElement::*;
-use super::links::Link;
+use super::links::{Anchor, Link};
 use super::micro_html::MicroNode;
 use super::{FormatCmd, LocalizedQuotes, OutputFormat};
 use crate::utils::JoinMany;
@@ -25,6 +25,13 @@ use self::html::HtmlWriter;
 mod plain;
 use self::plain::PlainWriter;
 
+mod jats;
+use self::jats::JatsWriter;
+
+mod custom;
+use self::custom::CustomWriter;
+pub use self::custom::{CustomFormat, CustomMarkup};
+
 mod flip_flop;
 use self::flip_flop::FlipFlopState;
 mod move_punctuation;
@@ -39,6 +46,10 @@ pub enum Markup {
     Html(FormatOptions),
     Rtf(FormatOptions),
     Plain(FormatOptions),
+    /// Inline JATS XML tags, suitable for the inside of a `<mixed-citation>`
+    Jats(FormatOptions),
+    /// A format supplied by the library user, see [CustomMarkup].
+    Custom(CustomFormat),
 }
 
 /// Controls how the output is formatted.
@@ -46,11 +57,31 @@ pub enum Markup {
 pub struct FormatOptions {
     /// See CSL 1.1, Appendix VI -- enable or disable making urls clickable. Default is enabled.
     pub link_anchors: bool,
+    /// HTML only. Wrap each cite in `<a href="#ref-{id}">` and start each bibliography entry with
+    /// a matching `<a id="ref-{id}"></a>`, so cites link to their entries. Default is disabled.
+    pub cite_anchors: bool,
+    /// HTML only. Escape just `&`, `<` and `>` in text, leaving quotes and apostrophes as they
+    /// are, the way the CSL test suite's expected output is written. Default is disabled.
+    pub test_escaping: bool,
+    /// HTML only. Put the bibliography's hanging-indent, line-spacing and entry-spacing in `style`
+    /// attributes on the wrapper and entry divs given in the bibliography meta, for pages that
+    /// can't add a stylesheet. Default is disabled.
+    pub bib_inline_styles: bool,
+    /// HTML only. Wrap each cluster in `<span data-cluster-id="{id}" data-cites="{id1},{id2}">`,
+    /// giving the cluster's id and the reference ids of its cites in order, so rich editors can
+    /// find what was clicked. Default is disabled.
+    pub cluster_data_attributes: bool,
 }
 
 impl Default for FormatOptions {
     fn default() -> Self {
-        FormatOptions { link_anchors: true }
+        FormatOptions {
+            link_anchors: true,
+            cite_anchors: false,
+            test_escaping: false,
+            bib_inline_styles: false,
+            cluster_data_attributes: false,
+        }
     }
 }
 
@@ -58,6 +89,10 @@ impl FormatOptions {
     pub fn test_suite() -> Self {
         FormatOptions {
             link_anchors: false,
+            cite_anchors: false,
+            test_escaping: true,
+            bib_inline_styles: false,
+            cluster_data_attributes: false,
         }
     }
 }
@@ -79,6 +114,9 @@ pub enum InlineElement {
     Text(String),
     Linked(Link),
     Div(DisplayMode, Vec<InlineElement>),
+    /// Only constructed when [FormatOptions::cite_anchors] or
+    /// [FormatOptions::cluster_data_attributes] is enabled.
+    Anchored(Anchor, Vec<InlineElement>),
 }
 
 impl InlineElement {}
@@ -96,6 +134,46 @@ impl Markup {
     pub fn plain() -> Self {
         Markup::Plain(FormatOptions::default())
     }
+    pub fn jats() -> Self {
+        Markup::Jats(FormatOptions::default())
+    }
+    /// What to put before and after the whole bibliography.
+    pub fn bib_body_affixes(&self) -> (&'static str, &'static str) {
+        match self {
+            Markup::Html(_) => ("<div class=\"csl-bib-body\">", "</div>"),
+            Markup::Rtf(_) => ("", ""),
+            Markup::Plain(_) => ("", ""),
+            Markup::Jats(_) => ("", ""),
+            Markup::Custom(custom) => custom.markup().bib_body_affixes(),
+        }
+    }
+    /// What to put before and after each bibliography entry, so that entries are separated
+    /// naturally for this format: one `csl-entry` div, RTF paragraph or line each.
+    pub fn bib_entry_affixes(&self) -> (&'static str, &'static str) {
+        match self {
+            Markup::Html(_) => ("<div class=\"csl-entry\">", "</div>\n"),
+            Markup::Rtf(_) => ("", "\\par\n"),
+            Markup::Plain(_) => ("", "\n"),
+            Markup::Jats(_) => ("", "\n"),
+            Markup::Custom(custom) => custom.markup().bib_entry_affixes(),
+        }
+    }
+}
+
+impl Markup {
+    /// Like [OutputFormat::meta], but if this is HTML with [FormatOptions::bib_inline_styles] set,
+    /// the wrapper and entry divs get `body_style` and `entry_style` as `style` attributes.
+    pub fn meta_with_styles(&self, body_style: &str, entry_style: &str) -> MarkupBibMeta {
+        match self {
+            Markup::Html(options) if options.bib_inline_styles => MarkupBibMeta {
+                markup_pre: format!("<div class=\"csl-bib-body\" style=\"{}\">", body_style).into(),
+                markup_post: "</div>".into(),
+                entry_pre: format!("<div class=\"csl-entry\" style=\"{}\">", entry_style).into(),
+                entry_post: "</div>\n".into(),
+            },
+            _ => self.meta(),
+        }
+    }
 }
 
 impl Default for Markup {
@@ -107,9 +185,13 @@ impl Default for Markup {
 #[derive(Debug, Clone, Serialize)]
 pub struct MarkupBibMeta {
     #[serde(rename = "markupPre")]
-    markup_pre: String,
+    pub markup_pre: String,
     #[serde(rename = "markupPost")]
-    markup_post: String,
+    pub markup_post: String,
+    #[serde(rename = "entryPre")]
+    pub entry_pre: String,
+    #[serde(rename = "entryPost")]
+    pub entry_post: String,
 }
 
 impl OutputFormat for Markup {
@@ -119,14 +201,13 @@ impl OutputFormat for Markup {
     type BibMeta = MarkupBibMeta;
 
     fn meta(&self) -> Self::BibMeta {
-        let (pre, post) = match self {
-            Markup::Html(_) => ("<div class=\"csl-bib-body\">", "</div>"),
-            Markup::Rtf(_) => ("", ""),
-            Markup::Plain(_) => ("", ""),
-        };
+        let (pre, post) = self.bib_body_affixes();
+        let (entry_pre, entry_post) = self.bib_entry_affixes();
         MarkupBibMeta {
             markup_pre: pre.into(),
             markup_post: post.into(),
+            entry_pre: entry_pre.into(),
+            entry_post: entry_post.into(),
         }
     }
 
@@ -242,6 +323,8 @@ impl OutputFormat for Markup {
             Markup::Html(options) => HtmlWriter::new(dest, options).stack_preorder(stack),
             Markup::Rtf(options) => PlainWriter::new(dest, options).stack_preorder(stack),
             Markup::Plain(options) => PlainWriter::new(dest, options).stack_preorder(stack),
+            Markup::Jats(options) => JatsWriter::new(dest, options).stack_preorder(stack),
+            Markup::Custom(ref custom) => CustomWriter::new(dest, custom).stack_preorder(stack),
         }
     }
 
@@ -251,6 +334,8 @@ impl OutputFormat for Markup {
             Markup::Html(options) => HtmlWriter::new(dest, options).stack_postorder(stack),
             Markup::Rtf(options) => PlainWriter::new(dest, options).stack_postorder(stack),
             Markup::Plain(options) => PlainWriter::new(dest, options).stack_postorder(stack),
+            Markup::Jats(options) => JatsWriter::new(dest, options).stack_postorder(stack),
+            Markup::Custom(ref custom) => CustomWriter::new(dest, custom).stack_postorder(stack),
         }
     }
 
@@ -282,6 +367,46 @@ impl OutputFormat for Markup {
 }
 
 impl Markup {
+    fn anchored(&self, anchor: Anchor, build: Vec<InlineElement>) -> Vec<InlineElement> {
+        let enabled = |options: &FormatOptions| match anchor {
+            Anchor::CiteHref(_) | Anchor::BibTarget(_) => options.cite_anchors,
+            Anchor::ClusterData { .. } => options.cluster_data_attributes,
+        };
+        match self {
+            Markup::Html(options) if enabled(options) && !build.is_empty() => {
+                vec![Anchored(anchor, build)]
+            }
+            _ => build,
+        }
+    }
+
+    /// Links a rendered cite to the bibliography entry for `ref_id`, if enabled by
+    /// [FormatOptions::cite_anchors].
+    pub fn cite_anchor(&self, ref_id: &str, build: Vec<InlineElement>) -> Vec<InlineElement> {
+        self.anchored(Anchor::CiteHref(ref_id.into()), build)
+    }
+
+    /// Marks a rendered bibliography entry as the target of cites to `ref_id`, if enabled by
+    /// [FormatOptions::cite_anchors].
+    pub fn bib_anchor(&self, ref_id: &str, build: Vec<InlineElement>) -> Vec<InlineElement> {
+        self.anchored(Anchor::BibTarget(ref_id.into()), build)
+    }
+
+    /// Tags a rendered cluster with its id and its cites' reference ids, if enabled by
+    /// [FormatOptions::cluster_data_attributes].
+    pub fn cluster_data(
+        &self,
+        cluster_id: &str,
+        ref_ids: &[&str],
+        build: Vec<InlineElement>,
+    ) -> Vec<InlineElement> {
+        let anchor = Anchor::ClusterData {
+            cluster_id: cluster_id.into(),
+            cites: ref_ids.iter().map(|&id| String::from(id)).collect(),
+        };
+        self.anchored(anchor, build)
+    }
+
     fn fmt_vec(
         &self,
         inlines: Vec<InlineElement>,
@@ -313,6 +438,12 @@ impl Markup {
             Markup::Plain(options) => {
                 PlainWriter::new(&mut dest, options).write_inlines(&flipped, false)
             }
+            Markup::Jats(options) => {
+                JatsWriter::new(&mut dest, options).write_inlines(&flipped, false)
+            }
+            Markup::Custom(ref custom) => {
+                CustomWriter::new(&mut dest, custom).write_inlines(&flipped, false)
+            }
         }
         dest
     }
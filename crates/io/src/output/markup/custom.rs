@@ -0,0 +1,196 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+use super::{FormatOptions, InlineElement, MarkupWriter, MaybeTrimStart};
+use crate::output::markup::Link;
+use crate::output::micro_html::MicroNode;
+use crate::output::FormatCmd;
+use crate::String;
+use core::fmt;
+use csl::Formatting;
+use std::sync::Arc;
+
+/// An output format supplied by a library user, for markup citeproc-rs doesn't produce itself.
+///
+/// By the time anything is written, quotes have been localized and flip-flopped and punctuation
+/// has been moved, so an implementation only has to escape text and write formatting. Links are
+/// written as their text. Register one with [Markup::Custom](super::Markup::Custom).
+pub trait CustomMarkup: fmt::Debug + Send + Sync {
+    /// Write `text` to `dest`, escaped however the format needs.
+    fn write_text(&self, dest: &mut String, text: &str);
+    /// Start some formatting, like italics or a display block. Ignores it by default.
+    fn open(&self, _dest: &mut String, _cmd: FormatCmd) {}
+    /// End formatting started by [CustomMarkup::open]. Ignores it by default.
+    fn close(&self, _dest: &mut String, _cmd: FormatCmd) {}
+    /// What to put before and after the whole bibliography.
+    fn bib_body_affixes(&self) -> (&'static str, &'static str) {
+        ("", "")
+    }
+    /// What to put before and after each bibliography entry.
+    fn bib_entry_affixes(&self) -> (&'static str, &'static str) {
+        ("", "\n")
+    }
+}
+
+/// A [CustomMarkup] implementation and the options to format with. Two are equal if they share
+/// the same implementation (by pointer) and options.
+#[derive(Clone)]
+pub struct CustomFormat {
+    markup: Arc<dyn CustomMarkup>,
+    options: FormatOptions,
+}
+
+impl CustomFormat {
+    pub fn new(markup: Arc<dyn CustomMarkup>, options: FormatOptions) -> Self {
+        CustomFormat { markup, options }
+    }
+    pub fn markup(&self) -> &dyn CustomMarkup {
+        &*self.markup
+    }
+    pub fn options(&self) -> FormatOptions {
+        self.options
+    }
+}
+
+impl PartialEq for CustomFormat {
+    fn eq(&self, other: &Self) -> bool {
+        // Compare data pointers only; vtable pointers are not guaranteed to be unique.
+        Arc::as_ptr(&self.markup) as *const () == Arc::as_ptr(&other.markup) as *const ()
+            && self.options == other.options
+    }
+}
+
+impl Eq for CustomFormat {}
+
+impl fmt::Debug for CustomFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomFormat")
+            .field("markup", &self.markup)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub struct CustomWriter<'a> {
+    dest: &'a mut String,
+    format: &'a CustomFormat,
+}
+
+impl<'a> CustomWriter<'a> {
+    pub fn new(dest: &'a mut String, format: &'a CustomFormat) -> Self {
+        CustomWriter { dest, format }
+    }
+}
+
+impl<'a> MarkupWriter for CustomWriter<'a> {
+    fn buf(&mut self) -> &mut String {
+        self.dest
+    }
+
+    fn write_escaped(&mut self, text: &str) {
+        self.format.markup.write_text(self.dest, text);
+    }
+
+    fn write_url(&mut self, url: &url::Url, trailing_slash: bool, _in_attr: bool) {
+        let mut text = String::new();
+        super::write_url(
+            &mut text,
+            url,
+            trailing_slash,
+            false,
+            |b, s| Ok(b.push_str(s)),
+            |b, s| Ok(b.push_str(s)),
+        )
+        .unwrap();
+        self.write_escaped(&text);
+    }
+
+    fn stack_preorder(&mut self, stack: &[FormatCmd]) {
+        for cmd in stack.iter() {
+            self.format.markup.open(self.dest, *cmd);
+        }
+    }
+
+    fn stack_postorder(&mut self, stack: &[FormatCmd]) {
+        for cmd in stack.iter().rev() {
+            self.format.markup.close(self.dest, *cmd);
+        }
+    }
+
+    fn write_micro(&mut self, micro: &MicroNode, trim_start: bool) {
+        use MicroNode::*;
+        match micro {
+            Text(text) => {
+                self.write_escaped(text.trim_start_if(trim_start));
+            }
+            Quoted {
+                is_inner,
+                localized,
+                children,
+            } => {
+                self.write_escaped(localized.opening(*is_inner).trim_start_if(trim_start));
+                self.write_micros(children, false);
+                self.write_escaped(localized.closing(*is_inner));
+            }
+            Formatted(nodes, cmd) => {
+                self.stack_preorder(&[*cmd][..]);
+                self.write_micros(nodes, trim_start);
+                self.stack_postorder(&[*cmd][..]);
+            }
+            NoCase(inners) => {
+                self.write_micros(inners, trim_start);
+            }
+            NoDecor(inners) => {
+                self.write_micros(inners, trim_start);
+            }
+        }
+    }
+
+    fn write_inline(&mut self, inline: &InlineElement, trim_start: bool) {
+        use super::InlineElement::*;
+        match inline {
+            Text(text) => {
+                self.write_escaped(text.trim_start_if(trim_start));
+            }
+            Div(display, inlines) => {
+                self.stack_formats(inlines, Formatting::default(), Some(*display));
+            }
+            Micro(micros) => {
+                self.write_micros(micros, trim_start);
+            }
+            Formatted(inlines, formatting) => {
+                self.stack_formats(inlines, *formatting, None);
+            }
+            Quoted {
+                is_inner,
+                localized,
+                inlines,
+            } => {
+                self.write_escaped(localized.opening(*is_inner).trim_start_if(trim_start));
+                self.write_inlines(inlines, false);
+                self.write_escaped(localized.closing(*is_inner));
+            }
+            Linked(link) => {
+                self.write_link("", link, "", "", self.format.options);
+            }
+            // cite anchors are HTML only
+            Anchored(_, inlines) => self.write_inlines(inlines, trim_start),
+        }
+    }
+
+    fn write_link(&mut self, _: &str, link: &Link, _: &str, _: &str, _: FormatOptions) {
+        match link {
+            Link::Url {
+                url,
+                trailing_slash,
+            } => {
+                self.write_url(url, *trailing_slash, false);
+            }
+            Link::Id { id, url: _ } => self.write_escaped(id),
+        }
+    }
+}
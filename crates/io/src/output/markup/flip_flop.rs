@@ -110,11 +110,13 @@ fn flip_flop(
             ref localized,
             ref inlines,
         } => {
+            // Quotes alternate outer -> inner -> outer, so anything nested within this one gets
+            // the opposite kind.
             let mut flop = state.clone();
-            flop.in_inner_quotes = !flop.in_inner_quotes;
+            flop.in_inner_quotes = !state.in_inner_quotes;
             let nodes = flop.flip_flop_inlines(inlines);
             Ok(InlineElement::Quoted {
-                is_inner: flop.in_inner_quotes,
+                is_inner: state.in_inner_quotes,
                 localized: localized.clone(),
                 inlines: nodes,
             })
@@ -125,6 +127,11 @@ fn flip_flop(
             Ok(InlineElement::Div(dm, nodes))
         }
 
+        InlineElement::Anchored(ref anchor, ref inlines) => {
+            let nodes = state.flip_flop_inlines(inlines);
+            Ok(InlineElement::Anchored(anchor.clone(), nodes))
+        }
+
         InlineElement::Text(ref string) if string.is_empty() => Err(vec![]),
 
         _ => Ok(inline.clone()),
@@ -154,7 +161,7 @@ fn flip_flop_node(node: &MicroNode, state: &FlipFlopState) -> Result<MicroNode,
             flop.in_inner_quotes = !state.in_inner_quotes;
             let nodes = flip_flop_nodes(children, &flop);
             Ok(MicroNode::Quoted {
-                is_inner: flop.in_inner_quotes,
+                is_inner: state.in_inner_quotes,
                 localized: localized.clone(),
                 children: nodes,
             })
@@ -5,6 +5,7 @@
 // Copyright © 2019 Corporation for Digital Scholarship
 
 use super::{FormatOptions, InlineElement, MarkupWriter, MaybeTrimStart};
+use crate::output::links::Anchor;
 use crate::output::micro_html::MicroNode;
 use crate::output::FormatCmd;
 use crate::String;
@@ -29,7 +30,11 @@ impl<'a> MarkupWriter for HtmlWriter<'a> {
         self.dest
     }
     fn write_escaped(&mut self, text: &str) {
-        write!(self.dest, "{}", escape_html(text)).unwrap();
+        if self.options.test_escaping {
+            write!(self.dest, "{}", escape_html_test(text)).unwrap();
+        } else {
+            write!(self.dest, "{}", escape_html(text)).unwrap();
+        }
     }
     fn stack_preorder(&mut self, stack: &[FormatCmd]) {
         for cmd in stack.iter() {
@@ -109,6 +114,38 @@ impl<'a> MarkupWriter for HtmlWriter<'a> {
                 self.write_escaped(localized.closing(*is_inner));
             }
             Linked(link) => self.write_link(r#"<a href=""#, link, r#"">"#, "</a>", self.options),
+            Anchored(anchor, inlines) => {
+                let fragment = anchor.fragment().unwrap_or_default();
+                match anchor {
+                    Anchor::CiteHref(_) => {
+                        self.write_raw(r##"<a href="#"##);
+                        write!(self.dest, "{}", escape_html_attribute(&fragment)).unwrap();
+                        self.write_raw(r#"">"#);
+                        self.write_inlines(inlines, trim_start);
+                        self.write_raw("</a>");
+                    }
+                    Anchor::BibTarget(_) => {
+                        self.write_raw(r#"<a id=""#);
+                        write!(self.dest, "{}", escape_html_attribute(&fragment)).unwrap();
+                        self.write_raw(r#""></a>"#);
+                        self.write_inlines(inlines, trim_start);
+                    }
+                    Anchor::ClusterData { cluster_id, cites } => {
+                        self.write_raw(r#"<span data-cluster-id=""#);
+                        write!(self.dest, "{}", escape_html_attribute(cluster_id)).unwrap();
+                        self.write_raw(r#"" data-cites=""#);
+                        for (i, id) in cites.iter().enumerate() {
+                            if i > 0 {
+                                self.write_raw(",");
+                            }
+                            write!(self.dest, "{}", escape_html_attribute(id)).unwrap();
+                        }
+                        self.write_raw(r#"">"#);
+                        self.write_inlines(inlines, trim_start);
+                        self.write_raw("</span>");
+                    }
+                }
+            }
         }
     }
 
@@ -174,26 +211,48 @@ fn scan_encodable_attr<'a>(remain: &'a str) -> IResult<&'a str, Encodable<'a>> {
         .parse(remain)
 }
 
-/// Try to gobble up as many non-escaping characters as possible.
+/// Try to gobble up as many non-escaping characters as possible. Like citeproc-js, apostrophes
+/// and slashes in text are left alone.
 fn scan_encodable<'a>(remain: &'a str) -> IResult<&'a str, Encodable<'a>> {
-    nbc::take_till1(|x| matches!(x, '<' | '>' | '&' | '"' | '\''))
+    nbc::take_till1(|x| matches!(x, '<' | '>' | '&' | '"'))
         .map(Encodable::Chunk)
         .or(nbc::tag("<").map(|_| Encodable::Esc("&lt;")))
         .or(nbc::tag(">").map(|_| Encodable::Esc("&gt;")))
         .or(nbc::tag("&").map(|_| Encodable::Esc("&amp;")))
         .or(nbc::tag("\"").map(|_| Encodable::Esc("&quot;")))
-        .or(nbc::tag("'").map(|_| Encodable::Esc("&#x27;")))
         .parse(remain)
 }
 
-struct HtmlEscaper<'a> {
+/// Like [scan_encodable_attr], but for XML, which also needs `<` and `&` escaped in attributes.
+fn scan_encodable_xml_attr<'a>(remain: &'a str) -> IResult<&'a str, Encodable<'a>> {
+    nbc::take_till1(|x| matches!(x, '<' | '&' | '"' | '\''))
+        .map(Encodable::Chunk)
+        .or(nbc::tag("<").map(|_| Encodable::Esc("&lt;")))
+        .or(nbc::tag("&").map(|_| Encodable::Esc("&amp;")))
+        .or(nbc::tag("\"").map(|_| Encodable::Esc("&quot;")))
+        .or(nbc::tag("'").map(|_| Encodable::Esc("&apos;")))
+        .parse(remain)
+}
+
+/// Like [scan_encodable], but leaves double quotes alone too, as the CSL test suite does.
+fn scan_encodable_test<'a>(remain: &'a str) -> IResult<&'a str, Encodable<'a>> {
+    nbc::take_till1(|x| matches!(x, '<' | '>' | '&'))
+        .map(Encodable::Chunk)
+        .or(nbc::tag("<").map(|_| Encodable::Esc("&lt;")))
+        .or(nbc::tag(">").map(|_| Encodable::Esc("&gt;")))
+        .or(nbc::tag("&").map(|_| Encodable::Esc("&amp;")))
+        .parse(remain)
+}
+
+pub(super) struct HtmlEscaper<'a> {
     text: &'a str,
+    scan: fn(&'a str) -> IResult<&'a str, Encodable<'a>>,
 }
 
 impl fmt::Display for HtmlEscaper<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut remain = self.text;
-        while let Ok((rest, chunk)) = scan_encodable(remain) {
+        while let Ok((rest, chunk)) = (self.scan)(remain) {
             remain = rest;
             match chunk {
                 Encodable::Chunk(s) => f.write_str(s)?,
@@ -227,5 +286,53 @@ fn escape_html_attribute(attr_inner: &str) -> HtmlAttrEscaper {
 }
 
 fn escape_html(text: &str) -> HtmlEscaper {
-    HtmlEscaper { text }
+    HtmlEscaper {
+        text,
+        scan: scan_encodable,
+    }
+}
+
+fn escape_html_test(text: &str) -> HtmlEscaper {
+    HtmlEscaper {
+        text,
+        scan: scan_encodable_test,
+    }
+}
+
+/// For XML text content, e.g. JATS. XML does not require quotes to be escaped there, so this is
+/// the same as [escape_html_test].
+pub(super) fn escape_xml(text: &str) -> HtmlEscaper {
+    escape_html_test(text)
+}
+
+pub(super) fn escape_xml_attribute(attr_inner: &str) -> HtmlEscaper {
+    HtmlEscaper {
+        text: attr_inner,
+        scan: scan_encodable_xml_attr,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_escaping_leaves_quotes() {
+        let text = r#"Q&A <b> "it's""#;
+        let mut normal = String::new();
+        write!(&mut normal, "{}", escape_html(text)).unwrap();
+        assert_eq!(normal.as_str(), "Q&amp;A &lt;b&gt; &quot;it's&quot;");
+        let mut test = String::new();
+        write!(&mut test, "{}", escape_html_test(text)).unwrap();
+        assert_eq!(test.as_str(), r#"Q&amp;A &lt;b&gt; "it's""#);
+    }
+
+    #[test]
+    fn leaves_slashes() {
+        let mut html = String::new();
+        let mut writer = HtmlWriter::new(&mut html, FormatOptions::default());
+        let url = Url::parse("https://example.com/a/b/").unwrap();
+        writer.write_url(&url, true, false);
+        assert_eq!(html.as_str(), "https://example.com/a/b/");
+    }
 }
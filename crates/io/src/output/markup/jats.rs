@@ -0,0 +1,181 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+//! Inline JATS XML, i.e. the formatting elements you can put inside a `<mixed-citation>`. This
+//! does not attempt to produce `<element-citation>` structure.
+
+use super::html::{escape_xml, escape_xml_attribute};
+use super::{FormatOptions, InlineElement, MarkupWriter, MaybeTrimStart};
+use crate::output::micro_html::MicroNode;
+use crate::output::FormatCmd;
+use crate::String;
+use core::fmt::Write;
+use csl::Formatting;
+use url::Url;
+
+#[derive(Debug)]
+pub struct JatsWriter<'a> {
+    dest: &'a mut String,
+    options: FormatOptions,
+}
+
+impl<'a> JatsWriter<'a> {
+    pub fn new(dest: &'a mut String, options: FormatOptions) -> Self {
+        JatsWriter { dest, options }
+    }
+}
+
+impl<'a> MarkupWriter for JatsWriter<'a> {
+    fn buf(&mut self) -> &mut String {
+        self.dest
+    }
+    fn write_escaped(&mut self, text: &str) {
+        write!(self.dest, "{}", escape_xml(text)).unwrap();
+    }
+    fn stack_preorder(&mut self, stack: &[FormatCmd]) {
+        for tag in stack.iter().filter_map(|cmd| cmd.jats_tag()) {
+            self.dest.push_str("<");
+            self.dest.push_str(tag);
+            self.dest.push_str(">");
+        }
+    }
+
+    fn stack_postorder(&mut self, stack: &[FormatCmd]) {
+        for tag in stack.iter().rev().filter_map(|cmd| cmd.jats_tag()) {
+            self.dest.push_str("</");
+            self.dest.push_str(tag);
+            self.dest.push_str(">");
+        }
+    }
+
+    fn write_micro(&mut self, micro: &MicroNode, trim_start: bool) {
+        use MicroNode::*;
+        match micro {
+            Text(text) => {
+                self.write_escaped(text.trim_start_if(trim_start));
+            }
+            Quoted {
+                is_inner,
+                localized,
+                children,
+            } => {
+                self.write_escaped(localized.opening(*is_inner).trim_start_if(trim_start));
+                self.write_micros(children, false);
+                self.write_escaped(localized.closing(*is_inner));
+            }
+            Formatted(nodes, cmd) => {
+                self.stack_preorder(&[*cmd][..]);
+                self.write_micros(nodes, trim_start);
+                self.stack_postorder(&[*cmd][..]);
+            }
+            NoCase(inners) => {
+                self.write_micros(inners, trim_start);
+            }
+            NoDecor(inners) => {
+                self.write_micros(inners, trim_start);
+            }
+        }
+    }
+
+    fn write_inline(&mut self, inline: &InlineElement, trim_start: bool) {
+        use super::InlineElement::*;
+        match inline {
+            Text(text) => {
+                self.write_escaped(text.trim_start_if(trim_start));
+            }
+            Div(display, inlines) => {
+                self.stack_formats(inlines, Formatting::default(), Some(*display));
+            }
+            Micro(micros) => {
+                self.write_micros(micros, trim_start);
+            }
+            Formatted(inlines, formatting) => {
+                self.stack_formats(inlines, *formatting, None);
+            }
+            Quoted {
+                is_inner,
+                localized,
+                inlines,
+            } => {
+                self.write_escaped(localized.opening(*is_inner).trim_start_if(trim_start));
+                self.write_inlines(inlines, false);
+                self.write_escaped(localized.closing(*is_inner));
+            }
+            Linked(link) => self.write_link(
+                r#"<ext-link ext-link-type="uri" xlink:href=""#,
+                link,
+                r#"">"#,
+                "</ext-link>",
+                self.options,
+            ),
+            // cite anchors are HTML only
+            Anchored(_, inlines) => self.write_inlines(inlines, trim_start),
+        }
+    }
+
+    fn write_url(&mut self, url: &Url, trailing_slash: bool, in_attr: bool) {
+        super::write_url(
+            self.dest,
+            url,
+            trailing_slash,
+            in_attr,
+            |b, s| write!(b, "{}", escape_xml_attribute(s)),
+            |b, s| write!(b, "{}", escape_xml(s)),
+        )
+        .unwrap()
+    }
+}
+
+impl FormatCmd {
+    /// JATS has no elements for turning formatting back off, and display modes are block-level,
+    /// so those are simply not written.
+    fn jats_tag(self) -> Option<&'static str> {
+        match self {
+            FormatCmd::FontStyleItalic | FormatCmd::FontStyleOblique => Some("italic"),
+            FormatCmd::FontStyleNormal => Some("roman"),
+            FormatCmd::FontWeightBold => Some("bold"),
+            FormatCmd::FontVariantSmallCaps => Some("sc"),
+            FormatCmd::TextDecorationUnderline => Some("underline"),
+            FormatCmd::VerticalAlignmentSuperscript => Some("sup"),
+            FormatCmd::VerticalAlignmentSubscript => Some("sub"),
+            FormatCmd::FontWeightNormal
+            | FormatCmd::FontWeightLight
+            | FormatCmd::FontVariantNormal
+            | FormatCmd::TextDecorationNone
+            | FormatCmd::VerticalAlignmentBaseline
+            | FormatCmd::DisplayBlock
+            | FormatCmd::DisplayIndent
+            | FormatCmd::DisplayLeftMargin
+            | FormatCmd::DisplayRightInline => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn escape(s: &str) -> String {
+        let mut buf = String::new();
+        write!(&mut buf, "{}", escape_xml(s)).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(
+            escape("Q&A <b> \"it's\"").as_str(),
+            "Q&amp;A &lt;b&gt; \"it's\""
+        );
+    }
+
+    #[test]
+    fn test_escape_xml_attribute() {
+        let mut buf = String::new();
+        write!(&mut buf, "{}", escape_xml_attribute(r#"a&b="c'd"<"#)).unwrap();
+        assert_eq!(buf.as_str(), "a&amp;b=&quot;c&apos;d&quot;&lt;");
+    }
+}
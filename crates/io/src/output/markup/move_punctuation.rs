@@ -229,6 +229,7 @@ pub fn normalise_text_elements(slice: &mut Vec<InlineElement>) {
         match inl {
             InlineElement::Quoted { inlines, .. }
             | InlineElement::Div(_, inlines)
+            | InlineElement::Anchored(_, inlines)
             | InlineElement::Formatted(inlines, _) => normalise_text_elements(inlines),
             InlineElement::Micro(micros) => normalise_text_elements_micro(micros),
             _ => {}
@@ -310,6 +311,7 @@ pub fn move_punctuation(slice: &mut Vec<InlineElement>, punctuation_in_quote: Op
         match inl {
             InlineElement::Quoted { inlines, .. }
             | InlineElement::Div(_, inlines)
+            | InlineElement::Anchored(_, inlines)
             | InlineElement::Formatted(inlines, _) => {
                 move_punctuation(inlines, punctuation_in_quote)
             }
@@ -636,7 +638,9 @@ fn find_right_quote_inside<'a>(
         InlineElement::Micro(micros) => micros
             .last_mut()
             .and_then(move |x| find_right_quote_inside_micro(x, next)),
-        InlineElement::Div(_, inlines) | InlineElement::Formatted(inlines, _) => inlines
+        InlineElement::Div(_, inlines)
+        | InlineElement::Anchored(_, inlines)
+        | InlineElement::Formatted(inlines, _) => inlines
             .last_mut()
             .and_then(move |x| find_right_quote_inside(x, next)),
         _ => None,
@@ -752,6 +756,7 @@ fn last_string(is: &mut [InlineElement]) -> Option<&mut String> {
         InlineElement::Micro(micros) => last_string_micro(micros),
         InlineElement::Quoted { inlines, .. }
         | InlineElement::Div(_, inlines)
+        | InlineElement::Anchored(_, inlines)
         | InlineElement::Formatted(inlines, _) => last_string(inlines),
         InlineElement::Text(string) => Some(string),
         _ => None,
@@ -926,6 +931,12 @@ static FULL_MONTY_PLAIN: phf::Map<&'static [u8], Option<&'static str>> = phf_map
 
 use unic_segment::Words;
 
+/// Whether `txt` ends with a full stop, question mark or exclamation mark, ignoring trailing
+/// whitespace.
+fn ends_sentence(txt: &str) -> bool {
+    txt.trim_end().ends_with(&['.', '?', '!'][..])
+}
+
 pub fn ends_with_full_stop(els: &[InlineElement], top: bool) -> bool {
     let last = match els.last() {
         Some(l) => l,
@@ -938,12 +949,12 @@ pub fn ends_with_full_stop(els: &[InlineElement], top: bool) -> bool {
             let mut words = Words::new(txt, |s| s.chars().any(|c| c.is_alphanumeric()));
             words.next();
             let is_single_word = words.next().is_none();
-            (txt.is_empty() || txt.trim_end().ends_with(".")) && !is_single_word
-        }
-        InlineElement::Text(txt) => txt.trim_end().ends_with("."),
-        InlineElement::Formatted(inlines, _) | InlineElement::Quoted { inlines, .. } => {
-            ends_with_full_stop(inlines, false)
+            (txt.is_empty() || ends_sentence(txt)) && !is_single_word
         }
+        InlineElement::Text(txt) => ends_sentence(txt),
+        InlineElement::Formatted(inlines, _)
+        | InlineElement::Quoted { inlines, .. }
+        | InlineElement::Anchored(_, inlines) => ends_with_full_stop(inlines, false),
         InlineElement::Div(..) | InlineElement::Linked(_) => true,
 
         InlineElement::Micro(micros) => {
@@ -961,9 +972,9 @@ pub fn ends_with_full_stop(els: &[InlineElement], top: bool) -> bool {
                         let mut words = Words::new(txt, |s| s.chars().any(|c| c.is_alphanumeric()));
                         words.next();
                         let is_single_word = words.next().is_none();
-                        (txt.is_empty() || txt.trim_end().ends_with(".")) && !is_single_word
+                        (txt.is_empty() || ends_sentence(txt)) && !is_single_word
                     }
-                    MicroNode::Text(txt) => txt.trim_end().ends_with("."),
+                    MicroNode::Text(txt) => ends_sentence(txt),
                     MicroNode::Formatted(children, _)
                     | MicroNode::NoDecor(children)
                     | MicroNode::NoCase(children)
@@ -105,6 +105,8 @@ impl<'a> MarkupWriter for PlainWriter<'a> {
             Linked(link) => {
                 self.write_link("", link, "", "", self.options);
             }
+            // cite anchors are HTML only
+            Anchored(_, inlines) => self.write_inlines(inlines, trim_start),
         }
     }
     fn write_link(&mut self, _: &str, link: &Link, _: &str, _: &str, _: FormatOptions) {
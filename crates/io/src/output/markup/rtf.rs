@@ -127,6 +127,8 @@ impl<'a> MarkupWriter for RtfWriter<'a> {
                     self.options,
                 );
             }
+            // cite anchors are HTML only
+            Anchored(_, inlines) => self.write_inlines(inlines, trim_start),
         }
     }
 }
@@ -10,7 +10,8 @@ pub enum MicroNode {
     Formatted(Vec<MicroNode>, FormatCmd),
 
     Quoted {
-        /// Holds false until flip_flop_nodes gives it a meaningful value
+        /// Holds false until flip_flop_nodes gives it a meaningful value, which is true when
+        /// these quotes are nested inside an odd number of other quotes.
         is_inner: bool,
         localized: LocalizedQuotes,
         children: Vec<MicroNode>,
@@ -169,6 +170,56 @@ fn test_sanitize() {
     );
 }
 
+#[cfg(feature = "markup")]
+#[cfg(test)]
+mod test_nested_quotes {
+    use crate::output::markup::Markup;
+    use crate::output::{LocalizedQuotes, OutputFormat};
+    use crate::IngestOptions;
+    use csl::Atom;
+
+    fn german_quotes() -> LocalizedQuotes {
+        LocalizedQuotes {
+            outer: (Atom::from("\u{201E}"), Atom::from("\u{201C}")),
+            inner: (Atom::from("\u{201A}"), Atom::from("\u{2018}")),
+            punctuation_in_quote: false,
+        }
+    }
+
+    fn render(input: &str, quoted_field: bool) -> String {
+        let fmt = Markup::plain();
+        let quotes = german_quotes();
+        let options = IngestOptions::default_with_quotes(quotes.clone());
+        let mut build = fmt.ingest(input, &options);
+        if quoted_field {
+            build = fmt.quoted(build, quotes);
+        }
+        fmt.output(build, false).to_string()
+    }
+
+    #[test]
+    fn one_level() {
+        assert_eq!(render("a 'b' c", false), "a \u{201E}b\u{201C} c");
+    }
+
+    #[test]
+    fn two_levels() {
+        assert_eq!(
+            render(r#""a 'b' c""#, false),
+            "\u{201E}a \u{201A}b\u{2018} c\u{201C}"
+        );
+    }
+
+    #[test]
+    fn two_levels_inside_quoted_field() {
+        // The field itself is the outer level, so the quotes inside alternate from there.
+        assert_eq!(
+            render(r#"x "a 'b' c" y"#, true),
+            "\u{201E}x \u{201A}a \u{201E}b\u{201C} c\u{2018} y\u{201C}"
+        );
+    }
+}
+
 // The following is based on the MIT-licensed html_sanitizer crate,
 // and adjusted to work on *inline* HTML, not entire documents.
 //
@@ -36,16 +36,16 @@ pub struct LocalizedQuotes {
 impl LocalizedQuotes {
     pub fn closing(&self, is_inner: bool) -> &str {
         if is_inner {
-            self.outer.1.as_ref()
-        } else {
             self.inner.1.as_ref()
+        } else {
+            self.outer.1.as_ref()
         }
     }
     pub fn opening(&self, is_inner: bool) -> &str {
         if is_inner {
-            self.outer.0.as_ref()
-        } else {
             self.inner.0.as_ref()
+        } else {
+            self.outer.0.as_ref()
         }
     }
 
@@ -20,6 +20,9 @@ use csl::{Atom, CslType, DateVariable, Lang, NameVariable, NumberVariable, Varia
 pub struct Reference {
     pub id: Atom,
     pub csl_type: CslType,
+    /// The `type` from CSL-JSON, if it was not one citeproc-rs knows. `csl_type` is then a
+    /// placeholder, which the processor replaces with its configured fallback type.
+    pub unknown_type: Option<String>,
     pub language: Option<Lang>,
 
     // each field type gets its own hashmap, as its data type is different
@@ -30,6 +33,9 @@ pub struct Reference {
     pub number: FnvHashMap<NumberVariable, NumberLike>,
     pub name: FnvHashMap<NameVariable, Vec<Name>>,
     pub date: FnvHashMap<DateVariable, DateOrRange>,
+    /// The CSL-JSON `custom` object, for arbitrary data a consumer wants to keep alongside the
+    /// reference. It is never rendered, only written back out when serializing.
+    pub custom: Option<serde_json::Value>,
 }
 
 impl Reference {
@@ -37,11 +43,171 @@ impl Reference {
         Reference {
             id,
             csl_type,
+            unknown_type: None,
             language: None,
             ordinary: FnvHashMap::default(),
             number: FnvHashMap::default(),
             name: FnvHashMap::default(),
             date: FnvHashMap::default(),
+            custom: None,
         }
     }
 }
+
+/// Builds a [Reference] from typed values, for Rust consumers that would otherwise have to write
+/// CSL-JSON and deserialize it.
+///
+/// ```
+/// use citeproc_io::{DateOrRange, Name, PersonName, ReferenceBuilder};
+/// use csl::CslType;
+///
+/// let refr = ReferenceBuilder::new()
+///     .id("doe2000")
+///     .ty(CslType::Book)
+///     .title("A Book")
+///     .author(vec![Name::Person(PersonName {
+///         family: Some("Doe".into()),
+///         given: Some("John".into()),
+///         is_latin_cyrillic: true,
+///         ..Default::default()
+///     })])
+///     .issued(DateOrRange::new(2000, 0, 0))
+///     .build()
+///     .unwrap();
+/// assert_eq!(&*refr.id, "doe2000");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceBuilder {
+    id: Option<Atom>,
+    csl_type: Option<CslType>,
+    language: Option<Lang>,
+    ordinary: FnvHashMap<Variable, String>,
+    number: FnvHashMap<NumberVariable, NumberLike>,
+    name: FnvHashMap<NameVariable, Vec<Name>>,
+    date: FnvHashMap<DateVariable, DateOrRange>,
+    custom: Option<serde_json::Value>,
+}
+
+impl ReferenceBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Required.
+    pub fn id(mut self, id: impl Into<Atom>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Defaults to `article`, as for CSL-JSON with no `type`.
+    pub fn ty(mut self, csl_type: CslType) -> Self {
+        self.csl_type = Some(csl_type);
+        self
+    }
+
+    pub fn language(mut self, language: Lang) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    pub fn ordinary(mut self, var: Variable, value: impl Into<String>) -> Self {
+        self.ordinary.insert(var, value.into());
+        self
+    }
+
+    pub fn number(mut self, var: NumberVariable, value: NumberLike) -> Self {
+        self.number.insert(var, value);
+        self
+    }
+
+    pub fn name(mut self, var: NameVariable, names: Vec<Name>) -> Self {
+        self.name.insert(var, names);
+        self
+    }
+
+    pub fn date(mut self, var: DateVariable, value: DateOrRange) -> Self {
+        self.date.insert(var, value);
+        self
+    }
+
+    pub fn custom(mut self, custom: serde_json::Value) -> Self {
+        self.custom = Some(custom);
+        self
+    }
+
+    pub fn title(self, title: impl Into<String>) -> Self {
+        self.ordinary(Variable::Title, title)
+    }
+
+    pub fn author(self, names: Vec<Name>) -> Self {
+        self.name(NameVariable::Author, names)
+    }
+
+    pub fn editor(self, names: Vec<Name>) -> Self {
+        self.name(NameVariable::Editor, names)
+    }
+
+    pub fn issued(self, value: DateOrRange) -> Self {
+        self.date(DateVariable::Issued, value)
+    }
+
+    pub fn build(self) -> Result<Reference, ReferenceBuilderError> {
+        let ReferenceBuilder {
+            id,
+            csl_type,
+            language,
+            ordinary,
+            number,
+            name,
+            date,
+            custom,
+        } = self;
+        let id = id
+            .filter(|id| !id.is_empty())
+            .ok_or(ReferenceBuilderError::MissingId)?;
+        Ok(Reference {
+            id,
+            csl_type: csl_type.unwrap_or(CslType::Article),
+            unknown_type: None,
+            language,
+            ordinary,
+            number,
+            name,
+            date,
+            custom,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceBuilderError {
+    /// No `id` was supplied, or it was empty.
+    MissingId,
+}
+
+impl std::error::Error for ReferenceBuilderError {}
+impl std::fmt::Display for ReferenceBuilderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferenceBuilderError::MissingId => f.write_str("reference has no id"),
+        }
+    }
+}
+
+#[test]
+fn builder_requires_id() {
+    let builder = ReferenceBuilder::new().ty(CslType::Book).title("A Book");
+    assert_eq!(
+        builder.clone().build(),
+        Err(ReferenceBuilderError::MissingId)
+    );
+    assert_eq!(
+        builder.clone().id("").build(),
+        Err(ReferenceBuilderError::MissingId)
+    );
+    let mut expected = Reference::empty(Atom::from("book"), CslType::Book);
+    expected
+        .ordinary
+        .insert(Variable::Title, String::from("A Book"));
+    assert_eq!(builder.id("book").build(), Ok(expected));
+}
@@ -295,17 +295,50 @@ fn title_case_word<'a>(
     )
 }
 
-fn transform_title_case(s: &str, seen_one: bool, is_last: bool) -> String {
+/// Whether a word is all capitals, like "NASA" or "DNA", and so probably an initialism whose case
+/// should be left alone. Single letters don't count.
+fn is_initialism(word: &str) -> bool {
+    let mut letters = word.chars().filter(|c| c.is_alphabetic());
+    letters.clone().nth(1).is_some() && letters.all(char::is_uppercase)
+}
+
+fn transform_title_case(
+    s: &str,
+    seen_one: bool,
+    is_last: bool,
+    entire_is_uppercase: bool,
+) -> String {
     transform_each_word(
         &s,
         seen_one,
         is_last,
         |word, word_and_rest, _is_first, no_stop| {
+            // If everything is in capitals, we can't tell initialisms from shouting.
+            if !entire_is_uppercase && is_initialism(word) {
+                return (SmartCow::Borrowed(word), None);
+            }
             title_case_word(word, word_and_rest, false, no_stop)
         },
     )
 }
 
+#[test]
+fn title_case_initialisms() {
+    assert_eq!(
+        transform_title_case("the DNA of NASA", false, true, false),
+        "The DNA of NASA"
+    );
+    // all-caps stopwords are initialisms too
+    assert_eq!(
+        transform_title_case("OR gates and AND gates", false, true, false),
+        "OR Gates and AND Gates"
+    );
+    assert_eq!(
+        transform_title_case("THE DNA OF NASA", false, true, true),
+        "THE DNA of NASA"
+    );
+}
+
 fn transform_each_word<'a, F>(mut s: &'a str, seen_one: bool, is_last: bool, transform: F) -> String
 where
     F: Fn(&'a str, &'a str, bool, bool) -> (SmartCow<'a>, Option<usize>),
@@ -418,7 +451,8 @@ impl IngestOptions {
                 InlineElement::Quoted {
                     inlines: content, ..
                 }
-                | InlineElement::Div(_, content) => {
+                | InlineElement::Div(_, content)
+                | InlineElement::Anchored(_, content) => {
                     seen_one = self.apply_text_case_inner(content.as_mut(), seen_one, is_uppercase)
                         || seen_one;
                 }
@@ -509,7 +543,9 @@ impl IngestOptions {
                 transform_sentence_case(s, seen_one, is_last, entire_is_uppercase)
             }
             // Fallback is nothing
-            TextCase::Title if self.is_english => transform_title_case(&s, seen_one, is_last),
+            TextCase::Title if self.is_english => {
+                transform_title_case(&s, seen_one, is_last, entire_is_uppercase)
+            }
             TextCase::CapitalizeAll => {
                 transform_each_word(&s, seen_one, is_last, |word, _, _, _| {
                     (transform_uppercase_first(word), None)
@@ -536,6 +572,7 @@ fn any_inlines<F: Fn(&str) -> bool + Copy>(f: F, invert: bool, inlines: &[Inline
         InlineElement::Micro(micros) => any_micros(f, invert, micros.as_ref()),
         InlineElement::Quoted { inlines, .. }
         | InlineElement::Div(_, inlines)
+        | InlineElement::Anchored(_, inlines)
         | InlineElement::Formatted(inlines, _) => any_inlines(f, invert, inlines.as_ref()) ^ invert,
         InlineElement::Linked(_) => false,
     }) ^ invert
@@ -17,6 +17,38 @@ pub fn to_bijective_base_26(int: u32) -> String {
     s
 }
 
+/// Like [`to_bijective_base_26`], but counts using the given alphabet, so with `['x', 'y']`, 1
+/// is `x`, 2 is `y`, 3 is `xx`. An empty alphabet produces an empty string.
+pub fn to_bijective_base(int: u32, alphabet: &[char]) -> String {
+    let base = alphabet.len() as u32;
+    let mut s = String::new();
+    if base == 0 {
+        return s;
+    }
+    let mut n = int;
+    while n > 0 {
+        n -= 1;
+        s.insert(0, alphabet[(n % base) as usize]);
+        n /= base;
+    }
+    s
+}
+
+#[test]
+fn test_bijective_base() {
+    assert_eq!(to_bijective_base_26(26), "z");
+    assert_eq!(to_bijective_base_26(27), "aa");
+    assert_eq!(to_bijective_base_26(28), "ab");
+    assert_eq!(to_bijective_base_26(703), "aaa");
+    let greek = ['α', 'β', 'γ'];
+    assert_eq!(to_bijective_base(1, &greek), "α");
+    assert_eq!(to_bijective_base(3, &greek), "γ");
+    assert_eq!(to_bijective_base(4, &greek), "αα");
+    assert_eq!(to_bijective_base(12, &greek), "γγ");
+    assert_eq!(to_bijective_base(13, &greek), "ααα");
+    assert_eq!(to_bijective_base(1, &[]), "");
+}
+
 pub trait JoinMany<T> {
     fn join_many(&self, sep: &[T]) -> Vec<T>;
 }
@@ -19,6 +19,7 @@ mod var {
 }
 
 use citeproc_io::*;
+use csl::CslType;
 use var::*;
 
 static INIT: Once = Once::new();
@@ -201,6 +202,54 @@ test_parse!(
 
 // From the spec
 
+#[test]
+fn serialize_round_trip() {
+    setup();
+    let input = r#"{
+        "id": "ref",
+        "type": "article-journal",
+        "language": "fr-FR",
+        "title": "Title",
+        "volume": 5,
+        "page": "33-44",
+        "author": [{ "family": "Doe", "given": "John" }, { "literal": "ACME Corp" }],
+        "issued": { "date-parts": [[2000, 5], [2001, 6, 7]], "circa": true },
+        "accessed": { "date-parts": [[2010, 13]] },
+        "original-date": { "literal": "Spring, long ago" }
+    }"#;
+    let refr: Reference = serde_json::from_str(input).unwrap();
+    let json = serde_json::to_string(&refr).unwrap();
+    let again: Reference = serde_json::from_str(&json).unwrap();
+    assert_eq!(refr, again);
+}
+
+#[test]
+fn unknown_type() {
+    setup();
+    let input = r#"{ "id": "ref", "type": "software", "title": "A program" }"#;
+    let refr: Reference = serde_json::from_str(input).unwrap();
+    assert_eq!(refr.csl_type, CslType::Article);
+    assert_eq!(refr.unknown_type.as_deref(), Some("software"));
+    // the original type is written back out
+    let json = serde_json::to_string(&refr).unwrap();
+    let again: Reference = serde_json::from_str(&json).unwrap();
+    assert_eq!(refr, again);
+}
+
+#[test]
+fn custom_object() {
+    setup();
+    let input = r#"{ "id": "ref", "title": "A", "custom": { "shelf": [3, "B"], "read": true } }"#;
+    let refr: Reference = serde_json::from_str(input).unwrap();
+    assert_eq!(
+        refr.custom,
+        Some(json!({ "shelf": [3, "B"], "read": true }))
+    );
+    let json = serde_json::to_string(&refr).unwrap();
+    let again: Reference = serde_json::from_str(&json).unwrap();
+    assert_eq!(refr, again);
+}
+
 fn parse_errors<'a, T: FromStr>(varnames: &[&'a str]) -> Vec<&'a str> {
     varnames
         .iter()
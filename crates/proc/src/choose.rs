@@ -8,8 +8,11 @@ use crate::prelude::*;
 
 use crate::ir::ConditionalDisambIR;
 use citeproc_io::DateOrRange;
-use csl::{AnyVariable, DateVariable};
-use csl::{Choose, Cond, CondSet, Conditions, CslType, Element, Else, IfThen, Match, Position};
+use csl::{AnyVariable, DateVariable, NameVariable};
+use csl::{
+    Choose, Cond, CondSet, Conditions, Context, CslType, Element, Else, IfThen, Match, Position,
+};
+use fnv::FnvHashMap;
 use std::sync::Arc;
 
 impl<'c, O, I> Proc<'c, O, I> for Arc<Choose>
@@ -166,7 +169,12 @@ where
     I: OutputFormat,
 {
     let IfThen(ref conditions, ref elements) = *branch;
-    let (matched, disambiguate) = eval_conditions(conditions, ctx, /* phony, not used */ 0);
+    let (matched, disambiguate) = eval_conditions_cached(
+        conditions,
+        ctx,
+        /* phony, not used */ 0,
+        &mut state.cond_cache,
+    );
     let content = if matched {
         Some(sequence(
             db,
@@ -230,13 +238,44 @@ pub fn eval_conditions<'c, Ck>(
     checker: &Ck,
     current_count: u32,
 ) -> (bool, bool)
+where
+    Ck: CondChecker,
+{
+    eval_conditions_inner(conditions, checker, current_count, None)
+}
+
+/// Results of evaluating single conditions for one cite, so that e.g. `is-numeric` is not
+/// recomputed for every `<choose>` in the style. Lives in [IrState], so it is scoped to rendering
+/// one cite.
+pub type CondCache = FnvHashMap<Cond, bool>;
+
+/// Like [eval_conditions], but memoizes each condition in `cache`. Only for checkers whose answers
+/// do not change for the lifetime of the cache, apart from `disambiguate`, which is never cached.
+pub fn eval_conditions_cached<'c, Ck>(
+    conditions: &'c Conditions,
+    checker: &Ck,
+    current_count: u32,
+    cache: &mut CondCache,
+) -> (bool, bool)
+where
+    Ck: CondChecker,
+{
+    eval_conditions_inner(conditions, checker, current_count, Some(cache))
+}
+
+fn eval_conditions_inner<'c, Ck>(
+    conditions: &'c Conditions,
+    checker: &Ck,
+    current_count: u32,
+    mut cache: Option<&mut CondCache>,
+) -> (bool, bool)
 where
     Ck: CondChecker,
 {
     let Conditions(ref match_type, ref conditions) = *conditions;
     let mut tests = conditions
         .iter()
-        .map(|c| eval_condset(c, checker, current_count));
+        .map(|c| eval_condset(c, checker, current_count, cache.as_deref_mut()));
     let disambiguate = conditions.iter().any(|c| {
         c.conds.contains(&Cond::Disambiguate(true)) || c.conds.contains(&Cond::Disambiguate(false))
     });
@@ -244,44 +283,71 @@ where
     (run_matcher(&mut tests, match_type), disambiguate)
 }
 
-fn eval_condset<'c, Ck>(cond_set: &'c CondSet, checker: &Ck, current_count: u32) -> bool
+fn eval_condset<'c, Ck>(
+    cond_set: &'c CondSet,
+    checker: &Ck,
+    current_count: u32,
+    mut cache: Option<&mut CondCache>,
+) -> bool
 where
     Ck: CondChecker,
 {
-    let features = checker.features();
+    let mut iter_all = cond_set
+        .conds
+        .iter()
+        .filter_map(|cond| match cache.as_deref_mut() {
+            // disambiguate="true" depends on the current_count and disambiguation pass
+            Some(cache) if !matches!(cond, Cond::Disambiguate(_)) => {
+                if let Some(&result) = cache.get(cond) {
+                    return Some(result);
+                }
+                let result = eval_cond(cond, checker, current_count)?;
+                cache.insert(cond.clone(), result);
+                Some(result)
+            }
+            _ => eval_cond(cond, checker, current_count),
+        });
 
-    let mut iter_all = cond_set.conds.iter().filter_map(|cond| {
-        Some(match cond {
-            Cond::Variable(var) => checker.has_variable(*var),
-            Cond::IsNumeric(var) => checker.is_numeric(*var),
-            Cond::Disambiguate(d) => *d == checker.is_disambiguate(current_count),
-            Cond::Type(typ) => checker.csl_type() == *typ,
-            // None in a bibliography
-            Cond::Position(pos) => checker.position().map_or(false, |p| p.matches(*pos)),
-            Cond::Locator(typ) => checker.locator_type() == Some(*typ),
-            Cond::IsUncertainDate(dvar) => checker.is_uncertain_date(*dvar),
+    run_matcher(&mut iter_all, &cond_set.match_type)
+}
 
-            Cond::HasYearOnly(_) | Cond::HasMonthOrSeason(_) | Cond::HasDay(_)
-                if !features.condition_date_parts =>
-            {
-                return None;
-            }
-            Cond::HasYearOnly(dvar) => checker.has_year_only(*dvar),
-            Cond::HasMonthOrSeason(dvar) => checker.has_month_or_season(*dvar),
-            Cond::HasDay(dvar) => checker.has_day(*dvar),
+/// None if the condition should be ignored.
+fn eval_cond<Ck>(cond: &Cond, checker: &Ck, current_count: u32) -> Option<bool>
+where
+    Ck: CondChecker,
+{
+    let features = checker.features();
+    Some(match cond {
+        Cond::Variable(var) => checker.has_variable(*var),
+        Cond::IsNumeric(var) => checker.is_numeric(*var),
+        Cond::Disambiguate(d) => *d == checker.is_disambiguate(current_count),
+        Cond::Type(typ) => checker.csl_type() == *typ,
+        // None in a bibliography
+        Cond::Position(pos) => checker.position().map_or(false, |p| p.matches(*pos)),
+        Cond::Locator(typ) => checker.locator_type() == Some(*typ),
+        Cond::IsUncertainDate(dvar) => checker.is_uncertain_date(*dvar),
+        Cond::IsPlural(nvar) => checker.is_plural(*nvar),
 
-            // Not implemented
-            Cond::Context(_)
-            | Cond::IsPlural(_)
-            | Cond::Jurisdiction(_)
-            | Cond::SubJurisdiction(_) => {
-                log::warn!("unimplemented choose condition: {:?}", cond);
-                return None;
-            }
-        })
-    });
+        Cond::HasYearOnly(_) | Cond::HasMonthOrSeason(_) | Cond::HasDay(_)
+            if !features.condition_date_parts =>
+        {
+            return None;
+        }
+        Cond::HasYearOnly(dvar) => checker.has_year_only(*dvar),
+        Cond::HasMonthOrSeason(dvar) => checker.has_month_or_season(*dvar),
+        Cond::HasDay(dvar) => checker.has_day(*dvar),
+        Cond::Context(context) => match (context, checker.cite_or_bib()) {
+            (Context::Citation, CiteOrBib::Citation) => true,
+            (Context::Bibliography, CiteOrBib::Bibliography) => true,
+            _ => false,
+        },
 
-    run_matcher(&mut iter_all, &cond_set.match_type)
+        // Not implemented
+        Cond::Jurisdiction(_) | Cond::SubJurisdiction(_) => {
+            log::warn!("unimplemented choose condition: {:?}", cond);
+            return None;
+        }
+    })
 }
 
 use csl::Features;
@@ -310,6 +376,12 @@ impl CondChecker for UselessCondChecker {
     fn position(&self) -> Option<Position> {
         None
     }
+    fn is_plural(&self, _var: NameVariable) -> bool {
+        false
+    }
+    fn cite_or_bib(&self) -> CiteOrBib {
+        CiteOrBib::Citation
+    }
     fn features(&self) -> &csl::version::Features {
         lazy_static::lazy_static! {
             static ref NO_FEATURES: Features = {
@@ -332,6 +404,11 @@ pub trait CondChecker {
     fn locator_type(&self) -> Option<LocatorType>;
     fn get_date(&self, dvar: DateVariable) -> Option<&DateOrRange>;
     fn position(&self) -> Option<Position>;
+    /// CSL-M. True when the name variable holds more than one name. An institution counts as one
+    /// name.
+    fn is_plural(&self, var: NameVariable) -> bool;
+    /// CSL-M. Whether this is rendering a cite or a bibliography entry, for `context="..."`.
+    fn cite_or_bib(&self) -> CiteOrBib;
     fn features(&self) -> &Features;
     fn has_year_only(&self, dvar: DateVariable) -> bool {
         self.get_date(dvar)
@@ -129,10 +129,21 @@ impl<'a, O: OutputFormat, I: OutputFormat> RenderContext for CiteContext<'a, O,
                 .map(NumericValue::from_localized(and_term)),
             NumberVariable::FirstReferenceNoteNumber => self.position.1.map(NumericValue::num),
             NumberVariable::CitationNumber => self.bib_number.map(NumericValue::num),
-            NumberVariable::PageFirst => get(NumberVariable::Page).and_then(|pp| pp.page_first()),
+            // An explicit page-first wins; otherwise it is the first number in page.
+            NumberVariable::PageFirst => get(NumberVariable::PageFirst)
+                .or_else(|| get(NumberVariable::Page).and_then(|pp| pp.page_first())),
             _ => get(var),
         }
     }
+
+    fn locator_extra(&self) -> Option<&str> {
+        // Accompanies the locator, and is dropped without one, which is what the reference side
+        // of disambiguation assumes.
+        self.cite
+            .locator_extra
+            .as_deref()
+            .filter(|_| self.locator_type().is_some())
+    }
 }
 
 pub trait RenderContext {
@@ -146,6 +157,11 @@ pub trait RenderContext {
         refr.language.as_ref()
     }
 
+    /// CSL-M's `locator-extra`, which only a cite can supply.
+    fn locator_extra(&self) -> Option<&str> {
+        None
+    }
+
     /// Common functionality between CiteContext and RefContext.
     fn get_ordinary(&self, var: Variable, form: VariableForm) -> Option<Cow<'_, str>> {
         let refr = self.reference();
@@ -160,9 +176,10 @@ pub trait RenderContext {
             (Variable::ContainerTitle, VariableForm::Short) => get(Variable::ContainerTitleShort)
                 .or_else(|| get(Variable::JournalAbbreviation))
                 .or_else(|| get(Variable::ContainerTitle)),
+            (Variable::LocatorExtra, _) => self.locator_extra().map(Cow::Borrowed),
             (Variable::CitationLabel, _) if refr.ordinary.get(&var).is_none() => {
-                let tri = crate::citation_label::Trigraph::default();
-                Some(Cow::Owned(tri.make_label(self.reference())))
+                let label = crate::citation_label::make_label(self.style(), self.reference());
+                Some(Cow::Owned(label))
             }
             _ => get(var),
         }
@@ -207,6 +224,10 @@ where
             },
             // Generated on demand
             AnyVariable::Ordinary(Variable::CitationLabel) => true,
+            AnyVariable::Ordinary(Variable::LocatorExtra) => self.locator_extra().is_some(),
+            AnyVariable::Date(DateVariable::LocatorDate) => {
+                self.get_date(DateVariable::LocatorDate).is_some()
+            }
             _ => ref_has_variable(self.reference, var),
         }
     }
@@ -242,7 +263,18 @@ where
             .and_then(|l| l.single().map(|l| l.type_of()))
     }
     fn get_date(&self, dvar: DateVariable) -> Option<&DateOrRange> {
-        self.reference.date.get(&dvar)
+        match dvar {
+            // Like locator-extra, only with a locator
+            DateVariable::LocatorDate => self
+                .cite
+                .locator_date
+                .as_ref()
+                .filter(|_| self.locator_type().is_some()),
+            _ => self.reference.date.get(&dvar),
+        }
+    }
+    fn is_plural(&self, var: NameVariable) -> bool {
+        self.get_name(var).map_or(false, |names| names.len() > 1)
     }
     fn position(&self) -> Option<Position> {
         if self.in_bibliography {
@@ -250,6 +282,13 @@ where
         }
         Some(self.position.0)
     }
+    fn cite_or_bib(&self) -> CiteOrBib {
+        if self.in_bibliography {
+            CiteOrBib::Bibliography
+        } else {
+            CiteOrBib::Citation
+        }
+    }
     fn is_disambiguate(&self, _current_count: u32) -> bool {
         // ignore count as that's for references
         self.disamb_pass == Some(DisambPass::Conditionals)
@@ -31,19 +31,25 @@ pub fn built_cluster_before_output(
     } else {
         return fmt.plain("");
     };
+    let build = render_cluster(db, cluster_id, &cite_ids, fmt);
+    match db.lookup_cluster_id(cluster_id) {
+        Some(label) => {
+            let cites: Vec<_> = cite_ids.iter().map(|id| id.lookup(db)).collect();
+            let ref_ids: Vec<&str> = cites.iter().map(|cite| &*cite.ref_id).collect();
+            fmt.cluster_data(&label, &ref_ids, build)
+        }
+        None => build,
+    }
+}
+
+fn render_cluster(
+    db: &dyn IrDatabase,
+    cluster_id: ClusterId,
+    cite_ids: &[CiteId],
+    fmt: &Markup,
+) -> MarkupBuild {
     let style = db.style();
-    let sorted_refs_arc = db.sorted_refs();
-    let mut irs: Vec<_> = cite_ids
-        .iter()
-        .map(|&id| {
-            let gen4 = db.ir_fully_disambiguated(id);
-            let position = db.cite_position(id).0;
-            let cite = id.lookup(db);
-            let (_keys, citation_numbers_by_id) = &*sorted_refs_arc;
-            let cnum = citation_numbers_by_id.get(&cite.ref_id).cloned();
-            CiteInCluster::new(id, cite, position, cnum.map(|x| x.get()), gen4, &fmt)
-        })
-        .collect();
+    let mut irs = cites_in_cluster(db, cite_ids, fmt);
 
     if let Some(maybe_collapse) = style.citation.group_collapsing() {
         group_by_name(&fmt, maybe_collapse, &mut irs);
@@ -68,11 +74,15 @@ pub fn built_cluster_before_output(
         collapse_cites(&fmt, collapse, &mut irs);
     }
 
+    group_parallels(db, &mut irs);
+
     // Cite capitalization
     // TODO: allow clients to pass a flag to prevent this (on ix==0) when a cluster is in the
     // middle of an existing footnote, and isn't preceded by a period (or however else a client
     // wants to judge that).
-    // We capitalize all cites whose prefixes end with full stops.
+    // We capitalize the first cite if it has no prefix, and any cite whose prefix ends a sentence
+    // (with a full stop, question mark or exclamation mark), like citeproc-js. A mid-sentence
+    // prefix like "see" leaves the cite alone.
     if style.class != csl::StyleClass::InText {
         for (ix, cite) in irs.iter_mut().enumerate() {
             if cite
@@ -127,19 +137,23 @@ pub fn built_cluster_before_output(
                         // this is something @fbennett made up specifically for author-only / clusters.
                         .flatten(fmt, None)
                 })
+                .map(|flat| fmt.cite_anchor(&cite.cite.ref_id, flat))
                 .unwrap_or_else(|| fmt.plain(CLUSTER_NO_PRINTED_FORM))
         });
 
     intext_stream.write_interspersed(intext_authors, DelimKind::Layout);
 
-    for cite in &irs {
-        match cite.destination {
-            WhichStream::Nowhere | WhichStream::MainToIntext { .. } => {
-                continue;
-            }
-            _ => {
-                citation_stream.write_flat(cite, None);
-            }
+    for run in group_by(&irs, |a, _b| a.parallel_delimiter.is_some()) {
+        match run {
+            [cite] => match cite.destination {
+                WhichStream::Nowhere | WhichStream::MainToIntext { .. } => {
+                    continue;
+                }
+                _ => {
+                    citation_stream.write_flat(cite, None);
+                }
+            },
+            _ => citation_stream.write_parallel(run),
         }
     }
 
@@ -167,6 +181,101 @@ pub fn built_cluster_before_output(
     fmt.seq(seq)
 }
 
+fn cites_in_cluster(
+    db: &dyn IrDatabase,
+    cite_ids: &[CiteId],
+    fmt: &Markup,
+) -> Vec<CiteInCluster<Markup>> {
+    let sorted_refs_arc = db.sorted_refs();
+    let (_keys, citation_numbers_by_id) = &*sorted_refs_arc;
+    cite_ids
+        .iter()
+        .map(|&id| {
+            let gen4 = db.ir_fully_disambiguated(id);
+            let position = db.cite_position(id).0;
+            let cite = id.lookup(db);
+            let cnum = citation_numbers_by_id.get(&cite.ref_id).cloned();
+            CiteInCluster::new(id, cite, position, cnum.map(|x| x.get()), gen4, &fmt)
+        })
+        .collect()
+}
+
+/// Renders only the authors of a cluster's cites, as `ClusterMode::AuthorOnly` would, regardless
+/// of the cluster's actual mode. None if no cite has a names block (or a substitute for one) to
+/// render that way.
+pub fn built_cluster_author_prefix(
+    db: &dyn IrDatabase,
+    cluster_id: ClusterId,
+    fmt: &Markup,
+) -> Option<MarkupBuild> {
+    let cite_ids = db.cluster_cites_sorted(cluster_id)?;
+    let style = db.style();
+    let mut irs = cites_in_cluster(db, &cite_ids, fmt);
+    if let Some(maybe_collapse) = style.citation.group_collapsing() {
+        group_by_name(&fmt, maybe_collapse, &mut irs);
+    }
+    transforms::apply_cluster_mode(db, &ClusterMode::AuthorOnly, &mut irs, style.class, fmt);
+
+    let default_locale = db.default_locale();
+    let intext_delimiters = layout::LayoutDelimiters::from_intext(
+        style.intext.as_ref(),
+        &style.citation,
+        &default_locale,
+    );
+    let mut intext_stream = layout::LayoutStream::new(0, intext_delimiters, fmt);
+    let authors = group_by(&irs, |a, b| a.by_name() == b.by_name())
+        .map(|run| &run[0])
+        .filter(|cite| cite.destination == WhichStream::MainToIntext { success: true })
+        .filter_map(|cite| cite.gen4.tree_ref().flatten(fmt, None));
+    intext_stream.write_interspersed(authors, DelimKind::Layout);
+    intext_stream.finish()
+}
+
+/// CSL-M parallel citations, e.g. one case reported in several reporters. Adjacent cites of
+/// references with the same type and title, which both render an `is-parallel` group, form a run.
+/// The first cite in a run keeps whatever it renders before its parallel group, the last keeps
+/// whatever comes after, and each is joined to the next with its parallel group's delimiter.
+fn group_parallels(db: &dyn IrDatabase, cites: &mut [CiteInCluster<Markup>]) {
+    let parallels: Vec<_> = cites
+        .iter()
+        .map(|cite| {
+            if cite.destination != WhichStream::MainToCitation {
+                return None;
+            }
+            let tree = cite.gen4.tree_ref();
+            let node = tree.find_parallel_group()?;
+            let delimiter = match &tree.arena.get(node)?.get().0 {
+                IR::Seq(seq) => seq.delimiter.clone(),
+                _ => None,
+            };
+            let refr = db.reference(cite.cite.ref_id.clone())?;
+            let title = refr.ordinary.get(&csl::Variable::Title)?;
+            Some((node, delimiter, (refr.csl_type, title.clone())))
+        })
+        .collect();
+    let joins_next: Vec<bool> = (0..cites.len())
+        .map(|ix| match (parallels.get(ix), parallels.get(ix + 1)) {
+            (Some(Some((_, _, a))), Some(Some((_, _, b)))) => {
+                a == b && !cites[ix].cite.has_suffix() && !cites[ix + 1].cite.has_prefix()
+            }
+            _ => false,
+        })
+        .collect();
+    for (ix, cite) in cites.iter_mut().enumerate() {
+        let before = ix > 0 && joins_next[ix - 1];
+        let after = joins_next[ix];
+        if let Some((node, delimiter, _)) = &parallels[ix] {
+            if before || after {
+                let gen4 = Arc::make_mut(&mut cite.gen4);
+                gen4.tree_mut().trim_around_parallel(*node, before, after);
+            }
+            if after {
+                cite.parallel_delimiter = Some(delimiter.clone().unwrap_or_else(|| ", ".into()));
+            }
+        }
+    }
+}
+
 /// A wrapper for Option where `a == b` evaluates to false if either is empty
 ///
 /// Implements PartialEq, but does not implement Eq, of course.
@@ -240,6 +349,9 @@ pub(crate) struct CiteInCluster<O: OutputFormat = Markup> {
     pub has_locator: bool,
     pub has_locator_or_affixes: bool,
     pub own_delimiter: Option<DelimKind>,
+    /// Set when the next cite is a parallel citation of the same item; the two are joined with
+    /// this instead of any of the layout delimiters.
+    pub parallel_delimiter: Option<SmartString>,
 }
 
 impl<O: OutputFormat> CiteInCluster<O> {
@@ -279,6 +391,7 @@ impl<O: OutputFormat<Output = SmartString>> Debug for CiteInCluster<O> {
             .field("has_locator", &self.has_locator)
             .field("has_locator_or_affixes", &self.has_locator_or_affixes)
             .field("own_delimiter", &self.own_delimiter)
+            .field("parallel_delimiter", &self.parallel_delimiter)
             .field("unique_name_number", &self.unique_name_number)
             .field("year_suffix", &self.year_suffix)
             .field("gen4_full", &self.gen4)
@@ -320,6 +433,7 @@ impl CiteInCluster<Markup> {
             unique_name_number: Partial::Incomparable,
             year: Partial::Incomparable,
             year_suffix: Partial::Incomparable,
+            parallel_delimiter: None,
         }
     }
 
@@ -96,6 +96,28 @@ impl<'a> LayoutStream<'a> {
         self.write_delim(override_delim_kind.or(single.own_delimiter));
     }
 
+    /// Writes a run of parallel citations as one cite, joining each to the next with its
+    /// `parallel_delimiter`.
+    pub(crate) fn write_parallel(&mut self, run: &[CiteInCluster<Markup>]) {
+        let fmt = self.fmt;
+        let mut prefix = None;
+        let mut suffix = None;
+        let mut builds = Vec::with_capacity(run.len() * 2);
+        for (ix, cite) in run.iter().enumerate() {
+            let (pre, built, suf) = flatten_with_affixes(cite, fmt);
+            if ix == 0 {
+                prefix = pre;
+            }
+            builds.push(built);
+            match &cite.parallel_delimiter {
+                Some(delim) if ix + 1 < run.len() => builds.push(fmt.plain(delim)),
+                _ => suffix = suf,
+            }
+        }
+        self.write_cite(prefix, fmt.seq(builds.into_iter()), suffix);
+        self.write_delim(run.last().and_then(|cite| cite.own_delimiter));
+    }
+
     /// Replaces an existing delimiter, which means you can write delimiters unconditionally and
     /// replace them with more appropriate ones later
     pub(crate) fn write_cite(
@@ -330,8 +352,9 @@ pub(crate) fn flatten_with_affixes(
     cite_in_cluster: &CiteInCluster<Markup>,
     fmt: &Markup,
 ) -> (Option<SmartString>, MarkupBuild, Option<SmartString>) {
-    let CiteInCluster { gen4, .. } = cite_in_cluster;
+    let CiteInCluster { gen4, cite, .. } = cite_in_cluster;
     let flattened = gen4.tree_ref().flatten_or_plain(&fmt, CSL_STYLE_ERROR);
+    let flattened = fmt.cite_anchor(&cite.ref_id, flattened);
 
     // we treat the None cases as empty strings because we would otherwise need a case
     // explosion for fmt.seq below. When they're empty they stay empty and don't allocate.
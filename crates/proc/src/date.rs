@@ -6,6 +6,7 @@
 
 use crate::prelude::*;
 
+use crate::choose::CondChecker;
 use crate::number::render_ordinal;
 use citeproc_io::{lazy, Date, DateOrRange};
 use csl::terms::*;
@@ -13,8 +14,8 @@ use csl::LocaleDate;
 #[cfg(test)]
 use csl::RangeDelimiter;
 use csl::{
-    BodyDate, DatePart, DatePartForm, DateParts, DateVariable, DayForm, IndependentDate, Locale,
-    LocalizedDate, MonthForm, NumberVariable, SortKey, YearForm,
+    BodyDate, DateForm, DatePart, DatePartForm, DateParts, DateVariable, DayForm, IndependentDate,
+    Locale, LocalizedDate, MonthForm, NumberVariable, SortKey, YearForm,
 };
 #[cfg(test)]
 use pretty_assertions::assert_eq;
@@ -34,8 +35,9 @@ impl<O: OutputFormat> Either<O> {
     fn into_cite_ir(self, var: DateVariable, arena: &mut IrArena<O>) -> NodeId {
         match self {
             Either::Build(opt) => {
-                // Get CiteEdgeData::Accessed if it's DateVariable::Accessed
-                // We guarantee below in dp_render_either that Accessed will not produce Either::Ir
+                // Get CiteEdgeData::Accessed if it's DateVariable::Accessed, and likewise
+                // CiteEdgeData::LocatorExtra for DateVariable::LocatorDate
+                // We guarantee below in dp_render_either that these will not produce Either::Ir
                 let mapper = CiteEdgeData::from_date_variable(var);
                 let content = opt.map(mapper);
                 let gv = GroupVars::rendered_if(content.is_some());
@@ -197,6 +199,12 @@ impl Disambiguation<Markup> for BodyDate {
                 ldate.variable,
             ),
         };
+        if var == DateVariable::LocatorDate {
+            // Only a cite has one, and only alongside a locator
+            let edge = ctx.locator_type.map(|_| EdgeData::Locator);
+            let gv = GroupVars::rendered_if(edge.is_some());
+            return (RefIR::Edge(edge), gv);
+        }
         if var == DateVariable::Accessed {
             either.map(|_| (RefIR::Edge(Some(EdgeData::Accessed)), GroupVars::Important))
         } else {
@@ -342,8 +350,23 @@ where
     I: OutputFormat,
 {
     let locale = ctx.locale();
-    // TODO: handle missing
-    let locale_date: &LocaleDate = locale.dates.get(&local.form).unwrap();
+    // A locale is not required to define both forms, and the merged locale only has what the
+    // fallback chain supplied. Use the other form rather than rendering nothing.
+    let other_form = match local.form {
+        DateForm::Text => DateForm::Numeric,
+        DateForm::Numeric => DateForm::Text,
+    };
+    let locale_date: &LocaleDate = match locale
+        .dates
+        .get(&local.form)
+        .or_else(|| locale.dates.get(&other_form))
+    {
+        Some(locale_date) => locale_date,
+        None => {
+            log::warn!("locale has no localized date formats, skipping {:?}", local);
+            return None;
+        }
+    };
     let gen_date = if ctx.sort_key().is_some() {
         GenericDateBits::sorting(locale)
     } else {
@@ -445,7 +468,8 @@ fn build_parts<'c, O: OutputFormat, I: OutputFormat>(
     // TODO: text-case
     let fmt = ctx.format();
     let len_hint = parts.len();
-    let mut val = ctx.reference().date.get(&var)?.clone();
+    // locator-date comes from the cite
+    let mut val = ctx.get_date(var)?.clone();
     let sorting = gen_date.sorting;
     if gen_date.sorting {
         // force range with zeroes on the end date if single
@@ -732,7 +756,7 @@ fn dp_render_either<'c, O: OutputFormat, I: OutputFormat>(
                 }
             }
             if let DatePartForm::Year(_) = part.form {
-                if var == DateVariable::Accessed {
+                if var == DateVariable::Accessed || var == DateVariable::LocatorDate {
                     let b = fmt.affixed_text(s, part.formatting, affixes.as_ref());
                     Either::Build(Some(b))
                 } else {
@@ -7,7 +7,7 @@
 // For the query group macro expansion
 #![allow(clippy::large_enum_variant)]
 
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
 use std::sync::Arc;
 
 use crate::cluster;
@@ -111,6 +111,13 @@ pub trait IrDatabase:
     fn sorted_refs(&self) -> Arc<(Vec<Atom>, FnvHashMap<Atom, BibNumber>)>;
     #[salsa::input]
     fn bibliography_no_sort(&self) -> bool;
+    /// References left out of the bibliography even though they are cited. Their cites render as
+    /// usual.
+    #[salsa::input]
+    fn bibliography_excluded(&self) -> Arc<FnvHashSet<Atom>>;
+    /// The characters used to count year suffixes. `None` means the usual `a` to `z`.
+    #[salsa::input]
+    fn year_suffix_alphabet(&self) -> Option<Arc<Vec<char>>>;
 
     #[salsa::invoke(crate::sort::bib_number)]
     fn bib_number(&self, id: CiteId) -> Option<BibNumber>;
@@ -118,6 +125,8 @@ pub trait IrDatabase:
 
 pub fn safe_default(db: &mut dyn IrDatabase) {
     db.set_bibliography_no_sort_with_durability(false, salsa::Durability::HIGH);
+    db.set_bibliography_excluded_with_durability(Default::default(), salsa::Durability::MEDIUM);
+    db.set_year_suffix_alphabet_with_durability(None, salsa::Durability::HIGH);
 }
 
 fn all_person_names(db: &dyn IrDatabase) -> Arc<Vec<DisambNameData>> {
@@ -205,7 +214,6 @@ fn year_suffix_for(db: &dyn IrDatabase, ref_id: Atom) -> Option<u32> {
 ///    b. For each cite A with more than its own, find, if any, a Group whose total refs intersects A.refs
 ///    c. If found G, add A to that group, and G.total_refs = G.total_refs UNION A.refs
 fn year_suffixes(db: &dyn IrDatabase) -> Arc<FnvHashMap<Atom, u32>> {
-    use fnv::FnvHashSet;
     let style = db.style();
     if !style.citation.disambiguate_add_year_suffix {
         return Arc::new(FnvHashMap::default());
@@ -320,6 +328,8 @@ pub struct IrGen {
     pub(crate) state: IrState,
     pub(crate) used_disambiguate_true: bool,
     pub(crate) disambiguation_finished: bool,
+    /// The disambiguation passes that changed this cite's output, in the order they ran.
+    pub(crate) disamb_passes: Vec<DisambPass>,
 }
 
 use std::fmt;
@@ -339,8 +349,20 @@ impl IrGen {
             state,
             used_disambiguate_true: false,
             disambiguation_finished,
+            disamb_passes: Vec::new(),
         }
     }
+    /// The disambiguation passes that changed this cite's output, in the order they ran. Empty
+    /// if the cite was unambiguous as first rendered.
+    pub fn disamb_passes(&self) -> &[DisambPass] {
+        &self.disamb_passes
+    }
+    /// A serializable copy of this cite's IR tree, with each rendered node's output formatted
+    /// using `fmt`.
+    #[cfg(feature = "debug-ir")]
+    pub fn debug_tree(&self, fmt: &Markup) -> crate::ir::debug::IrDebugNode {
+        crate::ir::debug::IrDebugNode::from_tree(self.tree_ref(), fmt)
+    }
     pub(crate) fn tree(&self) -> &IrTree {
         &self.tree
     }
@@ -375,7 +397,6 @@ fn ref_not_found(db: &dyn IrDatabase, ref_id: &Atom, log: bool) -> Arc<IrGen> {
 macro_rules! preamble {
     ($style:ident, $locale:ident, $cite:ident, $refr:ident, $ctx:ident, $db:expr, $id:expr, $pass:expr) => {{
         $style = $db.style();
-        $locale = $db.default_locale();
         // Avoid making bibliography ghosts all depend any positional / note num info
         let cite_stuff = match $db.lookup_cite($id) {
             CiteData::RealCite { cite, .. } => (cite, $db.cite_position($id)),
@@ -391,6 +412,7 @@ macro_rules! preamble {
             None => return ref_not_found($db, &$cite.ref_id, true),
             Some(r) => r,
         };
+        $locale = $db.reference_locale($refr.language.clone());
         let (names_delimiter, name_el) = $db.name_info_citation();
         $ctx = CiteContext {
             reference: &$refr,
@@ -642,12 +664,13 @@ fn get_cond_mut(cid: NodeId, arena: &mut IrArena) -> (&mut ConditionalDisambIR,
     (both.0.unwrap_cond_disamb_mut(), &mut both.1)
 }
 
+/// Returns whether the cite is now unambiguous, and whether any names were added or expanded.
 fn disambiguate_add_names(
     db: &dyn IrDatabase,
     tree: &mut IrTree,
     ctx: &mut CiteContext<'_, Markup>,
     also_expand: bool,
-) -> bool {
+) -> (bool, bool) {
     ctx.disamb_pass = Some(DisambPass::AddNames);
 
     let fmt = &db.get_formatter();
@@ -661,6 +684,7 @@ fn disambiguate_add_names(
         ctx.disamb_pass,
     );
     let mut best = initial_refs.len() as u16;
+    let mut changed = false;
     let name_refs = list_all_name_blocks(tree.tree_ref());
 
     debug!(
@@ -670,7 +694,7 @@ fn disambiguate_add_names(
 
     for (n, nid) in name_refs.into_iter().enumerate() {
         if best <= 1 {
-            return true;
+            return (true, changed);
         }
         let mut dfas = Vec::with_capacity(best as usize);
         for k in &initial_refs {
@@ -702,6 +726,7 @@ fn disambiguate_add_names(
             .map_or(false, |x| x.after_name);
         // Probably use an Atom for this buddy
         let built_label = nir.built_label.clone();
+        let start_bump = nir.name_counter.bump;
 
         while best > 1 {
             let nir = get_nir_mut(nid, &mut tree.arena);
@@ -720,13 +745,15 @@ fn disambiguate_add_names(
                 break;
             }
             if also_expand {
-                if let Some(expanded) = expand_one_name_ir(
+                let (expanded, any_expanded) = expand_one_name_ir(
                     db,
                     ctx,
                     &initial_refs,
                     get_nir_mut(nid, &mut tree.arena),
                     n as u32,
-                ) {
+                );
+                changed |= any_expanded;
+                if let Some(expanded) = expanded {
                     let seq = NameIR::rendered_ntbs_to_node(
                         expanded,
                         &mut tree.arena,
@@ -743,7 +770,10 @@ fn disambiguate_add_names(
             best = std::cmp::min(best, new_count);
         }
         // TODO: simply save the node id of the rolled-back nir, and restore it to position.
-        if let Some(rolled_back) = get_nir_mut(nid, &mut tree.arena).rollback(db, ctx) {
+        let nir = get_nir_mut(nid, &mut tree.arena);
+        let rolled_back = nir.rollback(db, ctx);
+        changed |= nir.name_counter.bump != start_bump;
+        if let Some(rolled_back) = rolled_back {
             let new_seq = NameIR::rendered_ntbs_to_node(
                 rolled_back,
                 &mut tree.arena,
@@ -756,54 +786,62 @@ fn disambiguate_add_names(
         tree.recompute_group_vars();
         best = total_ambiguity_number(tree.tree_ref());
     }
-    best <= 1
+    (best <= 1, changed)
 }
 
+/// Returns the rebuilt names, and whether any of them were expanded.
 fn expand_one_name_ir(
     db: &dyn IrDatabase,
     ctx: &CiteContext<'_, Markup>,
     refs_accepting: &[Atom],
     nir: &mut NameIR<Markup>,
     index: u32,
-) -> Option<Vec<MarkupBuild>> {
-    let mut double_vec: Vec<Vec<NameVariantMatcher>> = Vec::new();
+) -> (Option<Vec<MarkupBuild>>, bool) {
+    // One list of name matchers for each of the references that accept this cite, so we can
+    // tell which of them are still matched by the names expanded so far.
+    let mut by_ref: Vec<Vec<NameVariantMatcher>> = Vec::with_capacity(refs_accepting.len());
 
     for r in refs_accepting {
         if let Some(rnir) = make_identical_name_formatter(db, r.clone(), ctx, index) {
             let _var = rnir.variable;
-            let len = rnir.disamb_name_ids.len();
-            if len > double_vec.len() {
-                double_vec.resize_with(len, || Vec::with_capacity(nir.disamb_names.len()));
-            }
-            for (n, id) in rnir.disamb_name_ids.into_iter().enumerate() {
+            let matchers = rnir
+                .disamb_name_ids
+                .into_iter()
                 // This is ad-hoc RefIR, so we don't want it to have global disamb applied already.
                 // disambiguage_AndreaEg2
-                let dn = id.lookup(db);
-                let matcher = NameVariantMatcher::from_disamb_name(db, dn);
-                if let Some(slot) = double_vec.get_mut(n) {
-                    slot.push(matcher);
-                }
-            }
+                .map(|id| NameVariantMatcher::from_disamb_name(db, id.lookup(db)))
+                .collect();
+            by_ref.push(matchers);
         }
     }
     use crate::disamb::names::MatchKey;
 
-    let name_ambiguity_number =
-        |edge: &EdgeData, match_key: Option<&MatchKey>, slot: &[NameVariantMatcher]| -> u32 {
-            slot.iter()
-                .filter(|matcher| matcher.accepts(edge, None))
-                .count() as u32
-        };
+    let name_ambiguity_number = |edge: &EdgeData, _match_key: Option<&MatchKey>, n: usize| -> u32 {
+        by_ref
+            .iter()
+            .filter_map(|matchers| matchers.get(n))
+            .filter(|matcher| matcher.accepts(edge, None))
+            .count() as u32
+    };
+
+    // by-cite only expands names until the cite is no longer ambiguous. The other rules expand
+    // every ambiguous name.
+    let by_cite = ctx.style.citation.givenname_disambiguation_rule == GNDR::ByCite;
+    let mut still_matching = vec![true; by_ref.len()];
+    let mut expanded = false;
 
     let mut n = 0usize;
     for dnr in nir.disamb_names.iter_mut() {
         if let DisambNameRatchet::Person(ratchet) = dnr {
-            if let Some(ref slot) = double_vec.get(n) {
+            if by_cite && still_matching.iter().filter(|&&x| x).count() <= 1 {
+                break;
+            }
+            if by_ref.iter().any(|matchers| matchers.len() > n) {
                 // First, get the initial count
                 /* TODO: store format stack */
                 let mut edge = ratchet.data.single_name_edge(db, Formatting::default());
                 let key = ratchet.data.family_match_key();
-                let mut min = name_ambiguity_number(&edge, key.as_ref(), slot);
+                let mut min = name_ambiguity_number(&edge, key.as_ref(), n);
                 trace!("nan for {}-th ({:?}) initially {}", n, edge, min);
                 let mut stage_dn = ratchet.data.clone();
                 // Then, try to improve it
@@ -812,18 +850,27 @@ fn expand_one_name_ir(
                     if let Some(next) = iter.next() {
                         stage_dn.apply_upto_pass(next);
                         edge = stage_dn.single_name_edge(db, Formatting::default());
-                        let new_count = name_ambiguity_number(&edge, key.as_ref(), slot);
+                        let new_count = name_ambiguity_number(&edge, key.as_ref(), n);
                         if new_count < min {
                             // save the improvement
                             min = new_count;
                             ratchet.data = stage_dn.clone();
                             ratchet.iter = iter;
+                            expanded = true;
                         }
                         trace!("nan for {}-th ({:?}) got to {}", n, edge, min);
                     } else {
                         break;
                     }
                 }
+                if by_cite {
+                    let saved = ratchet.data.single_name_edge(db, Formatting::default());
+                    for (matching, matchers) in still_matching.iter_mut().zip(by_ref.iter()) {
+                        if let Some(matcher) = matchers.get(n) {
+                            *matching = *matching && matcher.accepts(&saved, None);
+                        }
+                    }
+                }
             } else {
                 // We've gone past the end of the slots.
                 // None of the ambiguous references had this many names
@@ -834,26 +881,28 @@ fn expand_one_name_ir(
             n += 1;
         }
     }
-    nir.intermediate_custom(
+    let built = nir.intermediate_custom(
         &ctx.format,
         ctx.position.0,
         ctx.sort_key.is_some(),
         ctx.disamb_pass,
         None,
-    )
+    );
+    (built, expanded)
 }
 
+/// Returns whether any names were expanded or added.
 fn disambiguate_add_givennames(
     db: &dyn IrDatabase,
     tree: &mut IrTree,
     ctx: &mut CiteContext<'_, Markup>,
     also_add: bool,
-) -> Option<bool> {
+) -> bool {
     ctx.disamb_pass = Some(DisambPass::AddGivenName(
         ctx.style.citation.givenname_disambiguation_rule,
     ));
     let _fmt = db.get_formatter();
-    let refs = refs_accepting_cite(
+    let mut refs = refs_accepting_cite(
         db,
         tree.tree_ref(),
         ctx.cite_id,
@@ -861,6 +910,8 @@ fn disambiguate_add_givennames(
         ctx.disamb_pass,
     );
     let name_refs = list_all_name_blocks(tree.tree_ref());
+    let by_cite = ctx.style.citation.givenname_disambiguation_rule == GNDR::ByCite;
+    let mut changed = false;
 
     let is_sort_key = ctx.sort_key.is_some();
     for (n, nid) in name_refs.into_iter().enumerate() {
@@ -873,7 +924,9 @@ fn disambiguate_add_givennames(
             .map_or(false, |x| x.after_name);
         let built_label = nir.built_label.clone();
 
-        if let Some(expanded) = expand_one_name_ir(db, ctx, &refs, nir, n as u32) {
+        let (expanded, any_expanded) = expand_one_name_ir(db, ctx, &refs, nir, n as u32);
+        changed |= any_expanded;
+        if let Some(expanded) = expanded {
             let seq = NameIR::rendered_ntbs_to_node(
                 expanded,
                 &mut tree.arena,
@@ -885,21 +938,41 @@ fn disambiguate_add_givennames(
         }
         // TODO: this is likely unnecessary
         tree.recompute_group_vars();
+        if by_cite {
+            // Leave the remaining name blocks alone once the cite is unambiguous.
+            refs = refs_accepting_cite(
+                db,
+                tree.tree_ref(),
+                ctx.cite_id,
+                &ctx.reference.id,
+                ctx.disamb_pass,
+            );
+            if refs.len() <= 1 {
+                break;
+            }
+        }
     }
     if also_add {
-        disambiguate_add_names(db, tree, ctx, true);
+        let (_, added) = disambiguate_add_names(db, tree, ctx, true);
+        changed |= added;
     }
-    None
+    changed
 }
 
-fn disambiguate_add_year_suffix(tree: &mut IrTree, ctx: &CiteContext<'_, Markup>, suffix: u32) {
+/// Returns whether a year suffix hook was found to put the suffix in.
+fn disambiguate_add_year_suffix(
+    db: &dyn IrDatabase,
+    tree: &mut IrTree,
+    ctx: &CiteContext<'_, Markup>,
+    suffix: u32,
+) -> bool {
     // First see if we can do it with an explicit one
     let hooks = tree.tree_ref().list_year_suffix_hooks();
     let mut added_suffix = false;
     for &yid in &hooks {
         let (ys, _) = get_ys_mut(yid, &mut tree.arena);
         let sum: IrSum<Markup> = match &ys.hook {
-            YearSuffixHook::Explicit(_) => ys.hook.render(ctx, suffix),
+            YearSuffixHook::Explicit(_) => ys.hook.render(db, ctx, suffix),
             _ => continue,
         };
         let gv = sum.1;
@@ -913,7 +986,7 @@ fn disambiguate_add_year_suffix(tree: &mut IrTree, ctx: &CiteContext<'_, Markup>
     }
     if added_suffix {
         tree.recompute_group_vars();
-        return;
+        return true;
     }
 
     // Then attempt to do it for the ones that are embedded in date output
@@ -921,7 +994,7 @@ fn disambiguate_add_year_suffix(tree: &mut IrTree, ctx: &CiteContext<'_, Markup>
         let (ys, _) = get_ys_mut(yid, &mut tree.arena);
         let sum: IrSum<Markup> = match &ys.hook {
             // This produces GroupVars::Important
-            YearSuffixHook::Plain => ys.hook.render(ctx, suffix),
+            YearSuffixHook::Plain => ys.hook.render(db, ctx, suffix),
             _ => continue,
         };
         let gv = sum.1;
@@ -930,27 +1003,31 @@ fn disambiguate_add_year_suffix(tree: &mut IrTree, ctx: &CiteContext<'_, Markup>
         let (ys, ys_gv) = get_ys_mut(yid, &mut tree.arena);
         *ys_gv = gv;
         ys.suffix_num = Some(suffix);
+        added_suffix = true;
         break;
     }
 
     tree.recompute_group_vars();
+    added_suffix
 }
 
+/// Returns whether any `disambiguate="true"` conditionals were re-rendered.
 #[inline(never)]
 fn disambiguate_true(
     db: &dyn IrDatabase,
     tree: &mut IrTree,
     state: &mut IrState,
     ctx: &CiteContext<'_, Markup>,
-) {
+) -> bool {
     debug!(
         "attempting to disambiguate {:?} ({}) with {:?}",
         ctx.cite_id, &ctx.reference.id, ctx.disamb_pass
     );
     let un = is_unambiguous(db, tree.tree_ref(), &ctx.reference.id);
     if un {
-        return;
+        return false;
     }
+    let mut changed = false;
     let cond_refs = list_all_cond_disambs(tree.tree_ref());
     for cid in cond_refs.into_iter() {
         if is_unambiguous(db, tree.tree_ref(), &ctx.reference.id) {
@@ -969,7 +1046,9 @@ fn disambiguate_true(
             *cond_gv = gv;
         }
         tree.recompute_group_vars();
+        changed = true;
     }
+    changed
 }
 
 fn ir_gen0(db: &dyn IrDatabase, id: CiteId) -> Arc<IrGen> {
@@ -1015,6 +1094,12 @@ impl IrGenCow {
     fn into_arc(self) -> Arc<IrGen> {
         self.arc
     }
+    /// Records `pass` if the disambiguation routine reported that it changed anything.
+    fn record_pass(&mut self, pass: DisambPass, changed: bool) {
+        if changed {
+            self.to_mut().disamb_passes.push(pass);
+        }
+    }
 }
 
 use std::ops::Deref;
@@ -1037,8 +1122,9 @@ impl IrGenCow {
         if ctx.style.citation.disambiguate_add_names {
             // Clone ir0; disambiguate by adding names
             let cloned = self.to_mut();
-            cloned.disambiguation_finished =
-                disambiguate_add_names(db, cloned.tree_mut(), ctx, false);
+            let (finished, changed) = disambiguate_add_names(db, cloned.tree_mut(), ctx, false);
+            cloned.disambiguation_finished = finished;
+            self.record_pass(DisambPass::AddNames, changed);
         }
     }
 
@@ -1049,7 +1135,9 @@ impl IrGenCow {
         if ctx.style.citation.disambiguate_add_givenname {
             let cloned = self.to_mut();
             let also_add_names = ctx.style.citation.disambiguate_add_names;
-            disambiguate_add_givennames(db, cloned.tree_mut(), ctx, also_add_names);
+            let changed = disambiguate_add_givennames(db, cloned.tree_mut(), ctx, also_add_names);
+            let rule = ctx.style.citation.givenname_disambiguation_rule;
+            self.record_pass(DisambPass::AddGivenName(rule), changed);
         }
     }
     fn disambiguate_add_year_suffix(&mut self, db: &dyn IrDatabase, ctx: &mut CiteContext<Markup>) {
@@ -1063,7 +1151,8 @@ impl IrGenCow {
             };
             let cloned = self.to_mut();
             ctx.disamb_pass = Some(DisambPass::AddYearSuffix(year_suffix));
-            disambiguate_add_year_suffix(cloned.tree_mut(), &ctx, year_suffix);
+            let changed = disambiguate_add_year_suffix(db, cloned.tree_mut(), &ctx, year_suffix);
+            self.record_pass(DisambPass::AddYearSuffix(year_suffix), changed);
             // if it's already unambiguous on names alone, then adding year suffixes is hardly
             // going to improve it. So avoid the cost.
             if !self.disambiguation_finished {
@@ -1087,7 +1176,8 @@ impl IrGenCow {
         let cloned = self.to_mut();
         ctx.disamb_pass = Some(DisambPass::Conditionals);
         cloned.used_disambiguate_true = true;
-        disambiguate_true(db, &mut cloned.tree, &mut cloned.state, &ctx);
+        let changed = disambiguate_true(db, &mut cloned.tree, &mut cloned.state, &ctx);
+        self.record_pass(DisambPass::Conditionals, changed);
     }
 }
 
@@ -1155,6 +1245,15 @@ pub fn built_cluster_preview(
     Arc::new(string)
 }
 
+pub fn built_cluster_author_prefix(
+    db: &dyn IrDatabase,
+    cluster_id: ClusterId,
+) -> Option<Arc<<Markup as OutputFormat>::Output>> {
+    let fmt = db.get_formatter();
+    let build = cluster::built_cluster_author_prefix(db, cluster_id, &fmt)?;
+    Some(Arc::new(fmt.output(build, get_piq(db))))
+}
+
 #[test]
 pub fn test_preview_unicode_escape_issue_91() {
     use crate::test::{test_style_layout, MockProcessor};
@@ -1227,9 +1326,9 @@ pub fn with_cite_context<T>(
     f: impl FnOnce(CiteContext) -> T,
 ) -> Option<T> {
     let style = db.style();
-    let locale = db.default_locale();
     let cite = id.lookup(db);
     let refr = db.reference(cite.ref_id.clone())?;
+    let locale = db.reference_locale(refr.language.clone());
     let (names_delimiter, name_el) = db.name_info_citation();
     let ctx = CiteContext {
         reference: &refr,
@@ -1269,7 +1368,6 @@ pub fn with_bib_context<T>(
 ) -> Option<T> {
     let style = db.style();
     let bib = style.bibliography.as_ref()?;
-    let locale = db.default_locale();
     let cite = Cite::basic(ref_id.clone());
     let null_ref = citeproc_io::Reference::empty("empty_ref".into(), csl::CslType::Article);
     let (refr, is_ref_missing) = if let Some(r) = refr {
@@ -1277,6 +1375,7 @@ pub fn with_bib_context<T>(
     } else {
         (&null_ref, true)
     };
+    let locale = db.reference_locale(refr.language.clone());
     let (names_delimiter, name_el) = db.name_info_bibliography();
     let ctx = CiteContext {
         reference: &refr,
@@ -1314,10 +1413,16 @@ fn bib_item_gen0(db: &dyn IrDatabase, ref_id: Atom) -> Option<Arc<IrGen>> {
     bib_item_gen0_acontextual(db, ref_id, refr_arc.as_deref(), Some(bib_number))
 }
 
-fn format_single_bib_item(ir_gen: Option<&IrGen>, fmt: &Markup, piq: bool) -> SmartString {
+fn format_single_bib_item(
+    ref_id: &str,
+    ir_gen: Option<&IrGen>,
+    fmt: &Markup,
+    piq: bool,
+) -> SmartString {
     ir_gen
         .and_then(|ir_gen| {
             let flat = ir_gen.tree_ref().flatten(&fmt, None)?;
+            let flat = fmt.bib_anchor(ref_id, flat);
             let string = fmt.output(flat, piq);
             if string.is_empty() {
                 return None;
@@ -1331,6 +1436,7 @@ fn bib_item(db: &dyn IrDatabase, ref_id: Atom) -> Arc<MarkupOutput> {
     let fmt = db.get_formatter();
     let gen0_arc = db.bib_item_gen0(ref_id);
     Arc::new(format_single_bib_item(
+        &ref_id,
         gen0_arc.as_deref(),
         &fmt,
         get_piq(db),
@@ -1346,8 +1452,43 @@ pub fn bib_item_preview(
     fmt: &Markup,
 ) -> SmartString {
     // Pretend it's the first item in the bibliography
-    let gen0_arc = bib_item_gen0_acontextual(db, ref_id, Some(refr), Some(1));
-    format_single_bib_item(gen0_arc.as_deref(), fmt, get_piq(db))
+    let gen0_arc = bib_item_gen0_acontextual(db, ref_id.clone(), Some(refr), Some(1));
+    format_single_bib_item(&ref_id, gen0_arc.as_deref(), fmt, get_piq(db))
+}
+
+/// The result of [ghost_cite_disambiguation].
+#[derive(Debug, Clone)]
+pub struct GhostDisambiguation {
+    /// Every disambiguation participant the undisambiguated cite could have been produced by,
+    /// including the reference itself.
+    pub matching_refs: Vec<Atom>,
+    /// The fully disambiguated cite
+    pub gen: Arc<IrGen>,
+    pub output: SmartString,
+}
+
+/// Runs a basic cite to `ref_id` through the disambiguation DFAs, the same way the bibliography
+/// does to assign year suffixes. `ref_id` must be a disambiguation participant.
+pub fn ghost_cite_disambiguation(
+    db: &dyn IrDatabase,
+    ref_id: Atom,
+    fmt: &Markup,
+) -> GhostDisambiguation {
+    let cite = db.ghost_cite(ref_id.clone());
+    let cite_id = db.cite(CiteData::BibliographyGhost { cite });
+    let gen0 = db.ir_gen0(cite_id);
+    let matching_refs = refs_accepting_cite(db, gen0.tree_ref(), Some(cite_id), &ref_id, None);
+    let gen = db.ir_fully_disambiguated(cite_id);
+    let output = gen
+        .tree_ref()
+        .flatten(fmt, None)
+        .map(|flat| fmt.output(flat, get_piq(db)))
+        .unwrap_or_default();
+    GhostDisambiguation {
+        matching_refs,
+        gen,
+        output,
+    }
 }
 
 fn bib_item_gen0_acontextual(
@@ -1380,7 +1521,7 @@ fn bib_item_gen0_acontextual(
             log::debug!("bib_ir_gen0: {}", tree);
             if let Some(suffix) = db.year_suffix_for(ref_id.clone()) {
                 ctx.disamb_pass = Some(DisambPass::AddYearSuffix(suffix));
-                disambiguate_add_year_suffix(&mut tree, &ctx, suffix);
+                disambiguate_add_year_suffix(db, &mut tree, &ctx, suffix);
                 log::debug!("bib_ir add_year_suffix: {}", tree);
             }
 
@@ -1466,15 +1607,74 @@ fn first_cite_used_disambiguate_true(db: &dyn IrDatabase, ref_id: Atom) -> bool
 }
 
 fn get_bibliography_map(db: &dyn IrDatabase) -> Arc<FnvHashMap<Atom, Arc<MarkupOutput>>> {
-    let fmt = db.get_formatter();
-    let style = db.style();
-    let sorted_refs = db.sorted_refs();
-    let mut m =
-        FnvHashMap::with_capacity_and_hasher(sorted_refs.0.len(), fnv::FnvBuildHasher::default());
-    let mut prev: Option<(NodeId, Arc<IrGen>)> = None;
-    for key in sorted_refs.0.iter() {
-        // TODO: put Nones in there so they can be updated
-        if let Some(mut gen0) = db.bib_item_gen0(key.clone()) {
+    let iter = BibliographyIter::new(db);
+    let mut m = FnvHashMap::with_capacity_and_hasher(iter.len_hint(), Default::default());
+    m.extend(iter);
+    Arc::new(m)
+}
+
+/// Renders bibliography entries one at a time, in bibliography order, skipping any that render to
+/// nothing or are in [IrDatabase::bibliography_excluded]. Collecting this gives you
+/// [IrDatabase::get_bibliography_map], without having to hold every rendered entry in memory at
+/// once.
+pub struct BibliographyIter<'a> {
+    db: &'a dyn IrDatabase,
+    fmt: Markup,
+    style: Arc<csl::Style>,
+    sorted_refs: Arc<(Vec<Atom>, FnvHashMap<Atom, BibNumber>)>,
+    exclude: Arc<FnvHashSet<Atom>>,
+    next_index: usize,
+    /// The previous entry's first names block, for subsequent-author-substitute
+    prev: Option<(NodeId, Arc<IrGen>)>,
+}
+
+impl<'a> BibliographyIter<'a> {
+    pub fn new(db: &'a dyn IrDatabase) -> Self {
+        Self::with_formatter(db, db.get_formatter())
+    }
+
+    /// Renders with `fmt` instead of the database's output format.
+    pub fn with_formatter(db: &'a dyn IrDatabase, fmt: Markup) -> Self {
+        BibliographyIter {
+            db,
+            fmt,
+            style: db.style(),
+            sorted_refs: db.sorted_refs(),
+            exclude: db.bibliography_excluded(),
+            next_index: 0,
+            prev: None,
+        }
+    }
+
+    /// The number of references left to render. Some of them may be skipped.
+    fn len_hint(&self) -> usize {
+        self.sorted_refs.0.len() - self.next_index
+    }
+}
+
+impl<'a> Iterator for BibliographyIter<'a> {
+    type Item = (Atom, Arc<MarkupOutput>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let BibliographyIter {
+            db,
+            ref fmt,
+            ref style,
+            ref sorted_refs,
+            ref exclude,
+            ref mut next_index,
+            ref mut prev,
+        } = *self;
+        while let Some(key) = sorted_refs.0.get(*next_index) {
+            *next_index += 1;
+            if exclude.contains(key) {
+                continue;
+            }
+            // TODO: put Nones in there so they can be updated
+            let mut gen0 = match db.bib_item_gen0(key.clone()) {
+                Some(gen0) => gen0,
+                None => continue,
+            };
             // in a bibliography, we do the affixes etc inside Layout, so they're not here
             let current = gen0.tree_ref().first_names_block();
             let sas = style.bibliography.as_ref().and_then(|bib| {
@@ -1490,7 +1690,7 @@ fn get_bibliography_map(db: &dyn IrDatabase) -> Arc<FnvHashMap<Atom, Arc<MarkupO
             ) {
                 let mutated = Arc::make_mut(&mut gen0);
                 let did = transforms::subsequent_author_substitute(
-                    &fmt,
+                    fmt,
                     // In order to unwrap this here, you must only replace the NameIR node's
                     // children, not the IR.
                     prev_name_block.get().0.unwrap_name_ir(),
@@ -1505,16 +1705,21 @@ fn get_bibliography_map(db: &dyn IrDatabase) -> Arc<FnvHashMap<Atom, Arc<MarkupO
             }
             let flat = gen0
                 .tree_ref()
-                .flatten(&fmt, None)
+                .flatten(fmt, None)
                 .unwrap_or_else(|| fmt.plain(""));
+            let flat = fmt.bib_anchor(key, flat);
             let string = fmt.output(flat, get_piq(db));
+            *prev = current.map(|cur| (cur, gen0));
             if !string.is_empty() {
-                m.insert(key.clone(), Arc::new(string));
+                return Some((key.clone(), Arc::new(string)));
             }
-            prev = current.map(|cur| (cur, gen0));
         }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.len_hint()))
     }
-    Arc::new(m)
 }
 
 // See https://github.com/jgm/pandoc-citeproc/blob/e36c73ac45c54dec381920e92b199787601713d1/src/Text/CSL/Reference.hs#L910
@@ -1698,9 +1903,11 @@ fn cite_positions(db: &dyn IrDatabase) -> Arc<FnvHashMap<CiteId, (Position, Opti
                             }
                         }
                         ClusterNumber::InText(_this_intext) => {
-                            log::warn!("InText should not be sorted after a Note");
-                            // this won't happen; InText is sorted before Note. Nevertheless:
-                            map.insert(cite_id, (Position::First, None));
+                            // An in-text cluster later in a mixed document. It has no note
+                            // number of its own, but the reference was first cited in a note.
+                            let unsigned = first_note_number.note_number();
+                            let pos = matching_prev.unwrap_or(Position::Subsequent);
+                            map.insert(cite_id, (pos, Some(unsigned)));
                         }
                         ClusterNumber::OutsideFlow => {
                             map.insert(cite_id, (Position::First, None));
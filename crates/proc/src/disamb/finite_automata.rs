@@ -168,6 +168,15 @@ impl fmt::Display for EdgeData {
 }
 
 impl Dfa {
+    /// Every piece of literal output on the DFA's edges, i.e. the tokens a cite to this reference
+    /// could be matched against. May contain duplicates.
+    pub fn output_tokens(&self) -> impl Iterator<Item = &<Markup as OutputFormat>::Output> + '_ {
+        self.graph.edge_weights().filter_map(|edge| match edge {
+            EdgeData::Output(o) => Some(o),
+            _ => None,
+        })
+    }
+
     pub fn debug_graph(&self, _db: &dyn IrDatabase) -> String {
         let g = self.graph.map(
             |node, _| {
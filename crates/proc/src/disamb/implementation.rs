@@ -18,7 +18,10 @@ impl Disambiguation<Markup> for Style {
         state: &mut IrState,
         stack: Formatting,
     ) -> (RefIR, GroupVars) {
-        let els = &self.citation.layout.elements;
+        let els = &self
+            .citation
+            .layout_for(ctx.reference.language.as_ref())
+            .elements;
         ref_sequence(db, state, ctx, els, false, Some(stack), None)
     }
 }
@@ -113,6 +116,13 @@ impl Disambiguation<Markup> for Element {
                                 return (RefIR::Edge(edge), gv);
                             }
                         }
+                        StandardVariable::Ordinary(v @ Variable::LocatorExtra) => {
+                            // Only a cite has one, and only alongside a locator
+                            let edge = state
+                                .maybe_suppress(v, |_| ctx.locator_type.map(|_| EdgeData::Locator));
+                            let gv = GroupVars::rendered_if(edge.is_some());
+                            return (RefIR::Edge(edge), gv);
+                        }
                         StandardVariable::Ordinary(v @ Variable::YearSuffix) => {
                             if ctx.year_suffix {
                                 let edge = state
@@ -200,7 +210,9 @@ impl Disambiguation<Markup> for Element {
                         .macros
                         .get(name)
                         .expect("undefined macro should not be valid CSL");
-                    state.push_macro(name);
+                    if !state.push_macro(name) {
+                        return (RefIR::Edge(None), GroupVars::Plain);
+                    }
                     let (seq, group_vars) = ref_sequence(
                         db,
                         state,
@@ -86,8 +86,14 @@ use csl::{
 };
 
 pub fn get_free_conds(db: &dyn IrDatabase) -> FreeCondSets {
+    let style = db.style();
     let mut walker = FreeCondWalker::new(db);
-    walker.walk_citation(&db.style())
+    let mut sets = walker.walk_citation(&style);
+    // Any reference might render with one of the locale layouts instead, like a choose branch.
+    for layout in &style.citation.locale_layouts {
+        sets.0.extend(walker.layout(layout).0);
+    }
+    sets
 }
 
 struct FreeCondWalker<'a> {
@@ -133,7 +139,9 @@ impl<'a> StyleWalker for FreeCondWalker<'a> {
             .get(name)
             .expect("undefined macro should not be valid CSL");
 
-        self.state.push_macro(name);
+        if !self.state.push_macro(name) {
+            return self.fold(&[], WalkerFoldType::Macro(text));
+        }
         let ret = self.fold(macro_elements, WalkerFoldType::Macro(text));
         self.state.pop_macro(name);
         ret
@@ -271,7 +279,7 @@ pub fn create_ref_ir<O: OutputFormat>(
     refr: &Reference,
 ) -> Vec<(FreeCond, RefIR)> {
     let style = db.style();
-    let locale = db.default_locale();
+    let locale = db.reference_locale(refr.language.clone());
     let ysh_explicit_edge = EdgeData::YearSuffixExplicit;
     let ysh_plain_edge = EdgeData::YearSuffixPlain;
     let ysh_edge = EdgeData::YearSuffix;
@@ -101,9 +101,14 @@ where
     pub fn count_disambiguate_branches(&mut self, location: CiteOrBib) {
         let count = {
             let mut counter = DisambCounter::new(&self);
+            let lang = self.reference.language.as_ref();
             match location {
-                CiteOrBib::Citation => counter.walk_citation(self.style),
-                CiteOrBib::Bibliography => counter.walk_bibliography(self.style).unwrap_or(0),
+                CiteOrBib::Citation => counter.layout(self.style.citation.layout_for(lang)),
+                CiteOrBib::Bibliography => self
+                    .style
+                    .bibliography
+                    .as_ref()
+                    .map_or(0, |bib| counter.layout(bib.layout_for(lang))),
             }
         };
         self.disamb_count = count;
@@ -129,7 +134,9 @@ impl<'c, O: OutputFormat> RenderContext for RefContext<'c, O> {
                 .map(NumericValue::from_localized(and_term))
         };
         match var {
-            NumberVariable::PageFirst => get(NumberVariable::Page).and_then(|pp| pp.page_first()),
+            // An explicit page-first wins; otherwise it is the first number in page.
+            NumberVariable::PageFirst => get(NumberVariable::PageFirst)
+                .or_else(|| get(NumberVariable::Page).and_then(|pp| pp.page_first())),
 
             // Should never be accessed, handled without using the actual NumericValue
             NumberVariable::FirstReferenceNoteNumber
@@ -150,7 +157,10 @@ where
             AnyVariable::Number(v) => match v {
                 NumberVariable::Locator => self.locator_type.is_some(),
                 NumberVariable::PageFirst => {
-                    self.is_numeric(AnyVariable::Number(NumberVariable::Page))
+                    self.reference
+                        .number
+                        .contains_key(&NumberVariable::PageFirst)
+                        || self.is_numeric(AnyVariable::Number(NumberVariable::Page))
                 }
                 NumberVariable::FirstReferenceNoteNumber => {
                     self.position.matches(Position::Subsequent)
@@ -164,8 +174,11 @@ where
                 // TODO: make Hereinafter a FreeCond
                 Variable::Hereinafter => unimplemented!("Hereinafter as a FreeCond"),
                 Variable::YearSuffix => self.year_suffix,
+                // Only a cite has one, and only alongside a locator
+                Variable::LocatorExtra => self.locator_type.is_some(),
                 _ => self.get_ordinary(v, VariableForm::Long).is_some(),
             },
+            AnyVariable::Date(DateVariable::LocatorDate) => self.locator_type.is_some(),
             AnyVariable::Date(v) => self.reference.date.contains_key(&v),
             AnyVariable::Name(NameVariable::Dummy) => false,
             AnyVariable::Name(v) => self.reference.name.contains_key(&v),
@@ -188,9 +201,16 @@ where
     fn get_date(&self, dvar: DateVariable) -> Option<&DateOrRange> {
         self.reference.date.get(&dvar)
     }
+    fn is_plural(&self, var: NameVariable) -> bool {
+        self.get_name(var).map_or(false, |names| names.len() > 1)
+    }
     fn position(&self) -> Option<Position> {
         Some(self.position)
     }
+    fn cite_or_bib(&self) -> CiteOrBib {
+        // RefIR is only ever matched against cites.
+        CiteOrBib::Citation
+    }
     fn is_disambiguate(&self, current_count: u32) -> bool {
         // See docs on is_disambiguate
         // current_count is mutated as IR is rolled out;
@@ -14,7 +14,7 @@ where
         ctx: &CiteContext<'c, O, I>,
         arena: &mut IrArena<O>,
     ) -> NodeId {
-        let layout = &self.layout;
+        let layout = self.layout_for(ctx.reference.language.as_ref());
         sequence(
             db,
             state,
@@ -63,7 +63,7 @@ where
     ) -> NodeId {
         // Unlike cite, we will apply affixes and formatting in the seq, so that they go inside
         // any second-field-align content.
-        let layout = &self.layout;
+        let layout = self.layout_for(ctx.reference.language.as_ref());
         sequence(
             db,
             state,
@@ -107,11 +107,9 @@ where
                             .macros
                             .get(name)
                             .expect("undefined macro should not be valid CSL");
-                        // Technically, if re-running a style with a fresh IrState, you might
-                        // get an extra level of recursion before it panics. BUT, then it will
-                        // already have panicked when it was run the first time! So we're OK.
-                        // XXX: that's not quite true
-                        state.push_macro(name);
+                        if !state.push_macro(name) {
+                            return arena.new_node((IR::Rendered(None), GroupVars::Plain));
+                        }
                         let ir_sum = sequence(
                             db,
                             state,
@@ -142,7 +140,7 @@ where
                             let hook = YearSuffixHook::Explicit(text.clone());
                             // Only available when sorting, and ir_gen3 and later
                             if let Some(i) = ctx.year_suffix {
-                                return arena.new_node(hook.render(ctx, i));
+                                return arena.new_node(hook.render(db, ctx, i));
                             }
                             return arena.new_node(IR::year_suffix(hook));
                         }
@@ -245,6 +243,7 @@ where
                     formatting: g.formatting,
                     affixes: g.affixes.clone(),
                     display: g.display,
+                    is_parallel: g.is_parallel,
                     ..Default::default()
                 }),
             ),
@@ -262,6 +261,7 @@ where
 impl YearSuffixHook {
     pub(crate) fn render<'c, O: OutputFormat, I: OutputFormat>(
         &self,
+        db: &dyn IrDatabase,
         ctx: &CiteContext<'c, O, I>,
         suffix_num: u32,
     ) -> IrSum<O> {
@@ -271,10 +271,13 @@ impl YearSuffixHook {
             _ => &implicit,
         };
         let renderer = Renderer::cite(ctx);
-        let base26 = citeproc_io::utils::to_bijective_base_26(suffix_num);
+        let suffix = match db.year_suffix_alphabet() {
+            Some(alphabet) => citeproc_io::utils::to_bijective_base(suffix_num, &alphabet),
+            None => citeproc_io::utils::to_bijective_base_26(suffix_num),
+        };
         let output = renderer
-            .text_value(text, &base26)
-            .expect("base26 is not empty");
+            .text_value(text, &suffix)
+            .expect("year suffix is not empty");
         (
             IR::Rendered(Some(CiteEdgeData::YearSuffix(output))),
             GroupVars::Important,
@@ -13,6 +13,8 @@ use csl::{NumberVariable, StandardVariable, Variable};
 
 use std::sync::Arc;
 
+#[cfg(feature = "debug-ir")]
+pub mod debug;
 pub mod transforms;
 
 pub type IrSum<O> = (IR<O>, GroupVars);
@@ -113,6 +115,9 @@ pub struct IrSeq {
     /// Useful for identifying each top-of-cite `<layout>` element, especially when two or more
     /// cites have already been combined into one tree.
     pub is_layout: bool,
+    /// CSL-M `<group is-parallel="true">`, marking the part of a cite that differs between
+    /// parallel citations.
+    pub is_parallel: bool,
 }
 
 impl fmt::Debug for IrSeq {
@@ -120,7 +125,7 @@ impl fmt::Debug for IrSeq {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let Self {
             formatting, affixes, delimiter, display, quotes,
-            text_case, dropped_gv, should_inherit_delim, is_layout,
+            text_case, dropped_gv, should_inherit_delim, is_layout, is_parallel,
         } = self;
         let mut f = f.debug_struct("IrSeq");
         if formatting.is_some() { f.field("formatting", &formatting); }
@@ -132,6 +137,7 @@ impl fmt::Debug for IrSeq {
         if dropped_gv.is_some() { f.field("dropped_gv", &dropped_gv); }
         if *should_inherit_delim { f.field("should_inherit_delim", &should_inherit_delim); }
         if *is_layout { f.field("is_layout", &is_layout); }
+        if *is_parallel { f.field("is_parallel", &is_parallel); }
         f.finish()
     }
 }
@@ -181,6 +187,9 @@ pub enum CiteEdgeData<O: OutputFormat = Markup> {
     Output(O::Build),
     Locator(O::Build),
     LocatorLabel(O::Build),
+    /// `locator-extra` and `locator-date`, which come from the cite. For disambiguation, these
+    /// stand in for the locator they accompany.
+    LocatorExtra(O::Build),
     /// Used for representing a YearSuffix that has actually been rendered during disambiguation.
     YearSuffix(O::Build),
     CitationNumber(O::Build),
@@ -211,6 +220,7 @@ impl<O: OutputFormat> CiteEdgeData<O> {
             Variable::YearSuffix => CiteEdgeData::YearSuffix,
             Variable::Title => CiteEdgeData::Title,
             Variable::TitleShort => CiteEdgeData::Title,
+            Variable::LocatorExtra => CiteEdgeData::LocatorExtra,
             _ => CiteEdgeData::Output,
         }
     }
@@ -223,6 +233,7 @@ impl<O: OutputFormat> CiteEdgeData<O> {
     pub fn from_date_variable(var: DateVariable) -> fn(O::Build) -> Self {
         match var {
             DateVariable::Accessed => CiteEdgeData::Accessed,
+            DateVariable::LocatorDate => CiteEdgeData::LocatorExtra,
             _ => CiteEdgeData::Output,
         }
     }
@@ -321,7 +332,7 @@ impl<O: OutputFormat<Output = SmartString>> CiteEdgeData<O> {
             CiteEdgeData::YearSuffix(_) => EdgeData::YearSuffix,
             CiteEdgeData::Frnn(_) => EdgeData::Frnn,
             CiteEdgeData::FrnnLabel(_) => EdgeData::FrnnLabel,
-            CiteEdgeData::Locator(_) => EdgeData::Locator,
+            CiteEdgeData::Locator(_) | CiteEdgeData::LocatorExtra(_) => EdgeData::Locator,
             CiteEdgeData::LocatorLabel(_) => EdgeData::LocatorLabel,
             CiteEdgeData::CitationNumber(_) => EdgeData::CitationNumber,
             CiteEdgeData::CitationNumberLabel(_) => EdgeData::CitationNumberLabel,
@@ -339,6 +350,7 @@ impl<O: OutputFormat> CiteEdgeData<O> {
             | Self::Output(b)
             | Self::Locator(b)
             | Self::LocatorLabel(b)
+            | Self::LocatorExtra(b)
             | Self::YearSuffix(b)
             | Self::CitationNumber(b)
             | Self::CitationNumberLabel(b)
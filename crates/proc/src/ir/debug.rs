@@ -0,0 +1,82 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2021 Corporation for Digital Scholarship
+
+//! A serializable view of an IR tree, for style authors to inspect how a cite was built.
+
+use crate::prelude::*;
+use serde_derive::Serialize;
+
+/// One node of an IR tree and its children. Serializes to JSON like
+/// `{ "kind": "seq", "groupVars": "Important", "delimiter": ", ", "children": [...] }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IrDebugNode {
+    /// One of `rendered`, `name`, `substitute`, `conditionalDisamb`, `yearSuffix`, `seq` or
+    /// `nameCounter`
+    pub kind: &'static str,
+    pub group_vars: String,
+    /// For `rendered` nodes, what the output is tagged as, e.g. `Output` or `Locator`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edge: Option<&'static str>,
+    /// For `rendered` nodes, the output they contribute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<IrDebugNode>,
+}
+
+impl IrDebugNode {
+    pub(crate) fn from_tree(tree: IrTreeRef, fmt: &Markup) -> Self {
+        let (ir, gv) = tree.get_node().expect("IR node not in its own arena").get();
+        let (kind, edge, affixes, delimiter) = match ir {
+            IR::Rendered(edge) => ("rendered", edge.as_ref(), None, None),
+            IR::Name(_) => ("name", None, None, None),
+            IR::Substitute => ("substitute", None, None, None),
+            IR::ConditionalDisamb(_) => ("conditionalDisamb", None, None, None),
+            IR::YearSuffix(_) => ("yearSuffix", None, None, None),
+            IR::Seq(seq) => ("seq", None, seq.affixes.as_ref(), seq.delimiter.as_ref()),
+            IR::NameCounter(_) => ("nameCounter", None, None, None),
+        };
+        let non_empty = |s: &SmartString| Some(s.to_string()).filter(|x| !x.is_empty());
+        IrDebugNode {
+            kind,
+            group_vars: format!("{:?}", gv),
+            edge: edge.map(edge_name),
+            output: edge.map(|edge| fmt.output(edge.inner(), false)),
+            prefix: affixes.and_then(|affixes| non_empty(&affixes.prefix)),
+            suffix: affixes.and_then(|affixes| non_empty(&affixes.suffix)),
+            delimiter: delimiter.map(|d| d.to_string()),
+            children: tree
+                .children()
+                .map(|child| IrDebugNode::from_tree(child, fmt))
+                .collect(),
+        }
+    }
+}
+
+fn edge_name(edge: &CiteEdgeData) -> &'static str {
+    match edge {
+        CiteEdgeData::Title(_) => "Title",
+        CiteEdgeData::Output(_) => "Output",
+        CiteEdgeData::Locator(_) => "Locator",
+        CiteEdgeData::LocatorLabel(_) => "LocatorLabel",
+        CiteEdgeData::LocatorExtra(_) => "LocatorExtra",
+        CiteEdgeData::YearSuffix(_) => "YearSuffix",
+        CiteEdgeData::CitationNumber(_) => "CitationNumber",
+        CiteEdgeData::CitationNumberLabel(_) => "CitationNumberLabel",
+        CiteEdgeData::Frnn(_) => "Frnn",
+        CiteEdgeData::FrnnLabel(_) => "FrnnLabel",
+        CiteEdgeData::Accessed(_) => "Accessed",
+        CiteEdgeData::Year(_) => "Year",
+        CiteEdgeData::Term(_) => "Term",
+    }
+}
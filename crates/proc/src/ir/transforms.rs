@@ -779,6 +779,43 @@ pub fn subsequent_author_substitute<O: OutputFormat>(
     false
 }
 
+////////////////////////
+// Parallel citations //
+////////////////////////
+
+impl<O: OutputFormat> IrTreeRef<'_, O> {
+    /// Finds the first CSL-M `<group is-parallel="true">` that rendered something.
+    pub(crate) fn find_parallel_group(&self) -> Option<NodeId> {
+        self.node.descendants(self.arena).find(|&node| {
+            self.arena.get(node).map_or(false, |n| match n.get() {
+                (IR::Seq(seq), gv) => seq.is_parallel && *gv == GroupVars::Important,
+                _ => false,
+            })
+        })
+    }
+}
+
+impl<O: OutputFormat> IrTree<O> {
+    /// For a cite in a run of parallel citations. Removes everything rendered before and/or after
+    /// the parallel group at `node`, so that what the run has in common (e.g. the case name and
+    /// the year) is only rendered once.
+    pub(crate) fn trim_around_parallel(&mut self, node: NodeId, before: bool, after: bool) {
+        let mut remove = Vec::new();
+        for ancestor in node.ancestors(&self.arena) {
+            if before {
+                remove.extend(ancestor.preceding_siblings(&self.arena).skip(1));
+            }
+            if after {
+                remove.extend(ancestor.following_siblings(&self.arena).skip(1));
+            }
+        }
+        for sibling in remove {
+            sibling.remove_subtree(&mut self.arena);
+        }
+        self.recompute_group_vars();
+    }
+}
+
 ///////////////////////
 // MixedNumericStyle //
 ///////////////////////
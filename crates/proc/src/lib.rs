@@ -47,7 +47,13 @@ mod walker;
 pub use crate::cluster::built_cluster_before_output;
 pub use crate::db::bib_item_preview;
 pub use crate::db::safe_default;
+pub use crate::db::{ghost_cite_disambiguation, GhostDisambiguation};
+pub use crate::ir::DisambPass;
+#[cfg(feature = "debug-ir")]
+pub use crate::ir::debug::IrDebugNode;
+pub use crate::sort::bibliography_sort_keys;
 pub use crate::sort::BibNumber;
+pub use crate::walker::citation_renders_citation_number;
 
 pub(crate) mod prelude {
     pub(crate) trait AsRefOptStr {
@@ -129,7 +135,7 @@ where
     ) -> NodeId;
 }
 
-use csl::{Affixes, DisplayMode, Formatting, Name, NameEtAl, NameLabelInput, Names};
+use csl::{Affixes, DisplayMode, Formatting, Institution, Name, NameEtAl, NameLabelInput, Names};
 use csl::{AnyVariable, DateVariable, NameAsSortOrder, NameVariable, NumberVariable, Variable};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -139,6 +145,17 @@ enum DidSupplyName {
     None,
 }
 
+/// The options a `<names>` element renders with, after inheritance.
+///
+/// Precedence, from highest to lowest:
+///
+/// - The `<names>` element's own attributes, and its `<name>` child's.
+/// - In a `<substitute>`, the `<names>` element being substituted for. Its `<name>` only applies
+///   if the substitute doesn't have one of its own.
+/// - For the `<name>`: `name-*` attributes (like `name-form` and `name-delimiter`) and other
+///   inheritable name attributes on `<citation>` or `<bibliography>`, then on `<style>`. For the
+///   delimiter between name variables: `names-delimiter` on `<citation>` or `<bibliography>`,
+///   then on `<style>`.
 #[derive(Debug, Eq, Clone, PartialEq)]
 pub struct NamesInheritance {
     pub name: Name,
@@ -153,8 +170,8 @@ pub struct NamesInheritance {
     pub affixes: Option<Affixes>,
     // CSL-M: institutions
     // pub with: Option<NameWith>,
-    // CSL-M: institutions
-    // pub institution: Option<Institution>,
+    /// CSL-M: institutions
+    pub institution: Option<Institution>,
 }
 
 use csl::SortKey;
@@ -180,6 +197,7 @@ impl NamesInheritance {
             formatting: other.formatting.or(self.formatting),
             display: other.display.or(self.display),
             affixes: other.affixes.or_else(|| self.affixes.clone()),
+            institution: other.institution.or_else(|| self.institution.clone()),
         }
     }
     fn from_names(ctx_name: &Name, ctx_delim: &Option<SmartString>, names: &Names) -> Self {
@@ -200,6 +218,7 @@ impl NamesInheritance {
             formatting: names.formatting,
             display: names.display,
             affixes: names.affixes.clone(),
+            institution: names.institution.clone(),
         }
     }
     fn from_sort_key(sort_key: &SortKey) -> Self {
@@ -221,6 +240,7 @@ impl NamesInheritance {
             formatting: None,
             display: None,
             affixes: None,
+            institution: None,
         }
     }
 }
@@ -236,6 +256,7 @@ pub struct IrState {
     pub name_override: NameOverrider,
     suppressed: FnvHashSet<AnyVariable>,
     pub disamb_count: u32,
+    pub(crate) cond_cache: crate::choose::CondCache,
 }
 
 #[derive(Default, Debug, PartialEq, Eq, Clone)]
@@ -251,10 +272,14 @@ impl NameOverrider {
         ctx_delim: &Option<SmartString>,
         own_names: &Names,
     ) -> NamesInheritance {
-        let over = NamesInheritance::from_names(ctx_name, ctx_delim, own_names);
         match &self.name_override {
-            None => over,
-            Some(stacked) => stacked.override_with(ctx_name, ctx_delim, over),
+            None => NamesInheritance::from_names(ctx_name, ctx_delim, own_names),
+            Some(stacked) => {
+                // Leave out the context's names-delimiter here, so the parent's delimiter gets a
+                // chance to apply first.
+                let over = NamesInheritance::from_names(ctx_name, &None, own_names);
+                stacked.override_with(ctx_name, ctx_delim, over)
+            }
         }
     }
 
@@ -377,14 +402,20 @@ impl IrState {
         IrState::default()
     }
 
-    pub fn push_macro(&mut self, macro_name: &SmartString) {
+    /// Returns false, and does not push anything, if the macro is already being rendered. Callers
+    /// should then render the macro as empty. Styles with recursive macros are rejected by the
+    /// parser, so this is only a fallback for styles constructed some other way.
+    #[must_use]
+    pub fn push_macro(&mut self, macro_name: &SmartString) -> bool {
         if self.macro_stack.contains(macro_name) {
-            panic!(
-                "foiled macro recursion: {} called from within itself; exiting",
+            warn!(
+                "foiled macro recursion: {} called from within itself; rendering it as empty",
                 macro_name
             );
+            return false;
         }
         self.macro_stack.insert(macro_name.clone());
+        true
     }
 
     pub fn pop_macro(&mut self, macro_name: &SmartString) {
@@ -14,8 +14,9 @@ use crate::NamesInheritance;
 use citeproc_io::utils::Intercalate;
 use citeproc_io::{Name, PersonName, Reference};
 use csl::{
-    Atom, DelimiterPrecedes, DemoteNonDroppingParticle, Name as NameEl, NameAnd, NameAsSortOrder,
-    NameEtAl, NameForm, NamePart, NameVariable, Names, Position,
+    Atom, DelimiterPrecedes, DemoteNonDroppingParticle, Institution, InstitutionUseFirst,
+    Name as NameEl, NameAnd, NameAsSortOrder, NameEtAl, NameForm, NamePart, NameVariable, Names,
+    Position,
 };
 
 mod initials;
@@ -67,6 +68,10 @@ pub fn to_individual_name_irs<'a, O: OutputFormat, I: OutputFormat>(
     let style = ctx.style();
     let locale = ctx.locale();
     let refr = ctx.reference();
+    let institution = names_inheritance
+        .institution
+        .as_ref()
+        .filter(|_| style.features.institutions);
     let get_name_ir = move |(var, label_var, value): (NameVariable, NameVariable, Vec<Name>)| {
         // fullstyles_APA.txt
         let all_same_family_name = disamb::all_same_family_name(&value);
@@ -76,8 +81,21 @@ pub fn to_individual_name_irs<'a, O: OutputFormat, I: OutputFormat>(
             .map(|(n, value)| {
                 // Each variable gets its own 'primary' name.
                 let primary = n == 0;
-                match value {
-                    Name::Person(pn) => DisambNameRatchet::for_person(
+                match (institution, value) {
+                    // A name with no given name is an institution
+                    (
+                        Some(institution),
+                        Name::Person(PersonName {
+                            family: Some(family),
+                            given: None,
+                            is_latin_cyrillic,
+                            ..
+                        }),
+                    ) => DisambNameRatchet::Literal {
+                        literal: fmt.text_node(institution_string(institution, &family), None),
+                        is_latin_cyrillic,
+                    },
+                    (_, Name::Person(pn)) => DisambNameRatchet::for_person(
                         db,
                         var,
                         pn,
@@ -87,10 +105,13 @@ pub fn to_individual_name_irs<'a, O: OutputFormat, I: OutputFormat>(
                         all_same_family_name,
                         advance_to_global,
                     ),
-                    Name::Literal {
-                        literal,
-                        is_latin_cyrillic,
-                    } => {
+                    (
+                        _,
+                        Name::Literal {
+                            literal,
+                            is_latin_cyrillic,
+                        },
+                    ) => {
                         warn!("literal names should be normalised into family-only");
                         DisambNameRatchet::Literal {
                             literal: fmt.text_node(literal, None),
@@ -176,6 +197,32 @@ pub fn to_individual_name_irs<'a, O: OutputFormat, I: OutputFormat>(
         .map(get_name_ir)
 }
 
+/// Renders a CSL-M institution, a name whose subunits are separated by `|`, largest first.
+fn institution_string(institution: &Institution, name: &str) -> SmartString {
+    let mut units: Vec<&str> = name
+        .split('|')
+        .map(str::trim)
+        .filter(|unit| !unit.is_empty())
+        .collect();
+    let len = units.len();
+    let use_first = institution.use_first.map(|uf| match uf {
+        InstitutionUseFirst::Normal(n) | InstitutionUseFirst::Substitute(n) => n as usize,
+    });
+    if use_first.is_some() || institution.use_last.is_some() {
+        let first = use_first.unwrap_or(0).min(len);
+        let last_start = len
+            .saturating_sub(institution.use_last.unwrap_or(0) as usize)
+            .max(first);
+        units.drain(first..last_start);
+    }
+    if institution.reverse_order {
+        units.reverse();
+    }
+    units
+        .join(institution.delimiter.as_deref().unwrap_or(", "))
+        .into()
+}
+
 use crate::sort::Natural;
 use crate::NameOverrider;
 use csl::SortKey;
@@ -740,8 +787,12 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
                     .take(ea_use_first)
                     .intercalate(&NameToken::Delimiter);
                 nms.push(NameToken::Delimiter);
-                nms.push(NameToken::Ellipsis);
-                nms.push(NameToken::Space);
+                // Like the et-al term, the ellipsis is excluded from sort keys; only the last
+                // name itself should affect the order.
+                if !is_sort_key {
+                    nms.push(NameToken::Ellipsis);
+                    nms.push(NameToken::Space);
+                }
                 nms.push(NameToken::Name(last));
                 nms
             } else {
@@ -863,10 +914,18 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
                                     s.push_str(" ");
                                 }
                             }
+                            // [NonDroppingParticle, Family]: "de la" + "Cruz" = "de la Cruz"
+                            if !s.is_empty() && !s.ends_with(' ') && dp_should_append_space(&s) {
+                                s.push_str(" ");
+                            }
                             s.push_str(fam);
                         }
                     }
                     NamePartToken::NonDroppingParticle => {
+                        // [DroppingParticle, NonDroppingParticle]: "de" + "La" = "de La"
+                        if !s.is_empty() && dp_should_append_space(&s) {
+                            s.push_str(" ");
+                        }
                         s.push_str(&pn.non_dropping_particle.as_ref().unwrap());
                     }
                     NamePartToken::DroppingParticle => {
@@ -355,3 +355,39 @@ fn test_initialize_false_period_space() {
     assert_eq!(init("好 好"), "好 好");
     assert_eq!(init("Immel, Ph. M.E."), "Immel, Ph. M. E.")
 }
+
+#[test]
+fn test_initialize_without_hyphens() {
+    fn init<'a>(given_name: &'a str, with: &str) -> Cow<'a, str> {
+        initialize(given_name, true, Some(with), false)
+    }
+    assert_eq!(init("Jean-Luc", "."), "J.L.");
+    assert_eq!(init("Jean-Luc", ". "), "J. L.");
+    assert_eq!(init("Jean-Luc K", "."), "J.L.K.");
+    assert_eq!(init("Jean-luc", "."), "J.");
+}
+
+#[test]
+fn test_initialize_false_empty() {
+    fn init(given_name: &str) -> Cow<'_, str> {
+        initialize(given_name, false, Some(""), true)
+    }
+    assert_eq!(init("ME"), "ME");
+    assert_eq!(init("A. Alan"), "A Alan");
+    assert_eq!(init("John R L"), "John RL");
+    assert_eq!(init("R. L."), "RL");
+    assert_eq!(init("John R L de Bortoli"), "John RL de Bortoli");
+}
+
+#[test]
+fn test_initialize_true_period_nbsp() {
+    fn init(given_name: &str) -> Cow<'_, str> {
+        initialize(given_name, true, Some(".\u{a0}"), true)
+    }
+    assert_eq!(init("ME"), "M.");
+    assert_eq!(init("John R L"), "J.\u{a0}R.\u{a0}L.");
+    assert_eq!(init("Jean-Luc K"), "J.-L.\u{a0}K.");
+    assert_eq!(init("R. L."), "R.\u{a0}L.");
+    // particles and other non-initials are still separated by a plain space
+    assert_eq!(init("John R L de Bortoli"), "J.\u{a0}R.\u{a0}L. de B.");
+}
@@ -43,6 +43,7 @@ impl<'a, O: OutputFormat, I: OutputFormat> RenderContext for GenericContext<'a,
         fn cite_lang(&self) -> Option<&Lang>;
         fn get_number(&self, var: NumberVariable) -> Option<NumericValue<'_>>;
         fn get_ordinary(&self, var: Variable, form: VariableForm) -> Option<Cow<'_, str>>;
+        fn locator_extra(&self) -> Option<&str>;
         fn get_name(&self, var: NameVariable) -> Option<&[Name]>;
     }
 }
@@ -56,6 +57,8 @@ impl<'a, O: OutputFormat, I: OutputFormat> CondChecker for GenericContext<'a, O,
         fn locator_type(&self) -> Option<LocatorType>;
         fn get_date(&self, dvar: DateVariable) -> Option<&DateOrRange>;
         fn position(&self) -> Option<Position>;
+        fn is_plural(&self, var: NameVariable) -> bool;
+        fn cite_or_bib(&self) -> CiteOrBib;
         fn features(&self) -> &Features;
         fn has_year_only(&self, dvar: DateVariable) -> bool;
         fn has_month_or_season(&self, dvar: DateVariable) -> bool;
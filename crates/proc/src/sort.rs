@@ -69,19 +69,12 @@ impl BibNumber {
     }
 }
 
-pub fn sorted_refs(db: &dyn IrDatabase) -> Arc<(Vec<Atom>, FnvHashMap<Atom, BibNumber>)> {
-    let style = db.style();
-    let bib = match style.bibliography {
-        None => None,
-        Some(ref b) => b.sort.as_ref(),
-    };
-
+/// All refs from all cites, in the order they appear (excluding non-existent), then all of the
+/// uncited reference ids; and the citation number each has in that order. This is the input to
+/// the bibliography sort, and the numbering its `citation-number` keys use.
+fn preordered_refs(db: &dyn IrDatabase) -> (Vec<Atom>, FnvHashMap<Atom, BibNumber>) {
     let mut citation_numbers = FnvHashMap::default();
 
-    // Construct preordered, which will then be stably sorted. It contains:
-    // - All refs from all cites, in the order they appear (excluding non-existent)
-    // - Then, all of the uncited reference ids.
-    //
     // first, compute refs in the order that they are cited.
     // stable sorting will cause this to be the final tiebreaker.
     let all = db.all_keys();
@@ -106,6 +99,48 @@ pub fn sorted_refs(db: &dyn IrDatabase) -> Arc<(Vec<Atom>, FnvHashMap<Atom, BibN
             i += 1;
         }
     }
+    (preordered, citation_numbers)
+}
+
+/// The bibliography sort keys for one reference, given its pre-sort citation number.
+fn bib_sort_items(
+    db: &dyn IrDatabase,
+    id: &Atom,
+    cnum: BibNumber,
+    sort: &Sort,
+    max_cnum: u32,
+) -> Option<Demoting> {
+    let refr_arc = db.reference(id.clone());
+    with_bib_context(
+        db,
+        id.clone(),
+        refr_arc.as_deref(),
+        cnum.cited_only(),
+        None,
+        None,
+        |_, mut ctx| {
+            Some(ctx_sort_items(
+                db,
+                CiteOrBib::Bibliography,
+                &mut ctx,
+                cnum,
+                sort,
+                max_cnum,
+            ))
+        },
+        |_, _, _| None,
+    )
+}
+
+pub fn sorted_refs(db: &dyn IrDatabase) -> Arc<(Vec<Atom>, FnvHashMap<Atom, BibNumber>)> {
+    let style = db.style();
+    let bib = match style.bibliography {
+        None => None,
+        Some(ref b) => b.sort.as_ref(),
+    };
+
+    // Construct preordered, which will then be stably sorted.
+    let (mut preordered, mut citation_numbers) = preordered_refs(db);
 
     let max_cnum = preordered.len() as u32;
     let mut reverse = false;
@@ -117,26 +152,7 @@ pub fn sorted_refs(db: &dyn IrDatabase) -> Arc<(Vec<Atom>, FnvHashMap<Atom, BibN
                 .get(a)
                 .expect("must have an citation_number entry for every bibliography item")
                 .clone();
-            let refr_arc = db.reference(a.clone());
-            let demoting = with_bib_context(
-                db,
-                a.clone(),
-                refr_arc.as_deref(),
-                a_cnum.cited_only(),
-                None,
-                None,
-                |_, mut a_ctx| {
-                    Some(ctx_sort_items(
-                        db,
-                        CiteOrBib::Bibliography,
-                        &mut a_ctx,
-                        a_cnum,
-                        sort,
-                        max_cnum,
-                    ))
-                },
-                |_, _, _| None,
-            );
+            let demoting = bib_sort_items(db, a, a_cnum, sort, max_cnum);
             log::debug!("(Bibliography) sort items for {:?}: {:?}", a_cnum, demoting);
             if let Some(Demoting {
                 fake_cnum: Some(_), ..
@@ -167,15 +183,46 @@ pub fn sorted_refs(db: &dyn IrDatabase) -> Arc<(Vec<Atom>, FnvHashMap<Atom, BibN
     Arc::new((now_sorted, citation_numbers))
 }
 
+/// Each reference in the bibliography, in bibliography order, with the sort key components
+/// computed for it by the style's `<bibliography><sort>`. This is for debugging sorting: the
+/// strings are readable versions of what is compared, not what the comparison uses directly.
+/// Missing values are empty strings. If the bibliography has no sort, every list is empty.
+///
+/// A `citation-number` key shows the number the sort compared, i.e. the order of first citation,
+/// not the number the reference is rendered with afterwards.
+pub fn bibliography_sort_keys(db: &dyn IrDatabase) -> Vec<(Atom, Vec<SmartString>)> {
+    let style = db.style();
+    let sort = style.bibliography.as_ref().and_then(|b| b.sort.as_ref());
+    let sorted_refs = db.sorted_refs();
+    let (ids, _) = &*sorted_refs;
+    let (_, citation_numbers) = preordered_refs(db);
+    let max_cnum = ids.len() as u32;
+    ids.iter()
+        .map(|id| {
+            let keys = sort.map_or_else(Vec::new, |sort| {
+                let cnum = *citation_numbers
+                    .get(id)
+                    .expect("must have an citation_number entry for every bibliography item");
+                bib_sort_items(db, id, cnum, sort, max_cnum).map_or_else(Vec::new, |d| {
+                    d.items.iter().map(|item| item.value.readable()).collect()
+                })
+            });
+            (id.clone(), keys)
+        })
+        .collect()
+}
+
 pub fn clusters_cites_sorted(db: &dyn IrDatabase) -> Arc<Vec<ClusterData>> {
     let cluster_ids = db.clusters_ordered();
-    let mut clusters: Vec<_> = cluster_ids
+    // clusters_ordered is already in document order. Don't sort by ClusterNumber, whose Ord puts
+    // every in-text cluster before every note cluster; in a mixed document they are interleaved,
+    // and an in-text cluster between two notes must interrupt ibid.
+    let clusters: Vec<_> = cluster_ids
         .iter()
         // No number? Not considered to be in document, position participant.
         // Although may be disamb participant.
         .filter_map(|&id| db.cluster_data_sorted(id))
         .collect();
-    clusters.sort_by_key(|cluster| cluster.number);
     Arc::new(clusters)
 }
 
@@ -261,6 +308,71 @@ enum SortValue {
     Date(Option<DateOrRange>),
 }
 
+impl SortValue {
+    /// A readable version of the value for [bibliography_sort_keys]. Dates use the spec's
+    /// `YYYYMMDD` form.
+    fn readable(&self) -> SmartString {
+        let mut s = SmartString::new();
+        match self {
+            SortValue::Macro(Some(nat)) => s.extend(nat.as_str().chars().filter(|c| {
+                !(natural_sort::DATE_START..=natural_sort::CITATION_NUM_END).contains(c)
+            })),
+            SortValue::Cnum(Some(cnum)) => s.push_str(&cnum.to_string()),
+            SortValue::OrdinaryVariable(Some(nat)) => s.push_str(nat.as_str()),
+            SortValue::Number(Some(num)) => match num {
+                citeproc_io::NumericValueOwned::Tokens(tokens) => {
+                    for token in tokens {
+                        readable_numeric_token(&mut s, token)
+                    }
+                }
+                citeproc_io::NumericValueOwned::Str(string) => s.push_str(string),
+            },
+            SortValue::Names(Some(names)) => {
+                for (i, name) in names.iter().enumerate() {
+                    if i > 0 {
+                        s.push_str(", ");
+                    }
+                    s.push_str(name.as_str());
+                }
+            }
+            SortValue::Date(Some(date)) => match date {
+                DateOrRange::Single(d) => readable_date(&mut s, d),
+                DateOrRange::Range(d1, d2) => {
+                    readable_date(&mut s, d1);
+                    s.push('/');
+                    readable_date(&mut s, d2);
+                }
+                DateOrRange::Literal { literal, .. } => s.push_str(literal),
+            },
+            _ => {}
+        }
+        s
+    }
+}
+
+fn readable_numeric_token(s: &mut SmartString, token: &citeproc_io::NumericToken) {
+    use citeproc_io::NumericToken::*;
+    match token {
+        Num(n) | Roman(n, _) => s.push_str(&n.to_string()),
+        Affixed(pre, n, suf) => {
+            s.push_str(pre);
+            s.push_str(&n.to_string());
+            s.push_str(suf);
+        }
+        Str(string) => s.push_str(string),
+        Comma => s.push(','),
+        Hyphen => s.push('-'),
+        Ampersand => s.push('&'),
+        And => s.push_str(" and "),
+        CommaAnd => s.push_str(", and "),
+    }
+}
+
+fn readable_date(s: &mut SmartString, date: &citeproc_io::Date) {
+    use std::fmt::Write;
+    let _ = write!(s, "{:04}{:02}{:02}", date.year, date.month, date.day);
+}
+
 use std::cmp::Ordering;
 #[derive(Debug)]
 enum Demoted {
@@ -404,9 +516,17 @@ fn ctx_sort_items(
                     fn strip_markup(s: impl AsRef<str>) -> SmartString {
                         micro_html_to_string(s.as_ref(), &Default::default())
                     }
+                    let noise_words = a_ctx.style.features.leading_noise_words && v.is_title();
                     let got = a_ctx
                         .get_ordinary(v, VariableForm::default())
                         .map(strip_markup)
+                        .map(|s| {
+                            if noise_words {
+                                a_ctx.locale.strip_leading_noise_word(&s).into()
+                            } else {
+                                s
+                            }
+                        })
                         .map(Natural::new);
                     SortValue::OrdinaryVariable(got)
                 }
@@ -542,10 +662,14 @@ impl<'a, O: OutputFormat> StyleWalker for SortingWalker<'a, O> {
                     renderer.text_variable(text, svar, nval.verbatim())
                 }
             }),
-            StandardVariable::Ordinary(var) => self
-                .ctx
-                .get_ordinary(var, form)
-                .map(|val| renderer.text_variable(text, svar, &val)),
+            StandardVariable::Ordinary(var) => self.ctx.get_ordinary(var, form).map(|val| {
+                if self.ctx.style.features.leading_noise_words && var.is_title() {
+                    let stripped = self.ctx.locale.strip_leading_noise_word(&val);
+                    renderer.text_variable(text, svar, stripped)
+                } else {
+                    renderer.text_variable(text, svar, &val)
+                }
+            }),
         };
         let gv = GroupVars::rendered_if(res.is_some());
         (res.unwrap_or_default(), gv)
@@ -624,7 +748,9 @@ impl<'a, O: OutputFormat> StyleWalker for SortingWalker<'a, O> {
             .get(name)
             .expect("undefined macro should not be valid CSL");
 
-        self.state.push_macro(name);
+        if !self.state.push_macro(name) {
+            return self.fold(&[], WalkerFoldType::Macro(text));
+        }
         let ret = self.fold(macro_elements, WalkerFoldType::Macro(text));
         self.state.pop_macro(name);
         ret
@@ -44,6 +44,9 @@ impl<S: AsRef<str>> Natural<S> {
     pub(crate) fn new(inner: S) -> Self {
         Natural(inner)
     }
+    pub(crate) fn as_str(&self) -> &str {
+        self.0.as_ref()
+    }
 }
 impl<S: AsRef<str>> Eq for Natural<S> {}
 impl<S: AsRef<str>> PartialEq for Natural<S> {
@@ -276,6 +276,9 @@ impl NaturalCmp {
             Some(NaturalCmp(s))
         }
     }
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 impl PartialOrd for NaturalCmp {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -132,3 +132,70 @@ pub trait StyleWalker {
         }
     }
 }
+
+/// Whether the citation layout (or any of its locale-specific layouts) can render
+/// `citation-number`, in any branch of a `<choose>` and through any macro. This is how numeric
+/// styles are recognised, as CSL has no class for them.
+pub fn citation_renders_citation_number(style: &Style) -> bool {
+    let mut walker = CitationNumberWalker {
+        style,
+        seen_macros: Vec::new(),
+    };
+    walker.walk_citation(style)
+        || style
+            .citation
+            .locale_layouts
+            .iter()
+            .any(|layout| walker.layout(layout))
+}
+
+struct CitationNumberWalker<'a> {
+    style: &'a Style,
+    seen_macros: Vec<&'a SmartString>,
+}
+
+impl<'a> StyleWalker for CitationNumberWalker<'a> {
+    type Output = bool;
+    type Checker = crate::choose::UselessCondChecker;
+
+    fn default(&mut self) -> Self::Output {
+        false
+    }
+    fn fold(&mut self, elements: &[Element], _fold_type: WalkerFoldType) -> Self::Output {
+        elements.iter().any(|el| self.element(el))
+    }
+    fn choose(&mut self, choose: &Choose) -> Self::Output {
+        let Choose(head, rest, last) = choose;
+        std::iter::once(head)
+            .chain(rest.iter())
+            .any(|branch| self.fold(&branch.1, WalkerFoldType::IfThen))
+            || self.fold(&last.0, WalkerFoldType::Else)
+    }
+    fn text_variable(
+        &mut self,
+        _text: &TextElement,
+        svar: StandardVariable,
+        _form: VariableForm,
+    ) -> Self::Output {
+        svar == StandardVariable::Number(NumberVariable::CitationNumber)
+    }
+    fn number(&mut self, number: &NumberElement) -> Self::Output {
+        number.variable == NumberVariable::CitationNumber
+    }
+    fn text_macro(&mut self, text: &TextElement, name: &SmartString) -> Self::Output {
+        let style = self.style;
+        // Each macro only needs walking once, which also stops recursive macros.
+        match style.macros.get_key_value(name) {
+            Some((key, elements)) if !self.seen_macros.contains(&key) => {
+                self.seen_macros.push(key);
+                self.fold(elements, WalkerFoldType::Macro(text))
+            }
+            _ => false,
+        }
+    }
+    fn names(&mut self, names: &Names) -> Self::Output {
+        names.substitute.as_ref().map_or(false, |subst| {
+            self.fold(&subst.0, WalkerFoldType::Substitute)
+        })
+    }
+}
@@ -65,6 +65,19 @@ describe("Driver", () => {
                 .toBe("Italicised https://google.com");
         })
     });
+
+    test("returns the clusterOrder that was set", () => {
+        withDriver({}, driver => {
+            driver.insertReference({ id: "citekey", type: "book", title: "TEST_TITLE" });
+            driver.initClusters([
+                { id: "one", cites: [{ id: "citekey" }] },
+                { id: "two", cites: [{ id: "citekey" }] },
+            ]);
+            const order = [{ id: "two", note: 1 }, { id: "one" }];
+            driver.setClusterOrder(order);
+            expect(driver.clusterOrder()).toEqual(order);
+        });
+    });
 });
 
 describe("batchedUpdates", () => {
@@ -97,6 +110,19 @@ describe("batchedUpdates", () => {
         })
     });
 
+    test('setBibliographyExclude drops entries but keeps cites', () => {
+        withDriver({ style: bibStyle }, driver => {
+            oneOneOne(driver);
+            driver.batchedUpdates();
+            driver.setBibliographyExclude(["citekey"]);
+            let up = driver.batchedUpdates();
+            expect(up.bibliography?.entryIds).toEqual([]);
+            let full = driver.fullRender();
+            expect(full.allClusters).toHaveProperty("one", "TEST_TITLE");
+            expect(full.bibEntries).toEqual([]);
+        })
+    });
+
     test('update queue generally', () => {
         withDriver({ style: bibStyle }, driver => {
             let once: UpdateSummary, twice: UpdateSummary;
@@ -22,6 +22,8 @@ pub enum Error {
     UnknownOutputFormat(String),
     #[error("Unknown CSL feature {0:?}")]
     UnknownCSLFeature(String),
+    #[error("Unknown reference type {0:?}")]
+    UnknownReferenceType(String),
     #[error("JSON Deserialization Error: {0}")]
     JsonError(
         #[from]
@@ -32,6 +34,8 @@ pub enum Error {
     GetFetcherError(#[from] GetFetcherError),
     #[error("Non-Existent Cluster id: {0}")]
     NonExistentCluster(String),
+    #[error("Invalid language tag {0:?}")]
+    InvalidLanguageTag(String),
     #[error("Reordering error: {0}")]
     ReorderingError(
         #[from]
@@ -135,6 +139,9 @@ type StyleError = {
 type DriverError = {
     tag: "UnknownOutputFormat",
     content: string,
+} | {
+    tag: "UnknownReferenceType",
+    content: string,
 } | {
     tag: "JsonError",
 } | {
@@ -142,6 +149,9 @@ type DriverError = {
 } | {
     tag: "NonExistentCluster",
     content: string,
+} | {
+    tag: "InvalidLanguageTag",
+    content: string,
 } | {
     tag: "ReorderingError"
 } | {
@@ -30,7 +30,7 @@ use wasm_bindgen_futures::{future_to_promise, JsFuture};
 
 use citeproc::prelude::*;
 use citeproc::string_id;
-use csl::{Lang, StyleMeta};
+use csl::{GetAttribute, Lang, StyleMeta};
 
 /// Parses a CSL style, either independent or dependent, and returns its metadata.
 #[wasm_bindgen]
@@ -51,7 +51,7 @@ impl Driver {
     ///
     /// * `style` is a CSL style as a string. Independent styles only.
     /// * `fetcher` must implement the `Fetcher` interface
-    /// * `format` is one of { "html", "rtf", "plain" }
+    /// * `format` is one of { "html", "rtf", "plain", "jats", "test-html" }
     ///
     /// Throws an error if it cannot parse the style you gave it.
     #[wasm_bindgen(constructor)]
@@ -66,13 +66,24 @@ impl Driver {
         let csl_features =
             csl::version::read_features(options.csl_features.iter().map(|x| x.as_str()))
                 .map_err(|x| Error::UnknownCSLFeature(x.to_owned()))?;
+        let unknown_type_fallback = options
+            .unknown_type_fallback
+            .map(|t| {
+                csl::CslType::get_attr(&t, &csl_features)
+                    .map_err(|_| Error::UnknownReferenceType(t))
+            })
+            .transpose()?;
         let init = InitOptions {
             style: options.style.as_ref(),
             fetcher: Some(us_fetcher),
             format: options.format,
             format_options: options.format_options,
             bibliography_no_sort: options.bibliography_no_sort,
+            year_suffix_alphabet: options
+                .year_suffix_alphabet
+                .map(|alphabet| alphabet.chars().collect()),
             locale_override: options.locale_override,
+            unknown_type_fallback,
             test_mode: false,
             csl_features: Some(csl_features),
             ..Default::default()
@@ -98,7 +109,7 @@ impl Driver {
 
     /// Sets the output format (which will also cause everything to be recomputed, use sparingly)
     ///
-    /// @param {"html" | "rtf" | "plain"} format The new output format as a string, same as `new Driver`
+    /// @param {"html" | "rtf" | "plain" | "jats" | "test-html"} format The new output format as a string, same as `new Driver`
     ///
     /// @param {FormatOptions | null} options If absent, this is set to the default FormatOptions.
     ///
@@ -122,6 +133,25 @@ impl Driver {
         Ok(())
     }
 
+    /// Overrides the style's default locale (which will also cause everything to be recomputed,
+    /// use sparingly). Pass `null` to go back to the style's `default-locale`.
+    ///
+    /// If the locale has not been fetched yet, it will be listed by `toFetch()`; you can use
+    /// `fetchLocales()` to get it.
+    ///
+    /// @param {string | null} lang A language tag like `"de-AT"`
+    #[wasm_bindgen(js_name = "setLocaleOverride")]
+    pub fn set_locale_override(&self, lang: Option<String>) -> Result<(), Error> {
+        let lang = lang
+            .map(|tag| {
+                tag.parse::<Lang>()
+                    .map_err(|_| Error::InvalidLanguageTag(tag.clone()))
+            })
+            .transpose()?;
+        self.engine.borrow_mut().set_locale_override(lang);
+        Ok(())
+    }
+
     /// Completely overwrites the references library.
     /// This **will** delete references that are not in the provided list.
     #[wasm_bindgen(js_name = "resetReferences")]
@@ -170,6 +200,19 @@ impl Driver {
         Ok(())
     }
 
+    /// Leaves these references out of the bibliography, even if they are cited. Their cites still
+    /// render. Replaces any previous list.
+    ///
+    /// * `ids` is a string[]
+    #[wasm_bindgen(js_name = "setBibliographyExclude")]
+    pub fn set_bibliography_exclude(&self, ids: typescript::StringArray) -> Result<(), Error> {
+        let ids: Vec<String> = ids.into_serde()?;
+        self.engine
+            .borrow_mut()
+            .set_bibliography_exclude(ids.iter().map(String::as_str).map(Atom::from).collect());
+        Ok(())
+    }
+
     /// Gets a list of locales in use by the references currently loaded.
     ///
     /// Note that Driver comes pre-loaded with the `en-US` locale.
@@ -242,7 +285,7 @@ impl Driver {
     ) -> Result<String, Error> {
         let cites = utils::read_js_array_2(cites)?;
         self.preview_cluster_inner(PreviewCluster::new(cites, None), positions, format)
-            .map(|arc| arc.to_string())
+            .map(|preview| preview.output.to_string())
     }
 
     /// Previews a formatted citation cluster, in a particular position.
@@ -264,7 +307,23 @@ impl Driver {
     ) -> Result<String, Error> {
         let preview_cluster: PreviewCluster = preview_cluster.into_serde()?;
         self.preview_cluster_inner(preview_cluster, positions, format)
-            .map(|arc| arc.to_string())
+            .map(|preview| preview.output.to_string())
+    }
+
+    /// Like `previewCluster`, but also lists the kinds of disambiguation the previewed cites
+    /// needed, e.g. to hint to a user that their cite is ambiguous.
+    ///
+    /// * returns a `ClusterPreview`
+    #[wasm_bindgen(js_name = "previewClusterWithDisambiguation")]
+    pub fn preview_cluster_with_disambiguation(
+        &self,
+        preview_cluster: typescript::PreviewCluster,
+        positions: Box<[JsValue]>,
+        format: Option<String>,
+    ) -> Result<typescript::ClusterPreview, Error> {
+        let preview_cluster: PreviewCluster = preview_cluster.into_serde()?;
+        self.preview_cluster_inner(preview_cluster, positions, format)?
+            .serialize_jsvalue()
     }
 
     fn preview_cluster_inner(
@@ -272,10 +331,10 @@ impl Driver {
         preview_cluster: PreviewCluster,
         positions: Box<[JsValue]>,
         format: Option<String>,
-    ) -> Result<String, Error> {
+    ) -> Result<ClusterPreview, Error> {
         let positions: Vec<string_id::ClusterPosition> = utils::read_js_array_2(positions)?;
         let mut eng = self.engine.borrow_mut();
-        let preview = eng.preview_citation_cluster(
+        let preview = eng.preview_cluster(
             preview_cluster,
             PreviewPosition::MarkWithZeroStr(&positions),
             format
@@ -285,7 +344,7 @@ impl Driver {
                 })
                 .transpose()?,
         )?;
-        Ok(preview.to_string())
+        Ok(preview)
     }
 
     #[wasm_bindgen(js_name = "makeBibliography")]
@@ -302,6 +361,167 @@ impl Driver {
         meta.serialize_jsvalue()
     }
 
+    /// Counts the clusters that have been positioned in the document, the cites within them, and
+    /// the bibliography entries. Useful for progress indicators.
+    ///
+    /// * returns a `DocumentStats`
+    #[wasm_bindgen(js_name = "documentStats")]
+    pub fn document_stats(&self) -> Result<typescript::DocumentStats, Error> {
+        let eng = self.engine.borrow();
+        let stats = eng.document_stats();
+        stats.serialize_jsvalue()
+    }
+
+    /// Returns the style's `default-locale`, `page-range-format` and
+    /// `demote-non-dropping-particle` settings, for displaying style configuration.
+    ///
+    /// * returns a `StyleDefaults`
+    #[wasm_bindgen(js_name = "styleDefaults")]
+    pub fn style_defaults(&self) -> Result<typescript::StyleDefaults, Error> {
+        let eng = self.engine.borrow();
+        eng.style_defaults().serialize_jsvalue()
+    }
+
+    /// Returns the style's class, `"note"` or `"in-text"`.
+    ///
+    /// * returns a `StyleClass`
+    #[wasm_bindgen(js_name = "styleClass")]
+    pub fn style_class(&self) -> Result<typescript::StyleClass, Error> {
+        let eng = self.engine.borrow();
+        eng.style_class().serialize_jsvalue()
+    }
+
+    /// Returns true if the style's citations can render `citation-number`, i.e. it is a numeric
+    /// style.
+    #[wasm_bindgen(js_name = "isNumericStyle")]
+    pub fn is_numeric_style(&self) -> bool {
+        let eng = self.engine.borrow();
+        eng.is_numeric_style()
+    }
+
+    /// Returns the position (`"first"`, `"ibid"`, `"subsequent"`, etc) computed for each cite in
+    /// the cluster, in order. Empty if the cluster has not been assigned a position in the
+    /// document.
+    ///
+    /// * returns a `CitePosition[]`
+    #[wasm_bindgen(js_name = "clusterCitePositions")]
+    pub fn cluster_cite_positions(&self, id: &str) -> Result<typescript::CitePositions, Error> {
+        let eng = self.engine.borrow();
+        let positions: Vec<String> = eng
+            .cluster_cite_positions_str(id)
+            .iter()
+            .map(|position| position.as_ref().to_owned())
+            .collect();
+        positions.serialize_jsvalue()
+    }
+
+    /// Renders only the author part of a cluster, as the `AuthorOnly` cluster mode would, without
+    /// changing the cluster's mode. Useful for narrative citations, where the author is written
+    /// into the running text before the cite. Returns `undefined` if the cluster has not been
+    /// positioned, or has no author to render.
+    #[wasm_bindgen(js_name = "clusterAuthorPrefix")]
+    pub fn cluster_author_prefix(&self, id: &str) -> Option<String> {
+        let eng = self.engine.borrow();
+        eng.cluster_author_prefix_str(id)
+            .map(|prefix| prefix.to_string())
+    }
+
+    /// Returns the footnote number the cluster was placed in by `setClusterOrder`. Clusters merged
+    /// into a single footnote share a number. Returns `undefined` for in-text clusters and
+    /// clusters that have not been positioned.
+    #[wasm_bindgen(js_name = "clusterNoteNumber")]
+    pub fn cluster_note_number(&self, id: &str) -> Option<u32> {
+        let eng = self.engine.borrow();
+        eng.get_note_number_str(id)
+    }
+
+    /// Returns the clusters in the document, in the order last given to `setClusterOrder`, with
+    /// their note numbers. Clusters removed since then are left out.
+    ///
+    /// * returns a `ClusterPosition[]`
+    #[wasm_bindgen(js_name = "clusterOrder")]
+    pub fn cluster_order(&self) -> Result<typescript::ClusterOrder, Error> {
+        let eng = self.engine.borrow();
+        eng.get_cluster_order_str().serialize_jsvalue()
+    }
+
+    /// Lists the cites in positioned clusters whose `id` does not match any reference that has
+    /// been inserted, in document order.
+    ///
+    /// * returns an `UnresolvedCite[]`
+    #[wasm_bindgen(js_name = "unresolvedCites")]
+    pub fn unresolved_cites(&self) -> Result<typescript::UnresolvedCites, Error> {
+        let eng = self.engine.borrow();
+        let unresolved = eng.unresolved_cites_str();
+        unresolved.serialize_jsvalue()
+    }
+
+    /// Maps each cited reference id to the note numbers of the clusters citing it, ascending, for
+    /// "cited in notes 2, 5" back-references. Only clusters positioned as notes are counted.
+    ///
+    /// * returns a `BackReferences`
+    #[wasm_bindgen(js_name = "backReferences")]
+    pub fn back_references(&self) -> Result<typescript::BackReferences, Error> {
+        let eng = self.engine.borrow();
+        eng.back_references().serialize_jsvalue()
+    }
+
+    /// Lists the references whose CSL-JSON `type` was not recognised, with that type. These are
+    /// rendered as if they had the `unknownTypeFallback` type from the init options, which
+    /// defaults to `document` (or `article` for styles declaring `version="1.0"`).
+    ///
+    /// * returns an `UnknownReferenceType[]`
+    #[wasm_bindgen(js_name = "unknownReferenceTypes")]
+    pub fn unknown_reference_types(&self) -> Result<typescript::UnknownReferenceTypes, Error> {
+        let eng = self.engine.borrow();
+        eng.unknown_reference_types().serialize_jsvalue()
+    }
+
+    /// Lists the ids of references whose bibliography entries render to nothing under the
+    /// current style, in bibliography order.
+    ///
+    /// * returns a `string[]`
+    #[wasm_bindgen(js_name = "emptyRenderingIds")]
+    pub fn empty_rendering_ids(&self) -> Result<typescript::StringArray, Error> {
+        let eng = self.engine.borrow();
+        let ids: Vec<_> = eng
+            .empty_rendering_ids()
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        ids.serialize_jsvalue()
+    }
+
+    /// Lists each reference in the bibliography, in order, with the sort key components computed
+    /// for it by the style's bibliography `<sort>`. For debugging sorting.
+    ///
+    /// * returns a `[string, string[]][]`
+    #[wasm_bindgen(js_name = "bibliographySortKeys")]
+    pub fn bibliography_sort_keys(&self) -> Result<typescript::BibliographySortKeys, Error> {
+        let eng = self.engine.borrow();
+        let keys: Vec<(String, Vec<String>)> = eng
+            .bibliography_sort_keys()
+            .into_iter()
+            .map(|(id, keys)| {
+                let keys = keys.iter().map(|k| k.to_string()).collect();
+                (id.to_string(), keys)
+            })
+            .collect();
+        keys.serialize_jsvalue()
+    }
+
+    /// Maps each disambiguation token (a piece of output a cite could render) to the ids of the
+    /// references that can produce it. Only cited references, and any included as uncited, take
+    /// part.
+    ///
+    /// * returns a `DisambiguationIndex`
+    #[wasm_bindgen(js_name = "disambiguationIndex")]
+    pub fn disambiguation_index(&self) -> Result<typescript::DisambiguationIndex, Error> {
+        let eng = self.engine.borrow();
+        let index = eng.disambiguation_index();
+        index.serialize_jsvalue()
+    }
+
     /// Specifies which clusters are actually considered to be in the document, and sets their
     /// order. You may insert as many clusters as you like, but the ones provided here are the only
     /// ones used.
@@ -363,6 +583,38 @@ impl Driver {
         all.serialize_jsvalue()
     }
 
+    /// Renders every positioned cluster in document order, followed by the bibliography, into a
+    /// single HTML fragment with a `<style>` element for the bibliography's layout options. Use
+    /// this for one-shot exports. Always HTML, whatever the driver's output format. Does not drain
+    /// the queue.
+    #[wasm_bindgen(js_name = "renderFullHtml")]
+    pub fn render_full_html(&self) -> String {
+        let eng = self.engine.borrow();
+        eng.render_full_html()
+    }
+
+    /// Captures the references, clusters, cluster order and uncited references, so a document can
+    /// be persisted (e.g. for crash recovery) and restored later with `importState`. Unlike
+    /// `fullRender`, this contains the inputs rather than any output.
+    ///
+    /// * returns a `DocumentState`
+    #[wasm_bindgen(js_name = "exportState")]
+    pub fn export_state(&self) -> Result<typescript::DocumentState, Error> {
+        let eng = self.engine.borrow();
+        eng.export_state().serialize_jsvalue()
+    }
+
+    /// Replaces the references, clusters, cluster order and uncited references with those from
+    /// an `exportState` snapshot.
+    ///
+    /// * `state` is a `DocumentState`
+    #[wasm_bindgen(js_name = "importState")]
+    pub fn import_state(&self, state: typescript::DocumentState) -> Result<(), Error> {
+        let state: string_id::DocumentState = state.into_serde()?;
+        self.engine.borrow_mut().import_state(state)?;
+        Ok(())
+    }
+
     /// Drains the `batchedUpdates` queue manually.
     #[wasm_bindgen(js_name = "drain")]
     pub fn drain(&self) {
@@ -9,6 +9,15 @@ use wasm_bindgen::prelude::*;
 pub(crate) struct JsFormatOptions {
     #[serde(default = "bool_true")]
     link_anchors: bool,
+    #[serde(default)]
+    cite_anchors: bool,
+    /// Set by the `"test-html"` format instead
+    #[serde(skip)]
+    test_escaping: bool,
+    #[serde(default)]
+    bib_inline_styles: bool,
+    #[serde(default)]
+    cluster_data_attributes: bool,
 }
 
 fn bool_true() -> bool {
@@ -47,6 +56,12 @@ pub struct WasmInitOptions {
     /// Disables sorting on the bibliography
     #[serde(default)]
     pub bibliography_no_sort: bool,
+    /// Characters to count year suffixes with
+    #[serde(default)]
+    pub year_suffix_alphabet: Option<String>,
+    /// The type to render references with an unrecognised CSL-JSON `type` as
+    #[serde(default)]
+    pub unknown_type_fallback: Option<String>,
 }
 
 #[wasm_bindgen]
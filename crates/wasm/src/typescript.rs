@@ -90,6 +90,15 @@ typescript_deserialize!(
 const TS_APPEND_CONTENT_1: &'static str = r#"
 interface FormatOptions {
     linkAnchors?: boolean;
+    /** HTML only. Link each cite to its bibliography entry, which gets a matching
+      * `<a id="ref-{id}"></a>` anchor at its start. Default false. */
+    citeAnchors?: boolean;
+    /** HTML only. Put the bibliography's hanging indent and spacing in `style` attributes on
+      * the `markupPre` and `entryPre` divs of `bibliographyMeta()`. Default false. */
+    bibInlineStyles?: boolean;
+    /** HTML only. Wrap each cluster in a span with `data-cluster-id` and `data-cites` (its
+      * cites' reference ids, comma-separated) attributes. Default false. */
+    clusterDataAttributes?: boolean;
 }
 
 interface InitOptions {
@@ -103,7 +112,7 @@ interface InitOptions {
     fetcher?: Fetcher;
 
     /** The output format for this driver instance (default: html) */
-    format?: "html" | "rtf" | "plain";
+    format?: "html" | "rtf" | "plain" | "jats" | "test-html";
     /** Configuration for the formatter */
     formatOptions?: FormatOptions;
 
@@ -119,6 +128,16 @@ interface InitOptions {
 
     /** Disables sorting in the bibliography; items appear in cited order. */
     bibliographyNoSort?: boolean;
+
+    /** Characters to use for disambiguating year suffixes, instead of "abc...z". Past the last
+      * character, suffixes continue with two characters, like "aa", "ab".
+      */
+    yearSuffixAlphabet?: string;
+
+    /** The type to render references with an unrecognised CSL-JSON `type` as, e.g. "report".
+      * Defaults to "document", or "article" for styles declaring `version="1.0"`.
+      */
+    unknownTypeFallback?: string;
 }
 
 /** This interface lets citeproc retrieve locales or modules asynchronously,
@@ -179,11 +198,103 @@ interface BibliographyMeta {
     hangingIndent: boolean;
     /** the second-field-align value of the CSL style */
     secondFieldAlign: null  | "flush" | "margin";
-    /** Format-specific metadata */
+    /** Format-specific metadata, e.g. markupPre/markupPost around the whole bibliography and
+     * entryPre/entryPost around each entry */
     formatMeta: any;
 }
 "#
 );
+typescript_serialize!(
+    citeproc::DocumentStats,
+    DocumentStats,
+    "DocumentStats",
+    r#"
+interface DocumentStats {
+    clusters: number;
+    cites: number;
+    bibEntries: number;
+}
+"#
+);
+typescript_serialize!(
+    citeproc::StyleDefaults,
+    StyleDefaults,
+    "StyleDefaults",
+    r#"
+interface StyleDefaults {
+    /** The style's default-locale, e.g. "en-GB", or null */
+    defaultLocale: string | null;
+    pageRangeFormat: "chicago" | "expanded" | "minimal" | "minimal-two" | null;
+    demoteNonDroppingParticle: "never" | "sort-only" | "display-and-sort";
+}
+"#
+);
+typescript_serialize!(
+    csl::StyleClass,
+    StyleClass,
+    "StyleClass",
+    r#"
+type StyleClass = "note" | "in-text";
+"#
+);
+typescript_serialize!(
+    Vec<(citeproc::prelude::SmartString, usize, csl::Atom)>,
+    UnresolvedCites,
+    "UnresolvedCite[]",
+    r#"
+/** A cluster id, the index of the cite within that cluster, and the reference id it failed to find. */
+type UnresolvedCite = [string, number, string];
+"#
+);
+typescript_serialize!(
+    citeproc::ClusterPreview,
+    ClusterPreview,
+    "ClusterPreview",
+    r#"
+type DisambiguationKind = "addNames" | "addGivenName" | "addYearSuffix" | "conditionals";
+interface ClusterPreview {
+    output: string;
+    /** Each kind of disambiguation that changed a previewed cite. Empty if none were ambiguous. */
+    disambiguation: DisambiguationKind[];
+}
+"#
+);
+typescript_serialize!(
+    fnv::FnvHashMap<csl::Atom, Vec<u32>>,
+    BackReferences,
+    "BackReferences",
+    r#"
+/** Maps reference ids to the note numbers they are cited in, ascending. */
+type BackReferences = Record<string, number[]>;
+"#
+);
+typescript_serialize!(
+    Vec<(csl::Atom, String)>,
+    UnknownReferenceTypes,
+    "UnknownReferenceType[]",
+    r#"
+/** A reference id, and the CSL-JSON type it had that citeproc-rs did not recognise. */
+type UnknownReferenceType = [string, string];
+"#
+);
+typescript_serialize!(
+    fnv::FnvHashMap<citeproc::prelude::SmartString, Vec<csl::Atom>>,
+    DisambiguationIndex,
+    "DisambiguationIndex",
+    r#"
+/** Keys are disambiguation tokens, values are the ids of the references that can produce them. */
+type DisambiguationIndex = Record<string, string[]>;
+"#
+);
+typescript_serialize!(
+    Vec<String>,
+    CitePositions,
+    "CitePosition[]",
+    r#"
+type CitePosition = "first" | "ibid" | "ibid-with-locator" | "subsequent" | "near-note"
+    | "ibid-near" | "ibid-with-locator-near" | "far-note";
+"#
+);
 typescript_serialize!(Vec<String>, StringArray, "string[]");
 
 typescript_serialize!(
@@ -263,6 +374,10 @@ export type Cite = {
     id: string;
     prefix?: string;
     suffix?: string;
+    /** CSL-M's locator-extra. Ignored without a locator. */
+    locatorExtra?: string;
+    /** A CSL-JSON date, like { "date-parts": [[2020, 5, 1]] }. Ignored without a locator. */
+    locatorDate?: any;
 } & Partial<CiteLocator> & CiteMode;
 
 export type ClusterMode
@@ -310,6 +425,32 @@ typescript_serialize!(
 "#
 );
 
+typescript_serialize!(
+    Vec<citeproc::string_id::ClusterPosition>,
+    ClusterOrder,
+    "ClusterPosition[]"
+);
+
+typescript_serialize!(
+    Vec<(String, Vec<String>)>,
+    BibliographySortKeys,
+    "[string, string[]][]"
+);
+
+typescript_serialize!(
+    citeproc::string_id::DocumentState,
+    DocumentState,
+    "DocumentState",
+    r#"
+interface DocumentState {
+    references: Reference[];
+    clusters: Cluster[];
+    clusterOrder: ClusterPosition[];
+    uncited: IncludeUncited;
+}
+"#
+);
+
 // Some misc date objects, mostly made redundant by CSL 1.1 EDTF
 #[wasm_bindgen(typescript_custom_section)]
 const TS_APPEND_CONTENT_2: &'static str = r#"